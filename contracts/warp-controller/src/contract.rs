@@ -1,17 +1,18 @@
-use crate::execute::{account, controller, job};
+use crate::execute::{account, controller, job, template};
 use crate::query::condition;
 
-use crate::state::{ACCOUNTS, CONFIG, FINISHED_JOBS, PENDING_JOBS};
+use crate::state::{ACCOUNTS, CONFIG, FINISHED_JOBS, LEGACY_CONFIG, PENDING_JOBS};
+use crate::util::validation::validate_reward_denoms;
 use crate::{query, state::STATE, ContractError};
 use cosmwasm_std::{
-    entry_point, to_binary, Attribute, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
-    StdError, StdResult, SubMsgResult, Uint64,
+    entry_point, to_binary, Attribute, BankMsg, Binary, Coin, Deps, DepsMut, Env, Event,
+    MessageInfo, Reply, Response, StdError, StdResult, SubMsgResult, Uint128, Uint64,
 };
 use warp_protocol::controller::account::Account;
 use warp_protocol::controller::controller::{
-    Config, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, State,
+    Config, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RewardDenom, State,
 };
-use warp_protocol::controller::job::{Job, JobStatus};
+use warp_protocol::controller::job::{Job, JobStatus, Recurrence, LEGACY_REWARD_DENOM};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -22,6 +23,7 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     let state = State {
         current_job_id: Uint64::zero() + Uint64::one() * Uint64::pow(Uint64::new(1), 100),
+        current_template_id: Some(Uint64::one()),
     };
 
     let config = Config {
@@ -29,11 +31,17 @@ pub fn instantiate(
             .api
             .addr_validate(&msg.owner.unwrap_or(info.sender.to_string()))?,
         warp_account_code_id: msg.warp_account_code_id,
-        minimum_reward: msg.minimum_reward,
+        reward_denoms: msg.reward_denoms,
         creation_fee_percentage: msg.creation_fee,
         cancellation_fee_percentage: msg.cancellation_fee,
+        max_condition_depth: msg.max_condition_depth,
+        max_condition_queries: msg.max_condition_queries,
+        max_condition_size: msg.max_condition_size,
+        cleanup_tip_percentage: msg.cleanup_tip,
     };
 
+    validate_reward_denoms(&config.reward_denoms)?;
+
     if config.creation_fee_percentage.u128() > 100 {
         return Err(ContractError::CreationFeeTooHigh {});
     }
@@ -42,6 +50,10 @@ pub fn instantiate(
         return Err(ContractError::CancellationFeeTooHigh {});
     }
 
+    if config.cleanup_tip_percentage.map_or(0, |tip| tip.u128()) > 100 {
+        return Err(ContractError::CleanupTipTooHigh {});
+    }
+
     STATE.save(deps.storage, &state)?;
     CONFIG.save(deps.storage, &config)?;
 
@@ -60,9 +72,17 @@ pub fn execute(
         ExecuteMsg::DeleteJob(data) => job::delete_job(deps, env, info, data),
         ExecuteMsg::UpdateJob(data) => job::update_job(deps, env, info, data),
         ExecuteMsg::ExecuteJob(data) => job::execute_job(deps, env, info, data),
+        ExecuteMsg::CleanupJob(data) => job::cleanup_job(deps, env, info, data),
 
         ExecuteMsg::CreateAccount(_) => account::create_account(deps, env, info),
 
+        ExecuteMsg::SubmitTemplate(data) => template::submit_template(deps, env, info, data),
+        ExecuteMsg::EditTemplate(data) => template::edit_template(deps, env, info, data),
+        ExecuteMsg::DeleteTemplate(data) => template::delete_template(deps, env, info, data),
+        ExecuteMsg::CreateJobFromTemplate(data) => {
+            template::create_job_from_template(deps, env, info, data)
+        }
+
         ExecuteMsg::UpdateConfig(data) => controller::update_config(deps, env, info, data),
     }
 }
@@ -78,6 +98,12 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::QueryResolveCondition(data) => {
             to_binary(&condition::query_resolve_condition(deps, env, data)?)
         }
+        QueryMsg::QueryExplainJobCondition(data) => {
+            to_binary(&condition::query_explain_job_condition(deps, env, data)?)
+        }
+        QueryMsg::QueryExplainCondition(data) => {
+            to_binary(&condition::query_explain_condition(deps, env, data)?)
+        }
 
         QueryMsg::SimulateQuery(data) => {
             to_binary(&query::controller::query_simulate_query(deps, env, data)?)
@@ -88,6 +114,13 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query::account::query_accounts(deps, env, data)?)
         }
 
+        QueryMsg::QueryTemplate(data) => {
+            to_binary(&query::template::query_template(deps, env, data)?)
+        }
+        QueryMsg::QueryTemplates(data) => {
+            to_binary(&query::template::query_templates(deps, env, data)?)
+        }
+
         QueryMsg::QueryConfig(data) => {
             to_binary(&query::controller::query_config(deps, env, data)?)
         }
@@ -96,22 +129,31 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    CONFIG.save(
-        deps.storage,
-        &Config {
-            owner: config.owner,
-            warp_account_code_id: config.warp_account_code_id,
-            minimum_reward: config.minimum_reward,
-            creation_fee_percentage: config.creation_fee_percentage,
-            cancellation_fee_percentage: config.cancellation_fee_percentage,
-        },
-    )?;
+    // only a config stored before reward denoms needs converting
+    if let Ok(legacy) = LEGACY_CONFIG.load(deps.storage) {
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                owner: legacy.owner,
+                warp_account_code_id: legacy.warp_account_code_id,
+                reward_denoms: vec![RewardDenom {
+                    denom: LEGACY_REWARD_DENOM.to_string(),
+                    minimum_reward: legacy.minimum_reward,
+                }],
+                creation_fee_percentage: legacy.creation_fee_percentage,
+                cancellation_fee_percentage: legacy.cancellation_fee_percentage,
+                max_condition_depth: legacy.max_condition_depth,
+                max_condition_queries: legacy.max_condition_queries,
+                max_condition_size: legacy.max_condition_size,
+                cleanup_tip_percentage: legacy.cleanup_tip_percentage,
+            },
+        )?;
+    }
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         //account creation
         0 => {
@@ -131,16 +173,16 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
             let owner = event
                 .attributes
                 .iter()
+                .find(|&attr| attr.key == "owner")
                 .cloned()
-                .find(|attr| attr.key == "owner")
                 .ok_or_else(|| StdError::generic_err("cannot find `owner` attribute"))?
                 .value;
 
             let address = event
                 .attributes
                 .iter()
+                .find(|&attr| attr.key == "contract_addr")
                 .cloned()
-                .find(|attr| attr.key == "contract_addr")
                 .ok_or_else(|| StdError::generic_err("cannot find `contract_addr` attribute"))?
                 .value;
 
@@ -163,37 +205,100 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
         }
         //job execution
         _ => {
-            let new_status = match msg.result {
+            let job = PENDING_JOBS().load(deps.storage, msg.id)?;
+
+            // the keeper was paid the reward out of the budget on execution
+            let executions = job.executions.unwrap_or_default() + Uint64::one();
+            let recurrence = job.recurrence.map(|recurrence| Recurrence {
+                budget: recurrence.budget - job.reward,
+                ..recurrence
+            });
+            let finished = match &recurrence {
+                None => true,
+                Some(recurrence) => {
+                    recurrence.budget < job.reward
+                        || recurrence
+                            .max_executions
+                            .is_some_and(|max_executions| executions >= max_executions)
+                }
+            };
+            let execution_status = match msg.result {
                 SubMsgResult::Ok(_) => JobStatus::Executed,
                 SubMsgResult::Err(_) => JobStatus::Failed,
             };
+            let new_status = match finished {
+                true => execution_status.clone(),
+                false => JobStatus::Pending,
+            };
 
-            let job = PENDING_JOBS().load(deps.storage, msg.id)?;
-            PENDING_JOBS().remove(deps.storage, msg.id)?;
-
-            let _new_job = FINISHED_JOBS().update(deps.storage, msg.id, |j| match j {
-                None => Ok(Job {
-                    id: job.id,
-                    owner: job.owner,
-                    last_update_time: job.last_update_time,
-                    name: job.name,
-                    status: new_status,
-                    condition: job.condition,
-                    msgs: job.msgs,
-                    reward: job.reward,
-                }),
-                Some(_) => Err(ContractError::JobAlreadyFinished {}),
-            })?;
+            let new_job = Job {
+                id: job.id,
+                owner: job.owner,
+                last_update_time: job.last_update_time,
+                last_update_height: job.last_update_height,
+                creation_time: job.creation_time,
+                creation_height: job.creation_height,
+                name: job.name,
+                status: new_status,
+                condition: job.condition,
+                msgs: job.msgs,
+                reward: job.reward,
+                template: job.template,
+                snapshots: job.snapshots,
+                selectors: job.selectors,
+                signer: job.signer,
+                recurrence,
+                executions: Some(executions),
+                last_execution_time: Some(Uint64::new(env.block.time.seconds())),
+                expiry: job.expiry,
+                reward_denom: job.reward_denom,
+            };
+
+            let mut refund_msgs = vec![];
+            if finished {
+                PENDING_JOBS().remove(deps.storage, msg.id)?;
+                FINISHED_JOBS().update(deps.storage, msg.id, |j| match j {
+                    None => Ok(new_job.clone()),
+                    Some(_) => Err(ContractError::JobAlreadyFinished {}),
+                })?;
+
+                // what is left of a recurring job's budget goes back to its owner
+                let refund = new_job
+                    .recurrence
+                    .as_ref()
+                    .map_or(Uint128::zero(), |recurrence| recurrence.budget);
+                if !refund.is_zero() {
+                    let account = ACCOUNTS().load(deps.storage, new_job.owner.clone())?;
+                    refund_msgs.push(BankMsg::Send {
+                        to_address: account.account.to_string(),
+                        amount: vec![Coin::new(refund.u128(), new_job.denom())],
+                    });
+                }
+            } else {
+                PENDING_JOBS().save(deps.storage, msg.id, &new_job)?;
+            }
 
             let res_attrs = match msg.result {
                 SubMsgResult::Err(e) => vec![Attribute::new("transaction_error", e)],
                 _ => vec![],
             };
 
+            let execution_event = Event::new("job_execution")
+                .add_attribute("job_id", new_job.id)
+                .add_attribute("execution", executions)
+                .add_attribute(
+                    "execution_status",
+                    serde_json_wasm::to_string(&execution_status)?,
+                )
+                .add_attribute("job_reward", new_job.reward)
+                .add_attribute("job_funds", new_job.funds());
+
             Ok(Response::new()
+                .add_messages(refund_msgs)
+                .add_event(execution_event)
                 .add_attribute("action", "execute_reply")
-                .add_attribute("job_id", job.id)
-                .add_attribute("job_status", serde_json_wasm::to_string(&job.status)?)
+                .add_attribute("job_id", new_job.id)
+                .add_attribute("job_status", serde_json_wasm::to_string(&new_job.status)?)
                 .add_attributes(res_attrs)) //todo: trying no attrs
         }
     }
@@ -1,7 +1,8 @@
 use crate::ContractError::{CustomError, DecodeError, DeserializationError, SerializationError};
-use cosmwasm_std::{DivideByZeroError, StdError};
+use cosmwasm_std::{DivideByZeroError, StdError, Uint64};
 use std::num::ParseIntError;
 use thiserror::Error;
+use warp_protocol::controller::path::PathError;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -17,6 +18,12 @@ pub enum ContractError {
     #[error("Reward provided is smaller than minimum")]
     RewardTooSmall {},
 
+    #[error("Reward denom is not accepted")]
+    RewardDenomNotAccepted {},
+
+    #[error("Reward denoms must be a non-empty list of distinct denoms")]
+    InvalidRewardDenoms {},
+
     #[error("Name must be at least 1 character long")]
     NameTooShort {},
 
@@ -50,9 +57,27 @@ pub enum ContractError {
     #[error("Job not active")]
     JobNotActive {},
 
+    #[error("Recurring jobs need a max execution count or a min interval, neither of them zero")]
+    InvalidRecurrence {},
+
+    #[error("Budget must cover at least one reward")]
+    BudgetTooSmall {},
+
+    #[error("Recurring job executed before its min interval passed")]
+    ExecutionTooSoon {},
+
+    #[error("Job expired")]
+    JobExpired {},
+
+    #[error("Job has not expired")]
+    JobNotExpired {},
+
     #[error("Cancellation fee too high")]
     CancellationFeeTooHigh {},
 
+    #[error("Cleanup tip too high")]
+    CleanupTipTooHigh {},
+
     #[error("Creation fee too high")]
     CreationFeeTooHigh {},
 
@@ -71,6 +96,117 @@ pub enum ContractError {
 
     #[error("Error resolving JSON path")]
     ResolveError {},
+
+    #[error("Invalid selector: {0}")]
+    InvalidSelector(#[from] PathError),
+
+    #[error("Division by zero")]
+    DivisionByZero {},
+
+    #[error("Arithmetic overflow")]
+    Overflow {},
+
+    #[error("Arithmetic underflow")]
+    Underflow {},
+
+    #[error("Cannot cast a negative value to an unsigned type")]
+    SignLoss {},
+
+    #[error("Job anchor is not available for this condition")]
+    AnchorUnavailable {},
+
+    #[error("Element values can only be used inside an array condition")]
+    ElementUnavailable {},
+
+    #[error("Cannot aggregate an empty array")]
+    EmptyArray {},
+
+    #[error("Exponent must be a whole number between 0 and 4294967295")]
+    InvalidExponent {},
+
+    #[error("Query default must be valid JSON")]
+    InvalidQueryDefault {},
+
+    #[error("Query message must be valid JSON")]
+    InvalidQueryMsg {},
+
+    #[error("Storage key must have a key part for maps and parts of at most 65535 bytes")]
+    InvalidStorageKey {},
+
+    #[error("Condition reads signed data but no valid signed data was attached")]
+    SignedDataMissing {},
+
+    #[error("Signed data attached to a job without a signer")]
+    UnexpectedSignedData {},
+
+    #[error("Condition reads signed data but the job has no signer")]
+    SignerRequired {},
+
+    #[error("Invalid signer public key")]
+    InvalidPublicKey {},
+
+    #[error("Invalid signature of the signed data")]
+    InvalidSignature {},
+
+    #[error("Signed data payload must be JSON with the job id, a timestamp and data")]
+    InvalidSignedData {},
+
+    #[error("Signed data timestamp is outside of the signer's max age")]
+    StaleSignedData {},
+
+    #[error("Value at selector '{selector}' is not a valid {expected}")]
+    InvalidSelectorValue { selector: String, expected: String },
+
+    #[error("And/Or conditions must contain at least one condition")]
+    EmptyCondition {},
+
+    #[error("Condition is nested deeper than the maximum depth of {max_depth}")]
+    ConditionTooDeep { max_depth: u64 },
+
+    #[error("Condition contains more than the maximum of {max_queries} queries")]
+    TooManyQueries { max_queries: u64 },
+
+    #[error("Condition size of {size} bytes exceeds the maximum of {max_size} bytes")]
+    ConditionTooLarge { size: u64, max_size: u64 },
+
+    #[error("Schedule period must be greater than zero")]
+    InvalidPeriod {},
+
+    #[error("Job {job_id} the condition depends on does not exist")]
+    DependencyDoesNotExist { job_id: Uint64 },
+
+    #[error("Job {job_id} would depend on itself")]
+    DependencyCycle { job_id: Uint64 },
+
+    #[error("Snapshot queries must be named")]
+    UnnamedSnapshotQuery {},
+
+    #[error("Snapshot query '{name}' shares its name with a different query")]
+    ConflictingSnapshotQuery { name: String },
+
+    #[error("Query name '{name}' is used for different queries")]
+    ConflictingNamedQuery { name: String },
+
+    #[error("Template does not exist")]
+    TemplateDoesNotExist {},
+
+    #[error("Template variable '{name}' is declared more than once")]
+    DuplicateTemplateVar { name: String },
+
+    #[error("Template uses undeclared variable '{name}'")]
+    UndefinedTemplateVar { name: String },
+
+    #[error("Template param '{name}' is given more than once")]
+    DuplicateTemplateParam { name: String },
+
+    #[error("Template param '{name}' is missing")]
+    MissingTemplateParam { name: String },
+
+    #[error("Template has no variable '{name}'")]
+    UnknownTemplateParam { name: String },
+
+    #[error("Template param '{name}' is not a valid {kind}")]
+    InvalidTemplateParam { name: String, kind: String },
 }
 
 impl From<serde_json_wasm::de::Error> for ContractError {
@@ -113,8 +249,6 @@ impl From<ParseIntError> for ContractError {
 
 impl From<DivideByZeroError> for ContractError {
     fn from(_: DivideByZeroError) -> Self {
-        CustomError {
-            val: "ERROR: Division by zero".to_string(),
-        }
+        ContractError::DivisionByZero {}
     }
 }
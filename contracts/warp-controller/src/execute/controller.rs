@@ -1,4 +1,6 @@
+use crate::execute::job::cleanup_tip_percentage;
 use crate::state::CONFIG;
+use crate::util::validation::{validate_reward_denoms, ConditionLimits};
 use crate::ContractError;
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 use warp_protocol::controller::controller::UpdateConfigMsg;
@@ -18,13 +20,21 @@ pub fn update_config(
         None => config.owner,
         Some(data) => deps.api.addr_validate(data.as_str())?,
     };
-    config.minimum_reward = data.minimum_reward.unwrap_or(config.minimum_reward);
+    config.reward_denoms = data.reward_denoms.unwrap_or(config.reward_denoms);
     config.creation_fee_percentage = data
         .creation_fee_percentage
         .unwrap_or(config.creation_fee_percentage);
     config.cancellation_fee_percentage = data
         .cancellation_fee_percentage
         .unwrap_or(config.cancellation_fee_percentage);
+    config.max_condition_depth = data.max_condition_depth.or(config.max_condition_depth);
+    config.max_condition_queries = data.max_condition_queries.or(config.max_condition_queries);
+    config.max_condition_size = data.max_condition_size.or(config.max_condition_size);
+    config.cleanup_tip_percentage = data
+        .cleanup_tip_percentage
+        .or(config.cleanup_tip_percentage);
+
+    validate_reward_denoms(&config.reward_denoms)?;
 
     if config.creation_fee_percentage.u128() > 100 {
         return Err(ContractError::CreationFeeTooHigh {});
@@ -34,12 +44,22 @@ pub fn update_config(
         return Err(ContractError::CancellationFeeTooHigh {});
     }
 
+    if config.cleanup_tip_percentage.map_or(0, |tip| tip.u128()) > 100 {
+        return Err(ContractError::CleanupTipTooHigh {});
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
+    let limits = ConditionLimits::from_config(&config);
+    let cleanup_tip = cleanup_tip_percentage(&config);
+
     Ok(Response::new()
         .add_attribute("action", "update_config")
         .add_attribute("config_owner", config.owner)
-        .add_attribute("config_minimum_reward", config.minimum_reward)
+        .add_attribute(
+            "config_reward_denoms",
+            serde_json_wasm::to_string(&config.reward_denoms)?,
+        )
         .add_attribute(
             "config_creation_fee_percentage",
             config.creation_fee_percentage,
@@ -47,5 +67,12 @@ pub fn update_config(
         .add_attribute(
             "config_cancellation_fee_percentage",
             config.cancellation_fee_percentage,
-        ))
+        )
+        .add_attribute("config_max_condition_depth", limits.max_depth.to_string())
+        .add_attribute(
+            "config_max_condition_queries",
+            limits.max_queries.to_string(),
+        )
+        .add_attribute("config_max_condition_size", limits.max_size.to_string())
+        .add_attribute("config_cleanup_tip_percentage", cleanup_tip))
 }
@@ -1,20 +1,37 @@
 use crate::state::{ACCOUNTS, CONFIG, FINISHED_JOBS, PENDING_JOBS, STATE};
-use crate::util::condition::resolve_cond;
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::dependency::validate_dependencies;
+use crate::util::normalize::normalize_condition;
+use crate::util::signed::{validate_signer, verify_signed_data};
+use crate::util::snapshot::capture_snapshots;
+use crate::util::validation::validate_condition;
 use crate::ContractError;
 use cosmwasm_std::{
-    to_binary, Attribute, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Record, ReplyOn,
-    Response, SubMsg, Uint128, Uint64, WasmMsg,
+    to_binary, Attribute, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, ReplyOn, Response,
+    SubMsg, Uint128, Uint64, WasmMsg,
 };
-use warp_protocol::controller::controller::State;
+use warp_protocol::controller::controller::{Config, State};
 use warp_protocol::controller::job::{
-    CreateJobMsg, DeleteJobMsg, ExecuteJobMsg, Job, JobStatus, UpdateJobMsg,
+    CleanupJobMsg, CreateJobMsg, DeleteJobMsg, ExecuteJobMsg, Expiry, Job, JobStatus, Recurrence,
+    UpdateJobMsg,
 };
+use warp_protocol::controller::template::JobTemplate;
 
 pub fn create_job(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     data: CreateJobMsg,
+) -> Result<Response, ContractError> {
+    create_job_from(deps, env, info, data, None)
+}
+
+pub fn create_job_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: CreateJobMsg,
+    template: Option<JobTemplate>,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
     let config = CONFIG.load(deps.storage)?;
@@ -23,14 +40,41 @@ pub fn create_job(
         return Err(ContractError::NameTooLong {});
     }
 
-    if data.name.len() < 1 {
+    if data.name.is_empty() {
         return Err(ContractError::NameTooShort {});
     }
 
-    if data.reward < config.minimum_reward || data.reward.is_zero() {
+    let reward_denom = match &data.reward_denom {
+        None => config.reward_denoms.first(),
+        Some(denom) => config
+            .reward_denoms
+            .iter()
+            .find(|reward_denom| &reward_denom.denom == denom),
+    }
+    .ok_or(ContractError::RewardDenomNotAccepted {})?;
+
+    if data.reward < reward_denom.minimum_reward || data.reward.is_zero() {
         return Err(ContractError::RewardTooSmall {});
     }
 
+    if let Some(recurrence) = &data.recurrence {
+        validate_recurrence(recurrence, data.reward)?;
+    }
+
+    if data
+        .expiry
+        .as_ref()
+        .is_some_and(|expiry| is_expired(expiry, &env))
+    {
+        return Err(ContractError::JobExpired {});
+    }
+
+    let selectors = validate_condition(&config, &data.condition)?;
+    validate_signer(&data.condition, data.signer.as_ref())?;
+    let condition = normalize_condition(deps.as_ref(), &env, data.condition);
+    validate_dependencies(deps.as_ref(), state.current_job_id, &condition)?;
+    let snapshots = capture_snapshots(deps.as_ref(), &env, &condition)?;
+
     let q = ACCOUNTS()
         .idx
         .account
@@ -39,7 +83,7 @@ pub fn create_job(
     let account = match q {
         None => ACCOUNTS()
             .load(deps.storage, info.sender.clone())
-            .map_err(|e| ContractError::AccountDoesNotExist {})?,
+            .map_err(|_| ContractError::AccountDoesNotExist {})?,
         Some(q) => q.1,
     };
 
@@ -53,11 +97,23 @@ pub fn create_job(
             id: state.current_job_id,
             owner: account.owner,
             last_update_time: Uint64::from(env.block.time.seconds()),
+            last_update_height: Some(Uint64::from(env.block.height)),
+            creation_time: Some(Uint64::from(env.block.time.seconds())),
+            creation_height: Some(Uint64::from(env.block.height)),
             name: data.name,
             status: JobStatus::Pending,
-            condition: data.condition.clone(),
+            condition: condition.clone(),
             msgs,
-            reward: data.reward.clone(),
+            reward: data.reward,
+            template,
+            snapshots,
+            selectors: Some(selectors),
+            signer: data.signer,
+            recurrence: data.recurrence,
+            executions: Some(Uint64::zero()),
+            last_execution_time: None,
+            expiry: data.expiry,
+            reward_denom: Some(reward_denom.denom.clone()),
         }),
         Some(_) => Err(ContractError::JobAlreadyExists {}),
     })?;
@@ -66,11 +122,12 @@ pub fn create_job(
         deps.storage,
         &State {
             current_job_id: state.current_job_id.saturating_add(Uint64::new(1)),
+            current_template_id: state.current_template_id,
         },
     )?;
 
     //assume reward.amount == warp token allowance
-    let fee = data.reward * config.creation_fee_percentage / Uint128::new(100);
+    let fee = job.funds() * config.creation_fee_percentage / Uint128::new(100);
 
     let reward_send_msgs = vec![
         //send reward to controller
@@ -79,7 +136,7 @@ pub fn create_job(
             msg: to_binary(&warp_protocol::account::account::ExecuteMsg {
                 msgs: vec![CosmosMsg::Bank(BankMsg::Send {
                     to_address: env.contract.address.to_string(),
-                    amount: vec![Coin::new((data.reward + fee).u128(), "uluna")],
+                    amount: vec![Coin::new((job.funds() + fee).u128(), job.denom())],
                 })],
             })?,
             funds: vec![],
@@ -96,6 +153,7 @@ pub fn create_job(
         .add_attribute("job_condition", serde_json_wasm::to_string(&job.condition)?)
         .add_attribute("job_msgs", serde_json_wasm::to_string(&job.msgs)?)
         .add_attribute("job_reward", job.reward)
+        .add_attribute("job_reward_denom", &reward_denom.denom)
         .add_attribute("job_creation_fee", fee)
         .add_attribute("job_last_updated_time", job.last_update_time))
 }
@@ -118,6 +176,8 @@ pub fn delete_job(
     }
 
     let account = ACCOUNTS().load(deps.storage, info.sender.clone())?;
+    let funds = job.funds();
+    let denom = job.denom().to_string();
 
     PENDING_JOBS().remove(deps.storage, data.id.u64())?;
     let _new_job = FINISHED_JOBS().update(deps.storage, data.id.u64(), |h| match h {
@@ -125,22 +185,34 @@ pub fn delete_job(
             id: job.id,
             owner: job.owner,
             last_update_time: job.last_update_time,
+            last_update_height: job.last_update_height,
+            creation_time: job.creation_time,
+            creation_height: job.creation_height,
             name: job.name,
             status: JobStatus::Cancelled,
             condition: job.condition,
             msgs: job.msgs,
-            reward: job.reward.clone(),
+            reward: job.reward,
+            template: job.template,
+            snapshots: job.snapshots,
+            selectors: job.selectors,
+            signer: job.signer,
+            recurrence: job.recurrence,
+            executions: job.executions,
+            last_execution_time: job.last_execution_time,
+            expiry: job.expiry,
+            reward_denom: job.reward_denom,
         }),
         Some(_job) => Err(ContractError::JobAlreadyFinished {}),
     })?;
 
-    let fee = job.reward * config.cancellation_fee_percentage / Uint128::new(100);
+    let fee = funds * config.cancellation_fee_percentage / Uint128::new(100);
 
     let cw20_send_msgs = vec![
         //send reward minus fee back to account
         BankMsg::Send {
             to_address: account.account.to_string(),
-            amount: vec![Coin::new((job.reward - fee).u128(), "uluna")],
+            amount: vec![Coin::new((funds - fee).u128(), denom)],
         },
     ];
 
@@ -165,6 +237,25 @@ pub fn update_job(
         return Err(ContractError::Unauthorized {});
     }
 
+    let selectors = match &data.condition {
+        Some(condition) => {
+            let selectors = validate_condition(&config, condition)?;
+            validate_signer(condition, job.signer.as_ref())?;
+            Some(selectors)
+        }
+        None => None,
+    };
+    let condition = data
+        .condition
+        .map(|condition| normalize_condition(deps.as_ref(), &env, condition));
+    let snapshots = match &condition {
+        Some(condition) => {
+            validate_dependencies(deps.as_ref(), job.id, condition)?;
+            Some(capture_snapshots(deps.as_ref(), &env, condition)?)
+        }
+        None => None,
+    };
+
     let account = ACCOUNTS().load(deps.storage, info.sender.clone())?;
 
     let added_reward = data.added_reward.unwrap_or(Uint128::new(0));
@@ -179,11 +270,33 @@ pub fn update_job(
             } else {
                 job.last_update_time
             },
+            last_update_height: if !added_reward.is_zero() {
+                Some(Uint64::new(env.block.height))
+            } else {
+                job.last_update_height
+            },
+            creation_time: job.creation_time,
+            creation_height: job.creation_height,
             name: data.name.unwrap_or(job.name),
             status: job.status,
-            condition: data.condition.unwrap_or(job.condition),
+            condition: condition.unwrap_or(job.condition),
             msgs: job.msgs,
-            reward: job.reward + added_reward,
+            reward: match job.recurrence {
+                Some(_) => job.reward,
+                None => job.reward + added_reward,
+            },
+            template: job.template,
+            snapshots: snapshots.unwrap_or(job.snapshots),
+            selectors: selectors.or(job.selectors),
+            signer: job.signer,
+            recurrence: job.recurrence.map(|recurrence| Recurrence {
+                budget: recurrence.budget + added_reward,
+                ..recurrence
+            }),
+            executions: job.executions,
+            last_execution_time: job.last_execution_time,
+            expiry: job.expiry,
+            reward_denom: job.reward_denom,
         }),
     })?;
 
@@ -201,7 +314,7 @@ pub fn update_job(
             msg: to_binary(&warp_protocol::account::account::ExecuteMsg {
                 msgs: vec![CosmosMsg::Bank(BankMsg::Send {
                     to_address: env.contract.address.to_string(),
-                    amount: vec![Coin::new((added_reward + fee).u128(), "uluna")],
+                    amount: vec![Coin::new((added_reward + fee).u128(), job.denom())],
                 })],
             })?,
             funds: vec![],
@@ -241,15 +354,47 @@ pub fn execute_job(
         return Err(ContractError::JobNotActive {});
     }
 
-    let resolution = resolve_cond(deps.as_ref(), env.clone(), job.condition.clone());
+    if job
+        .expiry
+        .as_ref()
+        .is_some_and(|expiry| is_expired(expiry, &env))
+    {
+        return Err(ContractError::JobExpired {});
+    }
+
+    let next_execution_time = job
+        .recurrence
+        .as_ref()
+        .and_then(|recurrence| recurrence.min_interval)
+        .zip(job.last_execution_time)
+        .map(|(interval, last)| last.saturating_add(interval));
+    if next_execution_time.is_some_and(|time| env.block.time.seconds() < time.u64()) {
+        return Err(ContractError::ExecutionTooSoon {});
+    }
+
+    // rejected rather than failing the job, so keepers cannot fail jobs with bad payloads
+    let mut ctx = ResolveContext::with_job(job.clone());
+    ctx.signed_data = verify_signed_data(deps.api, &env, &job, data.signed_data)?;
+
+    let resolution = resolve_cond(deps.as_ref(), env.clone(), &mut ctx, job.condition.clone());
 
     let mut attrs = vec![];
 
     let mut submsgs = vec![];
 
+    let mut refund_msgs = vec![];
+
     if resolution.is_err() {
         attrs.push(Attribute::new("job_condition_status", "invalid"));
         let job = PENDING_JOBS().load(deps.storage, data.id.u64())?;
+        // the rest of a recurring job's budget goes back to its owner
+        let refund = job.funds() - job.reward;
+        if !refund.is_zero() {
+            refund_msgs.push(BankMsg::Send {
+                to_address: account.account.to_string(),
+                amount: vec![Coin::new(refund.u128(), job.denom())],
+            });
+        }
         FINISHED_JOBS().save(
             deps.storage,
             data.id.u64(),
@@ -257,11 +402,23 @@ pub fn execute_job(
                 id: job.id,
                 owner: job.owner,
                 last_update_time: job.last_update_time,
+                last_update_height: job.last_update_height,
+                creation_time: job.creation_time,
+                creation_height: job.creation_height,
                 name: job.name,
                 status: JobStatus::Failed,
                 condition: job.condition,
                 msgs: job.msgs,
                 reward: job.reward,
+                template: job.template,
+                snapshots: job.snapshots,
+                selectors: job.selectors,
+                signer: job.signer,
+                recurrence: job.recurrence,
+                executions: job.executions,
+                last_execution_time: job.last_execution_time,
+                expiry: job.expiry,
+                reward_denom: job.reward_denom,
             },
         )?;
         PENDING_JOBS().remove(deps.storage, data.id.u64())?;
@@ -286,14 +443,121 @@ pub fn execute_job(
 
     let reward_msg = BankMsg::Send {
         to_address: keeper_account.account.to_string(),
-        amount: vec![Coin::new(job.reward.u128(), "uluna")],
+        amount: vec![Coin::new(job.reward.u128(), job.denom())],
     };
 
     Ok(Response::new()
         .add_submessages(submsgs)
         .add_message(reward_msg)
+        .add_messages(refund_msgs)
         .add_attribute("action", "execute_job")
         .add_attribute("executor", info.sender)
         .add_attribute("job_reward", job.reward)
         .add_attributes(attrs))
 }
+
+pub fn cleanup_job(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: CleanupJobMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let job = PENDING_JOBS().load(deps.storage, data.id.u64())?;
+
+    if !job
+        .expiry
+        .as_ref()
+        .is_some_and(|expiry| is_expired(expiry, &env))
+    {
+        return Err(ContractError::JobNotExpired {});
+    }
+
+    let account = ACCOUNTS().load(deps.storage, job.owner.clone())?;
+    let funds = job.funds();
+    let tip = funds * cleanup_tip_percentage(&config) / Uint128::new(100);
+
+    PENDING_JOBS().remove(deps.storage, data.id.u64())?;
+    let job = FINISHED_JOBS().update(deps.storage, data.id.u64(), |h| match h {
+        None => Ok(Job {
+            id: job.id,
+            owner: job.owner,
+            last_update_time: job.last_update_time,
+            last_update_height: job.last_update_height,
+            creation_time: job.creation_time,
+            creation_height: job.creation_height,
+            name: job.name,
+            status: JobStatus::Expired,
+            condition: job.condition,
+            msgs: job.msgs,
+            reward: job.reward,
+            template: job.template,
+            snapshots: job.snapshots,
+            selectors: job.selectors,
+            signer: job.signer,
+            recurrence: job.recurrence,
+            executions: job.executions,
+            last_execution_time: job.last_execution_time,
+            expiry: job.expiry,
+            reward_denom: job.reward_denom,
+        }),
+        Some(_job) => Err(ContractError::JobAlreadyFinished {}),
+    })?;
+
+    let mut msgs = vec![];
+    //send funds minus tip back to account
+    if funds > tip {
+        msgs.push(BankMsg::Send {
+            to_address: account.account.to_string(),
+            amount: vec![Coin::new((funds - tip).u128(), job.denom())],
+        });
+    }
+    if !tip.is_zero() {
+        msgs.push(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(tip.u128(), job.denom())],
+        });
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "cleanup_job")
+        .add_attribute("executor", info.sender)
+        .add_attribute("job_id", job.id)
+        .add_attribute("job_status", serde_json_wasm::to_string(&job.status)?)
+        .add_attribute("cleanup_tip", tip))
+}
+
+pub const DEFAULT_CLEANUP_TIP_PERCENTAGE: u128 = 1;
+
+pub fn cleanup_tip_percentage(config: &Config) -> Uint128 {
+    config
+        .cleanup_tip_percentage
+        .unwrap_or(Uint128::new(DEFAULT_CLEANUP_TIP_PERCENTAGE))
+}
+
+fn is_expired(expiry: &Expiry, env: &Env) -> bool {
+    match expiry {
+        Expiry::Time(time) => env.block.time.seconds() >= time.u64(),
+        Expiry::BlockHeight(height) => env.block.height >= height.u64(),
+    }
+}
+
+fn validate_recurrence(recurrence: &Recurrence, reward: Uint128) -> Result<(), ContractError> {
+    // without a count or an interval a job could run out its budget in a single block
+    if recurrence.max_executions.is_none() && recurrence.min_interval.is_none() {
+        return Err(ContractError::InvalidRecurrence {});
+    }
+
+    if recurrence.max_executions == Some(Uint64::zero())
+        || recurrence.min_interval == Some(Uint64::zero())
+    {
+        return Err(ContractError::InvalidRecurrence {});
+    }
+
+    if recurrence.budget < reward {
+        return Err(ContractError::BudgetTooSmall {});
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
 pub(crate) mod account;
 pub(crate) mod controller;
 pub(crate) mod job;
+pub(crate) mod template;
@@ -0,0 +1,179 @@
+use crate::execute::job::create_job_from;
+use crate::state::{CONFIG, STATE, TEMPLATES, TEMPLATE_VERSIONS};
+use crate::util::template::{fill_template, validate_template};
+use crate::ContractError;
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Storage, Uint64};
+use warp_protocol::controller::controller::State;
+use warp_protocol::controller::job::CreateJobMsg;
+use warp_protocol::controller::template::{
+    CreateJobFromTemplateMsg, DeleteTemplateMsg, EditTemplateMsg, JobTemplate, SubmitTemplateMsg,
+    Template,
+};
+
+pub fn submit_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SubmitTemplateMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    validate_name(&data.name)?;
+    validate_template(&env, &config, &data.condition, &data.msgs, &data.vars)?;
+
+    let id = state.current_template_id.unwrap_or_else(Uint64::one);
+    let template = Template {
+        id,
+        owner: info.sender,
+        version: Uint64::one(),
+        name: data.name,
+        condition: data.condition,
+        msgs: data.msgs,
+        vars: data.vars,
+    };
+    save_template(deps.storage, &template)?;
+
+    STATE.save(
+        deps.storage,
+        &State {
+            current_job_id: state.current_job_id,
+            current_template_id: Some(id.saturating_add(Uint64::one())),
+        },
+    )?;
+
+    template_response("submit_template", &template)
+}
+
+pub fn edit_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: EditTemplateMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let template = TEMPLATES()
+        .may_load(deps.storage, data.id.u64())?
+        .ok_or(ContractError::TemplateDoesNotExist {})?;
+
+    if info.sender != template.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let template = Template {
+        id: template.id,
+        owner: template.owner,
+        version: template.version.saturating_add(Uint64::one()),
+        name: data.name.unwrap_or(template.name),
+        condition: data.condition.unwrap_or(template.condition),
+        msgs: data.msgs.unwrap_or(template.msgs),
+        vars: data.vars.unwrap_or(template.vars),
+    };
+
+    validate_name(&template.name)?;
+    validate_template(
+        &env,
+        &config,
+        &template.condition,
+        &template.msgs,
+        &template.vars,
+    )?;
+    save_template(deps.storage, &template)?;
+
+    template_response("edit_template", &template)
+}
+
+pub fn delete_template(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data: DeleteTemplateMsg,
+) -> Result<Response, ContractError> {
+    let template = TEMPLATES()
+        .may_load(deps.storage, data.id.u64())?
+        .ok_or(ContractError::TemplateDoesNotExist {})?;
+
+    if info.sender != template.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // versions are kept for the jobs created from them
+    TEMPLATES().remove(deps.storage, data.id.u64())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "delete_template")
+        .add_attribute("template_id", template.id))
+}
+
+pub fn create_job_from_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: CreateJobFromTemplateMsg,
+) -> Result<Response, ContractError> {
+    // a kept version can still be used once the template is deleted
+    let template = match data.template_version {
+        None => TEMPLATES().may_load(deps.storage, data.template_id.u64())?,
+        Some(version) => {
+            TEMPLATE_VERSIONS.may_load(deps.storage, (data.template_id.u64(), version.u64()))?
+        }
+    }
+    .ok_or(ContractError::TemplateDoesNotExist {})?;
+
+    let (condition, msgs) = fill_template(deps.api, &template, &data.params)?;
+    let job = CreateJobMsg {
+        name: data.name,
+        condition: serde_json_wasm::from_str(&condition)?,
+        msgs,
+        reward: data.reward,
+        signer: None,
+        recurrence: None,
+        expiry: None,
+        reward_denom: data.reward_denom,
+    };
+
+    create_job_from(
+        deps,
+        env,
+        info,
+        job,
+        Some(JobTemplate {
+            id: template.id,
+            version: template.version,
+        }),
+    )
+}
+
+fn validate_name(name: &str) -> Result<(), ContractError> {
+    if name.len() > 140 {
+        return Err(ContractError::NameTooLong {});
+    }
+
+    if name.is_empty() {
+        return Err(ContractError::NameTooShort {});
+    }
+
+    Ok(())
+}
+
+fn save_template(storage: &mut dyn Storage, template: &Template) -> Result<(), ContractError> {
+    TEMPLATES().save(storage, template.id.u64(), template)?;
+    TEMPLATE_VERSIONS.save(
+        storage,
+        (template.id.u64(), template.version.u64()),
+        template,
+    )?;
+    Ok(())
+}
+
+fn template_response(action: &str, template: &Template) -> Result<Response, ContractError> {
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("template_id", template.id)
+        .add_attribute("template_version", template.version)
+        .add_attribute("template_owner", template.owner.clone())
+        .add_attribute("template_name", template.name.clone())
+        .add_attribute("template_condition", template.condition.clone())
+        .add_attribute("template_msgs", serde_json_wasm::to_string(&template.msgs)?)
+        .add_attribute("template_vars", serde_json_wasm::to_string(&template.vars)?))
+}
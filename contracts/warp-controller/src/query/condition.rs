@@ -1,18 +1,19 @@
 use crate::state::PENDING_JOBS;
-use crate::util::condition::resolve_cond;
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::explain::explain_cond;
 use cosmwasm_std::{Deps, Env, StdError, StdResult};
-use warp_protocol::controller::condition::QueryResolveConditionMsg;
-use warp_protocol::controller::job::QueryResolveJobConditionMsg;
+use warp_protocol::controller::condition::{
+    ConditionTrace, QueryExplainConditionMsg, QueryResolveConditionMsg,
+};
+use warp_protocol::controller::job::{QueryExplainJobConditionMsg, QueryResolveJobConditionMsg};
 
 pub fn query_resolve_condition(
     deps: Deps,
     env: Env,
     data: QueryResolveConditionMsg,
 ) -> StdResult<bool> {
-    Ok(
-        resolve_cond(deps, env, data.condition)
-            .map_err(|e| StdError::generic_err(e.to_string()))?,
-    )
+    resolve_cond(deps, env, &mut ResolveContext::default(), data.condition)
+        .map_err(|e| StdError::generic_err(e.to_string()))
 }
 
 pub fn query_condition_active(
@@ -21,7 +22,39 @@ pub fn query_condition_active(
     data: QueryResolveJobConditionMsg,
 ) -> StdResult<bool> {
     let job = PENDING_JOBS().load(deps.storage, data.id.u64())?;
-    let resp =
-        resolve_cond(deps, env, job.condition).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let resp = resolve_cond(
+        deps,
+        env,
+        &mut ResolveContext::with_job(job.clone()),
+        job.condition,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
     Ok(resp)
 }
+
+pub fn query_explain_condition(
+    deps: Deps,
+    env: Env,
+    data: QueryExplainConditionMsg,
+) -> StdResult<ConditionTrace> {
+    Ok(explain_cond(
+        deps,
+        env,
+        &mut ResolveContext::default(),
+        data.condition,
+    ))
+}
+
+pub fn query_explain_job_condition(
+    deps: Deps,
+    env: Env,
+    data: QueryExplainJobConditionMsg,
+) -> StdResult<ConditionTrace> {
+    let job = PENDING_JOBS().load(deps.storage, data.id.u64())?;
+    Ok(explain_cond(
+        deps,
+        env,
+        &mut ResolveContext::with_job(job.clone()),
+        job.condition,
+    ))
+}
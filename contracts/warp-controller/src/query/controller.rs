@@ -1,5 +1,5 @@
 use crate::state::CONFIG;
-use crate::util::condition::resolve_query_expr;
+use crate::util::condition::{resolve_query_expr, ResolveContext};
 use cosmwasm_std::{Deps, Env, StdError, StdResult};
 use warp_protocol::controller::condition::QueryExpr;
 use warp_protocol::controller::controller::{
@@ -20,10 +20,14 @@ pub fn query_simulate_query(
         response: resolve_query_expr(
             deps,
             env,
+            &mut ResolveContext::default(),
             QueryExpr {
                 selector: "".to_string(),
                 query: data.query,
                 name: "".to_string(),
+                default: None,
+                decimals: None,
+                snapshot: None,
             },
         )
         .map_err(|e| StdError::generic_err(e.to_string()))?,
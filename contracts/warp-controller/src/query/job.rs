@@ -7,12 +7,11 @@ use warp_protocol::controller::job::{
 };
 
 pub fn query_job(deps: Deps, _env: Env, data: QueryJobMsg) -> StdResult<JobResponse> {
-    let job;
-    if FINISHED_JOBS().has(deps.storage, data.id.u64()) {
-        job = FINISHED_JOBS().load(deps.storage, data.id.u64())?
+    let job = if FINISHED_JOBS().has(deps.storage, data.id.u64()) {
+        FINISHED_JOBS().load(deps.storage, data.id.u64())?
     } else {
-        job = PENDING_JOBS().load(deps.storage, data.id.u64())?;
-    }
+        PENDING_JOBS().load(deps.storage, data.id.u64())?
+    };
     Ok(JobResponse { job })
 }
 
@@ -48,10 +47,7 @@ pub fn query_jobs(deps: Deps, env: Env, data: QueryJobsMsg) -> StdResult<JobsRes
             owner,
             job_status,
             condition_status,
-            match start_after {
-                None => None,
-                Some(i) => Some((i._0.u128(), i._1.u64())),
-            },
+            start_after.map(|i| (i._0.u128(), i._1.u64())),
             Some(page_size as usize),
         ),
     }
@@ -80,12 +76,12 @@ pub fn query_jobs_by_ids(
 
         let job = query_job(deps, env.clone(), query_msg)?.job;
         if resolve_filters(
-            deps.clone(),
+            deps,
             env.clone(),
             job.clone(),
             None,
             None,
-            condition_status.clone(),
+            condition_status,
             job_status.clone(),
         ) {
             jobs.push(job)
@@ -97,6 +93,7 @@ pub fn query_jobs_by_ids(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn query_jobs_by_reward(
     deps: Deps,
     env: Env,
@@ -115,7 +112,7 @@ pub fn query_jobs_by_reward(
             .range(deps.storage, None, start, Order::Descending)
             .filter(|h| {
                 resolve_filters(
-                    deps.clone(),
+                    deps,
                     env.clone(),
                     h.as_ref().unwrap().clone().1,
                     name.clone(),
@@ -144,7 +141,7 @@ pub fn query_jobs_by_reward(
             .range(deps.storage, None, start, Order::Descending)
             .filter(|h| {
                 resolve_filters(
-                    deps.clone(),
+                    deps,
                     env.clone(),
                     h.as_ref().unwrap().clone().1,
                     name.clone(),
@@ -2,3 +2,4 @@ pub(crate) mod account;
 pub(crate) mod condition;
 pub(crate) mod controller;
 pub(crate) mod job;
+pub(crate) mod template;
@@ -0,0 +1,45 @@
+use crate::state::{QUERY_PAGE_SIZE, TEMPLATES, TEMPLATE_VERSIONS};
+use cosmwasm_std::{Deps, Env, Order, StdResult};
+use cw_storage_plus::Bound;
+use warp_protocol::controller::template::{
+    QueryTemplateMsg, QueryTemplatesMsg, TemplateResponse, TemplatesResponse,
+};
+
+pub fn query_template(
+    deps: Deps,
+    _env: Env,
+    data: QueryTemplateMsg,
+) -> StdResult<TemplateResponse> {
+    let template = match data.version {
+        None => TEMPLATES().load(deps.storage, data.id.u64())?,
+        Some(version) => TEMPLATE_VERSIONS.load(deps.storage, (data.id.u64(), version.u64()))?,
+    };
+    Ok(TemplateResponse { template })
+}
+
+pub fn query_templates(
+    deps: Deps,
+    _env: Env,
+    data: QueryTemplatesMsg,
+) -> StdResult<TemplatesResponse> {
+    let start_after = data.start_after.map(|id| Bound::exclusive(id.u64()));
+    let limit = data.limit.unwrap_or(QUERY_PAGE_SIZE).min(QUERY_PAGE_SIZE) as usize;
+
+    let infos = match data.owner {
+        None => TEMPLATES()
+            .range(deps.storage, start_after, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+        Some(owner) => TEMPLATES()
+            .idx
+            .owner
+            .prefix(owner)
+            .range(deps.storage, start_after, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    Ok(TemplatesResponse {
+        templates: infos.into_iter().map(|info| info.1).collect(),
+    })
+}
@@ -1,9 +1,11 @@
-use cosmwasm_std::Addr;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128, Uint64};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, UniqueIndex};
 use warp_protocol::controller::account::Account;
 
 use warp_protocol::controller::controller::{Config, State};
 use warp_protocol::controller::job::Job;
+use warp_protocol::controller::template::Template;
 
 pub struct JobIndexes<'a> {
     pub reward: UniqueIndex<'a, (u128, u64), Job>,
@@ -68,6 +70,49 @@ pub fn ACCOUNTS<'a>() -> IndexedMap<'a, Addr, Account, AccountIndexes<'a>> {
     IndexedMap::new("accounts", indexes)
 }
 
+pub struct TemplateIndexes<'a> {
+    pub owner: MultiIndex<'a, Addr, Template, u64>,
+}
+
+impl IndexList<Template> for TemplateIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Template>> + '_> {
+        let v: Vec<&dyn Index<Template>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+// latest version of every template that has not been deleted
+#[allow(non_snake_case)]
+pub fn TEMPLATES<'a>() -> IndexedMap<'a, u64, Template, TemplateIndexes<'a>> {
+    let indexes = TemplateIndexes {
+        owner: MultiIndex::new(
+            |_pk, template| template.owner.clone(),
+            "templates",
+            "templates__owner",
+        ),
+    };
+    IndexedMap::new("templates", indexes)
+}
+
+// every version of every template by (id, version), jobs refer to these
+pub const TEMPLATE_VERSIONS: Map<(u64, u64), Template> = Map::new("template_versions");
+
 pub const QUERY_PAGE_SIZE: u32 = 50;
 pub const CONFIG: Item<Config> = Item::new("config");
+
+// config saved before reward denoms, with a minimum reward in uluna, read on migration
+#[cw_serde]
+pub struct LegacyConfig {
+    pub owner: Addr,
+    pub warp_account_code_id: Uint64,
+    pub minimum_reward: Uint128,
+    pub creation_fee_percentage: Uint128,
+    pub cancellation_fee_percentage: Uint128,
+    pub max_condition_depth: Option<Uint64>,
+    pub max_condition_queries: Option<Uint64>,
+    pub max_condition_size: Option<Uint64>,
+    pub cleanup_tip_percentage: Option<Uint128>,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
 pub const STATE: Item<State> = Item::new("state");
@@ -1,23 +1,17 @@
-use crate::contract::{instantiate, reply};
-use crate::execute::account::create_account;
 use crate::tests::helpers::{create_warp_account, instantiate_warp};
 use crate::ContractError;
-use common::cw::testing::{mock_env, mock_info};
-use cosmwasm_std::testing::mock_dependencies;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{
-    coin, to_binary, Attribute, CosmosMsg, Event, Reply, ReplyOn, Response, SubMsg, SubMsgResponse,
-    SubMsgResult, Uint128, Uint64, WasmMsg,
+    coin, to_binary, CosmosMsg, ReplyOn, Response, SubMsg, Uint128, Uint64, WasmMsg,
 };
-use std::borrow::BorrowMut;
-use warp_protocol::controller::controller::InstantiateMsg;
 
 #[test]
 fn test_create_account_success() {
     let mut deps = mock_dependencies();
     let env = mock_env();
-    let info = mock_info("vlad", &vec![coin(100, "uluna")]);
+    let info = mock_info("vlad", &[coin(100, "uluna")]);
 
-    let instantiate_res = instantiate_warp(
+    let _instantiate_res = instantiate_warp(
         deps.as_mut(),
         env.clone(),
         info.clone(),
@@ -69,7 +63,7 @@ fn test_create_account_success() {
 fn test_create_account_exists() {
     let mut deps = mock_dependencies();
     let env = mock_env();
-    let info = mock_info("vlad", &vec![coin(100, "uluna")]);
+    let info = mock_info("vlad", &[coin(100, "uluna")]);
 
     let _instantiate_res = instantiate_warp(
         deps.as_mut(),
@@ -1,17 +1,18 @@
 use crate::execute::controller::update_config;
 use crate::tests::helpers::instantiate_warp;
+use crate::util::validation::{DEFAULT_MAX_CONDITION_QUERIES, DEFAULT_MAX_CONDITION_SIZE};
 use crate::ContractError;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{coin, Response, Uint128, Uint64};
-use warp_protocol::controller::controller::UpdateConfigMsg;
+use warp_protocol::controller::controller::{RewardDenom, UpdateConfigMsg};
 
 #[test]
 fn test_modify_config_success() {
     let mut deps = mock_dependencies();
     let env = mock_env();
-    let info = mock_info("vlad", &vec![coin(100, "uluna")]);
+    let info = mock_info("vlad", &[coin(100, "uluna")]);
 
-    let instantiate_res = instantiate_warp(
+    let _instantiate_res = instantiate_warp(
         deps.as_mut(),
         env.clone(),
         info.clone(),
@@ -25,9 +26,16 @@ fn test_modify_config_success() {
 
     let modify_config_msg = UpdateConfigMsg {
         owner: Some(info.sender.to_string()),
-        minimum_reward: Some(Uint128::new(1)),
+        reward_denoms: Some(vec![RewardDenom {
+            denom: "uluna".to_string(),
+            minimum_reward: Uint128::new(1),
+        }]),
         creation_fee_percentage: Some(Uint128::new(2)),
         cancellation_fee_percentage: Some(Uint128::new(3)),
+        max_condition_depth: Some(Uint64::new(10)),
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: Some(Uint128::new(2)),
     };
 
     let modify_config_res =
@@ -38,9 +46,22 @@ fn test_modify_config_success() {
         Response::new()
             .add_attribute("action", "update_config")
             .add_attribute("config_owner", info.sender.to_string())
-            .add_attribute("config_minimum_reward", Uint128::new(1))
+            .add_attribute(
+                "config_reward_denoms",
+                "[{\"denom\":\"uluna\",\"minimum_reward\":\"1\"}]"
+            )
             .add_attribute("config_creation_fee_percentage", Uint128::new(2),)
             .add_attribute("config_cancellation_fee_percentage", Uint128::new(3),)
+            .add_attribute("config_max_condition_depth", "10")
+            .add_attribute(
+                "config_max_condition_queries",
+                DEFAULT_MAX_CONDITION_QUERIES.to_string()
+            )
+            .add_attribute(
+                "config_max_condition_size",
+                DEFAULT_MAX_CONDITION_SIZE.to_string()
+            )
+            .add_attribute("config_cleanup_tip_percentage", Uint128::new(2))
     )
 }
 
@@ -48,9 +69,9 @@ fn test_modify_config_success() {
 fn test_modify_config_unauthorized() {
     let mut deps = mock_dependencies();
     let env = mock_env();
-    let info = mock_info("vlad", &vec![coin(100, "uluna")]);
+    let info = mock_info("vlad", &[coin(100, "uluna")]);
 
-    let instantiate_res = instantiate_warp(
+    let _instantiate_res = instantiate_warp(
         deps.as_mut(),
         env.clone(),
         info.clone(),
@@ -64,12 +85,16 @@ fn test_modify_config_unauthorized() {
 
     let modify_config_msg = UpdateConfigMsg {
         owner: Some(info.sender.to_string()),
-        minimum_reward: Some(Uint128::new(1)),
+        reward_denoms: None,
         creation_fee_percentage: Some(Uint128::new(2)),
         cancellation_fee_percentage: Some(Uint128::new(3)),
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: None,
     };
 
-    let info = mock_info("vlad2", &vec![coin(100, "uluna")]);
+    let info = mock_info("vlad2", &[coin(100, "uluna")]);
 
     let modify_config_res =
         update_config(deps.as_mut(), env.clone(), info.clone(), modify_config_msg).unwrap_err();
@@ -81,9 +106,9 @@ fn test_modify_config_unauthorized() {
 fn test_modify_config_bad_percentages() {
     let mut deps = mock_dependencies();
     let env = mock_env();
-    let info = mock_info("vlad", &vec![coin(100, "uluna")]);
+    let info = mock_info("vlad", &[coin(100, "uluna")]);
 
-    let instantiate_res = instantiate_warp(
+    let _instantiate_res = instantiate_warp(
         deps.as_mut(),
         env.clone(),
         info.clone(),
@@ -97,9 +122,13 @@ fn test_modify_config_bad_percentages() {
 
     let modify_config_msg = UpdateConfigMsg {
         owner: Some(info.sender.to_string()),
-        minimum_reward: Some(Uint128::new(1)),
+        reward_denoms: None,
         creation_fee_percentage: Some(Uint128::new(101)),
         cancellation_fee_percentage: Some(Uint128::new(3)),
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: None,
     };
 
     let modify_config_res =
@@ -109,13 +138,79 @@ fn test_modify_config_bad_percentages() {
 
     let modify_config_msg = UpdateConfigMsg {
         owner: Some(info.sender.to_string()),
-        minimum_reward: Some(Uint128::new(1)),
+        reward_denoms: None,
         creation_fee_percentage: Some(Uint128::new(100)),
         cancellation_fee_percentage: Some(Uint128::new(101)),
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: None,
     };
 
     let modify_config_res =
         update_config(deps.as_mut(), env.clone(), info.clone(), modify_config_msg).unwrap_err();
 
-    assert_eq!(modify_config_res, ContractError::CancellationFeeTooHigh {})
+    assert_eq!(modify_config_res, ContractError::CancellationFeeTooHigh {});
+
+    let modify_config_msg = UpdateConfigMsg {
+        owner: Some(info.sender.to_string()),
+        reward_denoms: None,
+        creation_fee_percentage: Some(Uint128::new(100)),
+        cancellation_fee_percentage: Some(Uint128::new(100)),
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: Some(Uint128::new(101)),
+    };
+
+    let modify_config_res =
+        update_config(deps.as_mut(), env.clone(), info.clone(), modify_config_msg).unwrap_err();
+
+    assert_eq!(modify_config_res, ContractError::CleanupTipTooHigh {})
+}
+
+#[test]
+fn test_modify_config_invalid_reward_denoms() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info("vlad", &[coin(100, "uluna")]);
+
+    let _instantiate_res = instantiate_warp(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Some(info.sender.to_string()),
+        Uint64::new(0),
+        Uint128::new(0),
+        Uint128::new(0),
+        Uint128::new(0),
+    )
+    .unwrap();
+
+    let reward_denom = |denom: &str| RewardDenom {
+        denom: denom.to_string(),
+        minimum_reward: Uint128::new(1),
+    };
+
+    for reward_denoms in [
+        vec![],
+        vec![reward_denom("uluna"), reward_denom("uluna")],
+        vec![reward_denom("")],
+    ] {
+        let modify_config_msg = UpdateConfigMsg {
+            owner: None,
+            reward_denoms: Some(reward_denoms),
+            creation_fee_percentage: None,
+            cancellation_fee_percentage: None,
+            max_condition_depth: None,
+            max_condition_queries: None,
+            max_condition_size: None,
+            cleanup_tip_percentage: None,
+        };
+
+        let modify_config_res =
+            update_config(deps.as_mut(), env.clone(), info.clone(), modify_config_msg).unwrap_err();
+
+        assert_eq!(modify_config_res, ContractError::InvalidRewardDenoms {})
+    }
 }
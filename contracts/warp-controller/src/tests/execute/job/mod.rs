@@ -1,4 +1,7 @@
+mod test_cleanup_job;
 mod test_create_job;
 mod test_delete_job;
 mod test_execute_job;
+mod test_recurring_job;
+mod test_reward_denom;
 mod test_update_job;
@@ -0,0 +1,181 @@
+use crate::execute::job::{cleanup_job, create_job, execute_job};
+use crate::query::job::query_job;
+use crate::tests::helpers::{create_job_msg, setup, KEEPER, OWNER, OWNER_ACCOUNT};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{BankMsg, Coin, CosmosMsg, Uint128, Uint64};
+use warp_protocol::controller::job::{
+    CleanupJobMsg, CreateJobMsg, ExecuteJobMsg, Expiry, JobStatus, QueryJobMsg, Recurrence,
+};
+
+#[test]
+fn test_cleanup_expired_job() {
+    let mut deps = setup();
+    let mut env = mock_env();
+    let expiry = env.block.time.seconds() + 100;
+
+    create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        CreateJobMsg {
+            expiry: Some(Expiry::Time(Uint64::new(expiry))),
+            ..create_job_msg()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        cleanup_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(KEEPER, &[]),
+            CleanupJobMsg { id: Uint64::one() }
+        ),
+        Err(ContractError::JobNotExpired {})
+    );
+
+    env.block.time = env.block.time.plus_seconds(100);
+    assert_eq!(
+        execute_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(KEEPER, &[]),
+            ExecuteJobMsg {
+                id: Uint64::one(),
+                signed_data: None,
+            }
+        ),
+        Err(ContractError::JobExpired {})
+    );
+
+    let res = cleanup_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(KEEPER, &[]),
+        CleanupJobMsg { id: Uint64::one() },
+    )
+    .unwrap();
+
+    // default tip of 1%
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: OWNER_ACCOUNT.to_string(),
+                amount: vec![Coin::new(99, "uluna")],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: KEEPER.to_string(),
+                amount: vec![Coin::new(1, "uluna")],
+            }),
+        ]
+    );
+
+    let job = query_job(
+        deps.as_ref(),
+        env.clone(),
+        QueryJobMsg { id: Uint64::one() },
+    )
+    .unwrap()
+    .job;
+    assert_eq!(job.status, JobStatus::Expired);
+
+    // no longer pending
+    assert!(cleanup_job(
+        deps.as_mut(),
+        env,
+        mock_info(KEEPER, &[]),
+        CleanupJobMsg { id: Uint64::one() }
+    )
+    .is_err());
+}
+
+#[test]
+fn test_cleanup_recurring_job() {
+    let mut deps = setup();
+    let mut env = mock_env();
+    let expiry = env.block.height + 10;
+
+    create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        CreateJobMsg {
+            recurrence: Some(Recurrence {
+                budget: Uint128::new(500),
+                max_executions: Some(Uint64::new(5)),
+                min_interval: None,
+            }),
+            expiry: Some(Expiry::BlockHeight(Uint64::new(expiry))),
+            ..create_job_msg()
+        },
+    )
+    .unwrap();
+
+    env.block.height = expiry;
+    let res = cleanup_job(
+        deps.as_mut(),
+        env,
+        mock_info(KEEPER, &[]),
+        CleanupJobMsg { id: Uint64::one() },
+    )
+    .unwrap();
+
+    // the whole remaining budget is returned
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: OWNER_ACCOUNT.to_string(),
+            amount: vec![Coin::new(495, "uluna")],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: KEEPER.to_string(),
+            amount: vec![Coin::new(5, "uluna")],
+        })
+    );
+}
+
+#[test]
+fn test_cleanup_job_without_expiry() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        create_job_msg(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        cleanup_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(KEEPER, &[]),
+            CleanupJobMsg { id: Uint64::one() }
+        ),
+        Err(ContractError::JobNotExpired {})
+    );
+
+    // already expired on creation
+    assert_eq!(
+        create_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            CreateJobMsg {
+                expiry: Some(Expiry::Time(Uint64::new(env.block.time.seconds()))),
+                ..create_job_msg()
+            },
+        ),
+        Err(ContractError::JobExpired {})
+    );
+}
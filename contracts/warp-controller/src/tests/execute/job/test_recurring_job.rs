@@ -0,0 +1,240 @@
+use crate::contract::reply;
+use crate::execute::job::{create_job, execute_job, update_job};
+use crate::query::job::query_job;
+use crate::tests::helpers::{create_job_msg, setup, KEEPER, OWNER, OWNER_ACCOUNT};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    BankMsg, Coin, CosmosMsg, Reply, StdError, SubMsgResponse, SubMsgResult, Uint128, Uint64,
+};
+use warp_protocol::controller::job::{
+    CreateJobMsg, ExecuteJobMsg, JobStatus, QueryJobMsg, Recurrence, UpdateJobMsg,
+};
+
+fn execute_msg() -> ExecuteJobMsg {
+    ExecuteJobMsg {
+        id: Uint64::one(),
+        signed_data: None,
+    }
+}
+
+fn reply_ok() -> Reply {
+    Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    }
+}
+
+#[test]
+fn test_recurring_job_executions() {
+    let mut deps = setup();
+    let mut env = mock_env();
+
+    create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        CreateJobMsg {
+            recurrence: Some(Recurrence {
+                budget: Uint128::new(250),
+                max_executions: None,
+                min_interval: Some(Uint64::new(60)),
+            }),
+            ..create_job_msg()
+        },
+    )
+    .unwrap();
+
+    execute_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(KEEPER, &[]),
+        execute_msg(),
+    )
+    .unwrap();
+    let res = reply(deps.as_mut(), env.clone(), reply_ok()).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "job_execution");
+
+    let job = query_job(
+        deps.as_ref(),
+        env.clone(),
+        QueryJobMsg { id: Uint64::one() },
+    )
+    .unwrap()
+    .job;
+    assert_eq!(job.status, JobStatus::Pending);
+    assert_eq!(job.executions, Some(Uint64::one()));
+    assert_eq!(
+        job.last_execution_time,
+        Some(Uint64::new(env.block.time.seconds()))
+    );
+    assert_eq!(job.funds(), Uint128::new(150));
+
+    assert_eq!(
+        execute_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(KEEPER, &[]),
+            execute_msg()
+        ),
+        Err(ContractError::ExecutionTooSoon {})
+    );
+
+    env.block.time = env.block.time.plus_seconds(60);
+    execute_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(KEEPER, &[]),
+        execute_msg(),
+    )
+    .unwrap();
+    let res = reply(deps.as_mut(), env.clone(), reply_ok()).unwrap();
+
+    // 50 left is less than a reward
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: OWNER_ACCOUNT.to_string(),
+            amount: vec![Coin::new(50, "uluna")],
+        })
+    );
+
+    let job = query_job(deps.as_ref(), env, QueryJobMsg { id: Uint64::one() })
+        .unwrap()
+        .job;
+    assert_eq!(job.status, JobStatus::Executed);
+    assert_eq!(job.executions, Some(Uint64::new(2)));
+}
+
+#[test]
+fn test_recurring_job_max_executions() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        CreateJobMsg {
+            recurrence: Some(Recurrence {
+                budget: Uint128::new(200),
+                max_executions: Some(Uint64::new(3)),
+                min_interval: None,
+            }),
+            ..create_job_msg()
+        },
+    )
+    .unwrap();
+
+    // added rewards top up the budget
+    update_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        UpdateJobMsg {
+            id: Uint64::one(),
+            name: None,
+            condition: None,
+            added_reward: Some(Uint128::new(150)),
+        },
+    )
+    .unwrap();
+    let job = query_job(
+        deps.as_ref(),
+        env.clone(),
+        QueryJobMsg { id: Uint64::one() },
+    )
+    .unwrap()
+    .job;
+    assert_eq!(job.reward, Uint128::new(100));
+    assert_eq!(job.funds(), Uint128::new(350));
+
+    for _ in 0..3 {
+        execute_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(KEEPER, &[]),
+            execute_msg(),
+        )
+        .unwrap();
+        reply(deps.as_mut(), env.clone(), reply_ok()).unwrap();
+    }
+
+    let job = query_job(
+        deps.as_ref(),
+        env.clone(),
+        QueryJobMsg { id: Uint64::one() },
+    )
+    .unwrap()
+    .job;
+    assert_eq!(job.status, JobStatus::Executed);
+    assert_eq!(job.executions, Some(Uint64::new(3)));
+    assert_eq!(job.funds(), Uint128::new(50));
+
+    assert_eq!(
+        execute_job(deps.as_mut(), env, mock_info(KEEPER, &[]), execute_msg()),
+        Err(ContractError::Std(StdError::not_found(
+            "warp_protocol::controller::job::Job"
+        )))
+    );
+}
+
+#[test]
+fn test_create_recurring_job_invalid() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    assert_eq!(
+        create_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            CreateJobMsg {
+                recurrence: Some(Recurrence {
+                    budget: Uint128::new(500),
+                    max_executions: None,
+                    min_interval: None,
+                }),
+                ..create_job_msg()
+            },
+        ),
+        Err(ContractError::InvalidRecurrence {})
+    );
+    assert_eq!(
+        create_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            CreateJobMsg {
+                recurrence: Some(Recurrence {
+                    budget: Uint128::new(500),
+                    max_executions: Some(Uint64::zero()),
+                    min_interval: None,
+                }),
+                ..create_job_msg()
+            },
+        ),
+        Err(ContractError::InvalidRecurrence {})
+    );
+    assert_eq!(
+        create_job(
+            deps.as_mut(),
+            env,
+            mock_info(OWNER, &[]),
+            CreateJobMsg {
+                recurrence: Some(Recurrence {
+                    budget: Uint128::new(50),
+                    max_executions: Some(Uint64::new(2)),
+                    min_interval: None,
+                }),
+                ..create_job_msg()
+            },
+        ),
+        Err(ContractError::BudgetTooSmall {})
+    );
+}
@@ -0,0 +1,198 @@
+use crate::contract::migrate;
+use crate::execute::controller::update_config;
+use crate::execute::job::{create_job, delete_job, execute_job};
+use crate::query::job::query_job;
+use crate::state::{LegacyConfig, CONFIG, LEGACY_CONFIG};
+use crate::tests::helpers::{create_job_msg, setup, KEEPER, KEEPER_ACCOUNT, OWNER, OWNER_ACCOUNT};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Coin, CosmosMsg, MemoryStorage, OwnedDeps, Uint128, Uint64, WasmMsg,
+};
+use warp_protocol::controller::controller::{MigrateMsg, RewardDenom, UpdateConfigMsg};
+use warp_protocol::controller::job::{CreateJobMsg, DeleteJobMsg, ExecuteJobMsg, QueryJobMsg};
+
+fn reward_denom(denom: &str, minimum_reward: u128) -> RewardDenom {
+    RewardDenom {
+        denom: denom.to_string(),
+        minimum_reward: Uint128::new(minimum_reward),
+    }
+}
+
+// accepts uusd with a minimum of 50 and uluna with a minimum of 10, with a 10% cancellation fee
+fn setup_denoms() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    let mut deps = setup();
+
+    update_config(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("vlad", &[]),
+        UpdateConfigMsg {
+            owner: None,
+            reward_denoms: Some(vec![reward_denom("uusd", 50), reward_denom("uluna", 10)]),
+            creation_fee_percentage: None,
+            cancellation_fee_percentage: Some(Uint128::new(10)),
+            max_condition_depth: None,
+            max_condition_queries: None,
+            max_condition_size: None,
+            cleanup_tip_percentage: None,
+        },
+    )
+    .unwrap();
+
+    deps
+}
+
+// job paying `reward` in `reward_denom`
+fn create_msg(reward: u128, reward_denom: Option<&str>) -> CreateJobMsg {
+    CreateJobMsg {
+        reward: Uint128::new(reward),
+        reward_denom: reward_denom.map(|denom| denom.to_string()),
+        ..create_job_msg()
+    }
+}
+
+#[test]
+fn test_create_job_reward_denom() {
+    let mut deps = setup_denoms();
+    let env = mock_env();
+
+    let res = create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        create_msg(100, Some("uluna")),
+    )
+    .unwrap();
+
+    // fees are collected in the job's denom
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: OWNER_ACCOUNT.to_string(),
+            msg: to_binary(&warp_protocol::account::account::ExecuteMsg {
+                msgs: vec![CosmosMsg::Bank(BankMsg::Send {
+                    to_address: env.contract.address.to_string(),
+                    amount: vec![Coin::new(105, "uluna")],
+                })],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // defaults to the first accepted denom
+    create_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        create_msg(100, None),
+    )
+    .unwrap();
+    let job = query_job(
+        deps.as_ref(),
+        env.clone(),
+        QueryJobMsg { id: Uint64::new(2) },
+    )
+    .unwrap()
+    .job;
+    assert_eq!(job.reward_denom, Some("uusd".to_string()));
+
+    assert_eq!(
+        create_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            create_msg(40, Some("uusd")),
+        ),
+        Err(ContractError::RewardTooSmall {})
+    );
+    assert_eq!(
+        create_job(
+            deps.as_mut(),
+            env,
+            mock_info(OWNER, &[]),
+            create_msg(100, Some("ukrw")),
+        ),
+        Err(ContractError::RewardDenomNotAccepted {})
+    );
+}
+
+#[test]
+fn test_execute_and_delete_job_reward_denom() {
+    let mut deps = setup_denoms();
+    let env = mock_env();
+
+    for _ in 0..2 {
+        create_job(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            create_msg(100, Some("uusd")),
+        )
+        .unwrap();
+    }
+
+    let res = execute_job(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(KEEPER, &[]),
+        ExecuteJobMsg {
+            id: Uint64::one(),
+            signed_data: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: KEEPER_ACCOUNT.to_string(),
+            amount: vec![Coin::new(100, "uusd")],
+        })
+    );
+
+    let res = delete_job(
+        deps.as_mut(),
+        env,
+        mock_info(OWNER, &[]),
+        DeleteJobMsg { id: Uint64::new(2) },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: OWNER_ACCOUNT.to_string(),
+            amount: vec![Coin::new(90, "uusd")],
+        })
+    );
+}
+
+#[test]
+fn test_migrate_legacy_config() {
+    let mut deps = mock_dependencies();
+
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                owner: Addr::unchecked("vlad"),
+                warp_account_code_id: Uint64::zero(),
+                minimum_reward: Uint128::new(7),
+                creation_fee_percentage: Uint128::new(5),
+                cancellation_fee_percentage: Uint128::new(10),
+                max_condition_depth: None,
+                max_condition_queries: None,
+                max_condition_size: None,
+                cleanup_tip_percentage: None,
+            },
+        )
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.reward_denoms, vec![reward_denom("uluna", 7)]);
+
+    // migrating again keeps the config
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(CONFIG.load(deps.as_ref().storage).unwrap(), config);
+}
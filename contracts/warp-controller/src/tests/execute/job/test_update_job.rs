@@ -1,3 +1,12 @@
+use crate::execute::job::{create_job, update_job};
+use crate::query::job::query_job;
+use crate::tests::helpers::{create_job_msg, setup, OWNER};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{Binary, Deps, QueryRequest, Uint256, Uint64, WasmQuery};
+use warp_protocol::controller::condition::{Condition, Expr, GenExpr, NumOp, NumValue, QueryExpr};
+use warp_protocol::controller::job::{CreateJobMsg, QueryJobMsg, UpdateJobMsg};
+use warp_protocol::controller::path::{ParsedSelector, PathSegment};
+
 #[test]
 fn test_update_job_successful() {}
 
@@ -9,3 +18,94 @@ fn test_update_job_not_pending() {}
 
 #[test]
 fn test_update_job_add_too_little_reward() {}
+
+fn reserve_above(selector: &str) -> Condition {
+    Condition::Expr(Expr::Uint(GenExpr {
+        left: NumValue::Query(QueryExpr {
+            selector: selector.to_string(),
+            query: QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: "terra1pool".to_string(),
+                msg: Binary::from("{\"pool\":{}}".as_bytes()),
+            })
+            .into(),
+            name: "".to_string(),
+            default: None,
+            decimals: None,
+            snapshot: None,
+        }),
+        op: NumOp::Gt,
+        right: NumValue::Simple(Uint256::zero()),
+    }))
+}
+
+fn stored_selectors(deps: Deps) -> Option<Vec<ParsedSelector>> {
+    query_job(deps, mock_env(), QueryJobMsg { id: Uint64::one() })
+        .unwrap()
+        .job
+        .selectors
+}
+
+#[test]
+fn test_update_job_selectors() {
+    let mut deps = setup();
+
+    create_job(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        CreateJobMsg {
+            condition: reserve_above("$.reserve"),
+            ..create_job_msg()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        stored_selectors(deps.as_ref()),
+        Some(vec![ParsedSelector {
+            selector: "$.reserve".to_string(),
+            path: vec![PathSegment::Key("reserve".to_string())],
+        }])
+    );
+
+    let update = UpdateJobMsg {
+        id: Uint64::one(),
+        name: None,
+        condition: None,
+        added_reward: None,
+    };
+    update_job(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        stored_selectors(deps.as_ref()),
+        Some(vec![ParsedSelector {
+            selector: "$.reserve".to_string(),
+            path: vec![PathSegment::Key("reserve".to_string())],
+        }])
+    );
+
+    update_job(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        UpdateJobMsg {
+            condition: Some(reserve_above("$.assets[-1]")),
+            ..update
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        stored_selectors(deps.as_ref()),
+        Some(vec![ParsedSelector {
+            selector: "$.assets[-1]".to_string(),
+            path: vec![
+                PathSegment::Key("assets".to_string()),
+                PathSegment::Index(-1)
+            ],
+        }])
+    );
+}
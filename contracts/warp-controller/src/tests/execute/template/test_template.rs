@@ -0,0 +1,421 @@
+use crate::execute::template::{
+    create_job_from_template, delete_template, edit_template, submit_template,
+};
+use crate::query::job::query_job;
+use crate::query::template::{query_template, query_templates};
+use crate::state::QUERY_PAGE_SIZE;
+use crate::tests::helpers::{setup, OWNER};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{Addr, BankMsg, Coin, CosmosMsg, Decimal256, Uint128, Uint64};
+use warp_protocol::controller::condition::{Condition, Expr, GenExpr, NumOp, NumValue};
+use warp_protocol::controller::job::QueryJobMsg;
+use warp_protocol::controller::template::{
+    CreateJobFromTemplateMsg, DeleteTemplateMsg, EditTemplateMsg, JobTemplate, QueryTemplateMsg,
+    QueryTemplatesMsg, SubmitTemplateMsg, TemplateParam, TemplateVar, TemplateVarKind,
+};
+
+fn submit_msg() -> SubmitTemplateMsg {
+    SubmitTemplateMsg {
+        name: "send above threshold".to_string(),
+        condition: r#"{"expr":{"decimal":{"left":{"simple":"{{threshold}}"},"op":"gt","right":{"simple":"1"}}}}"#.to_string(),
+        msgs: vec![
+            r#"{"bank":{"send":{"to_address":"{{recipient}}","amount":[{"denom":"uluna","amount":"{{amount}}"}]}}}"#.to_string(),
+        ],
+        vars: vec![
+            TemplateVar {
+                name: "threshold".to_string(),
+                kind: TemplateVarKind::Decimal,
+            },
+            TemplateVar {
+                name: "recipient".to_string(),
+                kind: TemplateVarKind::Address,
+            },
+            TemplateVar {
+                name: "amount".to_string(),
+                kind: TemplateVarKind::Uint,
+            },
+        ],
+    }
+}
+
+fn param(name: &str, value: &str) -> TemplateParam {
+    TemplateParam {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn create_msg(params: Vec<TemplateParam>) -> CreateJobFromTemplateMsg {
+    CreateJobFromTemplateMsg {
+        template_id: Uint64::one(),
+        template_version: None,
+        name: "job".to_string(),
+        params,
+        reward: Uint128::new(10),
+        reward_denom: None,
+    }
+}
+
+#[test]
+fn test_submit_edit_and_query_template() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    submit_template(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        submit_msg(),
+    )
+    .unwrap();
+    let template = query_template(
+        deps.as_ref(),
+        env.clone(),
+        QueryTemplateMsg {
+            id: Uint64::one(),
+            version: None,
+        },
+    )
+    .unwrap()
+    .template;
+    assert_eq!(template.version, Uint64::one());
+    assert_eq!(template.owner, Addr::unchecked(OWNER));
+
+    let edit = EditTemplateMsg {
+        id: Uint64::one(),
+        name: Some("renamed".to_string()),
+        condition: None,
+        msgs: None,
+        vars: None,
+    };
+    assert_eq!(
+        edit_template(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("other", &[]),
+            edit.clone()
+        ),
+        Err(ContractError::Unauthorized {})
+    );
+    edit_template(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), edit).unwrap();
+
+    let latest = query_template(
+        deps.as_ref(),
+        env.clone(),
+        QueryTemplateMsg {
+            id: Uint64::one(),
+            version: None,
+        },
+    )
+    .unwrap()
+    .template;
+    assert_eq!(latest.version, Uint64::new(2));
+    assert_eq!(latest.name, "renamed");
+
+    let first = query_template(
+        deps.as_ref(),
+        env.clone(),
+        QueryTemplateMsg {
+            id: Uint64::one(),
+            version: Some(Uint64::one()),
+        },
+    )
+    .unwrap()
+    .template;
+    assert_eq!(first, template);
+
+    submit_template(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        submit_msg(),
+    )
+    .unwrap();
+    let owned = query_templates(
+        deps.as_ref(),
+        env.clone(),
+        QueryTemplatesMsg {
+            owner: Some(Addr::unchecked(OWNER)),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap()
+    .templates;
+    assert_eq!(owned, vec![latest]);
+
+    let all = query_templates(
+        deps.as_ref(),
+        env,
+        QueryTemplatesMsg {
+            owner: None,
+            start_after: Some(Uint64::one()),
+            limit: None,
+        },
+    )
+    .unwrap()
+    .templates;
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].id, Uint64::new(2));
+}
+
+#[test]
+fn test_query_templates_limit() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    for _ in 0..QUERY_PAGE_SIZE + 1 {
+        submit_template(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            submit_msg(),
+        )
+        .unwrap();
+    }
+
+    let templates = query_templates(
+        deps.as_ref(),
+        env,
+        QueryTemplatesMsg {
+            owner: None,
+            start_after: None,
+            limit: Some(QUERY_PAGE_SIZE + 1),
+        },
+    )
+    .unwrap()
+    .templates;
+    assert_eq!(templates.len(), QUERY_PAGE_SIZE as usize);
+}
+
+#[test]
+fn test_submit_template_invalid_vars() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    let mut msg = submit_msg();
+    msg.vars.pop();
+    assert_eq!(
+        submit_template(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg),
+        Err(ContractError::UndefinedTemplateVar {
+            name: "amount".to_string()
+        })
+    );
+
+    let mut msg = submit_msg();
+    msg.vars.push(TemplateVar {
+        name: "threshold".to_string(),
+        kind: TemplateVarKind::Uint,
+    });
+    assert_eq!(
+        submit_template(deps.as_mut(), env, mock_info(OWNER, &[]), msg),
+        Err(ContractError::DuplicateTemplateVar {
+            name: "threshold".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_create_job_from_template() {
+    let mut deps = setup();
+    let env = mock_env();
+    let info = mock_info(OWNER, &[]);
+
+    submit_template(deps.as_mut(), env.clone(), info.clone(), submit_msg()).unwrap();
+    create_job_from_template(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        create_msg(vec![
+            param("threshold", "2.5"),
+            param("recipient", "terra1recipient"),
+            param("amount", "1000"),
+        ]),
+    )
+    .unwrap();
+
+    let job = query_job(deps.as_ref(), env, QueryJobMsg { id: Uint64::one() })
+        .unwrap()
+        .job;
+    assert_eq!(
+        job.template,
+        Some(JobTemplate {
+            id: Uint64::one(),
+            version: Uint64::one(),
+        })
+    );
+    assert_eq!(
+        job.condition,
+        Condition::Expr(Expr::Decimal(GenExpr {
+            left: NumValue::Simple(Decimal256::from_ratio(5u128, 2u128)),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Decimal256::one()),
+        }))
+    );
+    assert_eq!(
+        job.msgs,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "terra1recipient".to_string(),
+            amount: vec![Coin::new(1000, "uluna")],
+        })]
+    );
+}
+
+#[test]
+fn test_create_job_from_template_invalid_params() {
+    let mut deps = setup();
+    let env = mock_env();
+    let info = mock_info(OWNER, &[]);
+
+    submit_template(deps.as_mut(), env.clone(), info.clone(), submit_msg()).unwrap();
+
+    assert_eq!(
+        create_job_from_template(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create_msg(vec![
+                param("threshold", "2.5"),
+                param("recipient", "terra1recipient")
+            ]),
+        ),
+        Err(ContractError::MissingTemplateParam {
+            name: "amount".to_string()
+        })
+    );
+
+    assert_eq!(
+        create_job_from_template(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create_msg(vec![
+                param("threshold", "2.5"),
+                param("recipient", "terra1recipient"),
+                param("amount", "1000"),
+                param("extra", "x"),
+            ]),
+        ),
+        Err(ContractError::UnknownTemplateParam {
+            name: "extra".to_string()
+        })
+    );
+
+    assert_eq!(
+        create_job_from_template(
+            deps.as_mut(),
+            env,
+            info,
+            create_msg(vec![
+                param("threshold", "2.5"),
+                param("recipient", "terra1recipient"),
+                param("amount", "-1"),
+            ]),
+        ),
+        Err(ContractError::InvalidTemplateParam {
+            name: "amount".to_string(),
+            kind: "uint".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_delete_template() {
+    let mut deps = setup();
+    let env = mock_env();
+    let info = mock_info(OWNER, &[]);
+
+    submit_template(deps.as_mut(), env.clone(), info.clone(), submit_msg()).unwrap();
+    assert_eq!(
+        delete_template(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("other", &[]),
+            DeleteTemplateMsg { id: Uint64::one() }
+        ),
+        Err(ContractError::Unauthorized {})
+    );
+    delete_template(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        DeleteTemplateMsg { id: Uint64::one() },
+    )
+    .unwrap();
+
+    let params = vec![
+        param("threshold", "2.5"),
+        param("recipient", "terra1recipient"),
+        param("amount", "1000"),
+    ];
+    assert_eq!(
+        create_job_from_template(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create_msg(params.clone()),
+        ),
+        Err(ContractError::TemplateDoesNotExist {})
+    );
+
+    // versions stay usable
+    create_job_from_template(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        CreateJobFromTemplateMsg {
+            template_version: Some(Uint64::one()),
+            ..create_msg(params)
+        },
+    )
+    .unwrap();
+    query_template(
+        deps.as_ref(),
+        env,
+        QueryTemplateMsg {
+            id: Uint64::one(),
+            version: Some(Uint64::one()),
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_create_job_from_template_int() {
+    let mut deps = setup();
+    let env = mock_env();
+    let info = mock_info(OWNER, &[]);
+
+    let msg = SubmitTemplateMsg {
+        name: "int".to_string(),
+        condition:
+            r#"{"expr":{"int":{"left":{"simple":"{{min}}"},"op":"lt","right":{"simple":"0"}}}}"#
+                .to_string(),
+        msgs: vec![],
+        vars: vec![TemplateVar {
+            name: "min".to_string(),
+            kind: TemplateVarKind::Int,
+        }],
+    };
+    submit_template(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    create_job_from_template(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        create_msg(vec![param("min", "-5")]),
+    )
+    .unwrap();
+
+    let job = query_job(deps.as_ref(), env, QueryJobMsg { id: Uint64::one() })
+        .unwrap()
+        .job;
+    assert_eq!(
+        job.condition,
+        Condition::Expr(Expr::Int(GenExpr {
+            left: NumValue::Simple(-5),
+            op: NumOp::Lt,
+            right: NumValue::Simple(0),
+        }))
+    );
+}
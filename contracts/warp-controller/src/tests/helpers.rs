@@ -1,15 +1,23 @@
 use crate::contract::{instantiate, reply};
 use crate::execute::account::create_account;
 use crate::ContractError;
-use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
 use cosmwasm_std::{
     Attribute, DepsMut, Env, Event, MessageInfo, OwnedDeps, Reply, Response, SubMsgResponse,
     SubMsgResult, Uint128, Uint64,
 };
-use std::fmt::format;
-use std::ops::Add;
-use warp_protocol::controller::controller::InstantiateMsg;
+use warp_protocol::controller::condition::{Condition, Expr, NumOp, TimeExpr};
+use warp_protocol::controller::controller::{InstantiateMsg, RewardDenom};
+use warp_protocol::controller::job::CreateJobMsg;
+
+pub const OWNER: &str = "terra1vladvladvladvladvladvladvladvladvla100";
+pub const OWNER_ACCOUNT: &str = "terra1vladvladvladvladvladvladvladvladvla101";
+pub const KEEPER: &str = "terra1vladvladvladvladvladvladvladvladvla110";
+pub const KEEPER_ACCOUNT: &str = "terra1vladvladvladvladvladvladvladvladvla111";
 
+#[allow(clippy::too_many_arguments)]
 pub fn instantiate_warp(
     deps: DepsMut,
     env: Env,
@@ -23,16 +31,23 @@ pub fn instantiate_warp(
     let instantiate_msg = InstantiateMsg {
         owner,
         warp_account_code_id,
-        minimum_reward,
+        reward_denoms: vec![RewardDenom {
+            denom: "uluna".to_string(),
+            minimum_reward,
+        }],
         creation_fee,
         cancellation_fee,
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip: None,
     };
 
-    return instantiate(deps, env.clone(), info.clone(), instantiate_msg.clone());
+    instantiate(deps, env.clone(), info.clone(), instantiate_msg.clone())
 }
 
 pub fn create_warp_account(
-    mut deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
     env: Env,
     info: MessageInfo,
     account_id: Uint64,
@@ -68,5 +83,55 @@ pub fn create_warp_account(
 
     let reply_res = reply(deps.as_mut(), env, reply_msg);
 
-    return (create_account_res, reply_res);
+    (create_account_res, reply_res)
+}
+
+// warp instantiated by vlad with a 5% creation fee, with accounts for the owner and the keeper
+pub fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info("vlad", &[]);
+
+    let _instantiate_res = instantiate_warp(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Some(info.sender.to_string()),
+        Uint64::new(0),
+        Uint128::new(0),
+        Uint128::new(5),
+        Uint128::new(0),
+    )
+    .unwrap();
+
+    for (owner, account_id) in [(OWNER, 0), (KEEPER, 10)] {
+        let (create_account_res, reply_res) = create_warp_account(
+            &mut deps,
+            env.clone(),
+            mock_info(owner, &[]),
+            Uint64::new(account_id),
+        );
+        create_account_res.unwrap();
+        reply_res.unwrap();
+    }
+
+    deps
+}
+
+// job that can always execute, tests override the fields they need
+pub fn create_job_msg() -> CreateJobMsg {
+    CreateJobMsg {
+        name: "job".to_string(),
+        condition: Condition::Expr(Expr::Timestamp(TimeExpr {
+            comparator: Uint64::zero(),
+            op: NumOp::Gt,
+            anchor: None,
+        })),
+        msgs: vec![],
+        reward: Uint128::new(100),
+        signer: None,
+        recurrence: None,
+        expiry: None,
+        reward_denom: None,
+    }
 }
@@ -1,3 +1,10 @@
 mod test_condition;
+mod test_dependency;
+mod test_explain;
 mod test_filter;
+mod test_normalize;
 mod test_path;
+mod test_query;
+mod test_signed;
+mod test_snapshot;
+mod test_validation;
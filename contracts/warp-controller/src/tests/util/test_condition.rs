@@ -1 +1,1002 @@
+use crate::util::condition::{
+    resolve_cond, resolve_num_value_decimal, resolve_num_value_int, resolve_num_value_uint,
+    ResolveContext,
+};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    Addr, Binary, ContractResult, Decimal256, QueryRequest, SystemResult, Timestamp, Uint128,
+    Uint256, Uint64, WasmQuery,
+};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::str::FromStr;
+use warp_protocol::controller::condition::{
+    AggregateOp, ArrayAggregate, ArrayCondition, ArrayValue, BlockExpr, CalendarExpr,
+    CalendarField, CastValue, Condition, DecimalFnOp, Expr, GenExpr, IntFnOp, JobAnchor, ListExpr,
+    ListOp, ListValue, NumCastValue, NumClampValue, NumEnvValue, NumExprOp, NumExprValue,
+    NumFnValue, NumOp, NumValue, OverflowPolicy, PeriodExpr, QueryExpr, StringEnvValue, StringOp,
+    TimeExpr, Value,
+};
+use warp_protocol::controller::job::{Job, JobStatus};
+use warp_protocol::controller::path::{ParsedSelector, PathSegment};
 
+fn pool_query_expr(name: &str, selector: &str) -> QueryExpr {
+    QueryExpr {
+        selector: selector.to_string(),
+        query: QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1pool".to_string(),
+            msg: Binary::from("{\"pool\":{}}".as_bytes()),
+        })
+        .into(),
+        name: name.to_string(),
+        default: None,
+        decimals: None,
+        snapshot: None,
+    }
+}
+
+fn pool_condition(name: &str) -> Condition {
+    Condition::And(vec![
+        Box::new(Condition::Expr(Expr::Decimal(GenExpr {
+            left: NumValue::Query(pool_query_expr(name, "$.price")),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Decimal256::from_str("1.05").unwrap()),
+        }))),
+        Box::new(Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Query(pool_query_expr(name, "$.reserve")),
+            op: NumOp::Gte,
+            right: NumValue::Simple(Uint256::from(100u64)),
+        }))),
+    ])
+}
+
+#[test]
+fn test_resolve_cond_named_query_issued_once() {
+    let mut deps = mock_dependencies();
+    let count = Rc::new(Cell::new(0));
+    let counter = count.clone();
+    deps.querier.update_wasm(move |_| {
+        counter.set(counter.get() + 1);
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"price\":\"1.5\",\"reserve\":\"100\"}".as_bytes(),
+        )))
+    });
+
+    let mut ctx = ResolveContext::default();
+    let res = resolve_cond(deps.as_ref(), mock_env(), &mut ctx, pool_condition("pool")).unwrap();
+
+    assert!(res);
+    assert_eq!(count.get(), 1);
+    assert!(ctx.queries.contains_key("pool"));
+}
+
+#[test]
+fn test_resolve_cond_unnamed_query_not_cached() {
+    let mut deps = mock_dependencies();
+    let count = Rc::new(Cell::new(0));
+    let counter = count.clone();
+    deps.querier.update_wasm(move |_| {
+        counter.set(counter.get() + 1);
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"price\":\"1.5\",\"reserve\":\"100\"}".as_bytes(),
+        )))
+    });
+
+    let res = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        pool_condition(""),
+    )
+    .unwrap();
+
+    assert!(res);
+    assert_eq!(count.get(), 2);
+}
+
+#[test]
+fn test_resolve_cond_named_query_conflict() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"price\":\"1.5\",\"reserve\":\"100\"}".as_bytes(),
+        )))
+    });
+
+    let mut other = pool_query_expr("pool", "$.reserve");
+    other.query = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: "terra1other".to_string(),
+        msg: Binary::from("{\"pool\":{}}".as_bytes()),
+    })
+    .into();
+    let cond = Condition::And(vec![
+        Box::new(pool_condition("pool")),
+        Box::new(Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Query(other),
+            op: NumOp::Gte,
+            right: NumValue::Simple(Uint256::from(100u64)),
+        }))),
+    ]);
+
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            cond
+        ),
+        Err(ContractError::ConflictingNamedQuery {
+            name: "pool".to_string()
+        })
+    );
+}
+
+fn uint_expr(
+    left: u64,
+    op: NumExprOp,
+    right: u64,
+    overflow: Option<OverflowPolicy>,
+) -> NumValue<Uint256, NumExprOp, IntFnOp> {
+    NumValue::Expr(NumExprValue {
+        left: Box::new(NumValue::Simple(Uint256::from(left))),
+        op,
+        right: Box::new(NumValue::Simple(Uint256::from(right))),
+        overflow,
+    })
+}
+
+#[test]
+fn test_resolve_num_expr_division_by_zero() {
+    let deps = mock_dependencies();
+
+    let err = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        uint_expr(10, NumExprOp::Div, 0, None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DivisionByZero {});
+
+    let err = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: Box::new(NumValue::Simple(10)),
+            op: NumExprOp::Mod,
+            right: Box::new(NumValue::Simple(0)),
+            overflow: None,
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DivisionByZero {});
+}
+
+#[test]
+fn test_resolve_num_expr_overflow_policy() {
+    let deps = mock_dependencies();
+
+    let saturated = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        uint_expr(1, NumExprOp::Sub, 2, Some(OverflowPolicy::Saturate)),
+    )
+    .unwrap();
+    assert_eq!(saturated, Uint256::zero());
+
+    let err = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        uint_expr(1, NumExprOp::Sub, 2, Some(OverflowPolicy::Error)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Underflow {});
+
+    let err = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: Box::new(NumValue::Simple(i128::MAX)),
+            op: NumExprOp::Mul,
+            right: Box::new(NumValue::Simple(2)),
+            overflow: Some(OverflowPolicy::Error),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Overflow {});
+}
+
+#[test]
+fn test_resolve_num_fn_int_overflow() {
+    let deps = mock_dependencies();
+
+    for op in [IntFnOp::Neg, IntFnOp::Abs] {
+        let err = resolve_num_value_int(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Fn(NumFnValue {
+                op,
+                right: Box::new(NumValue::Simple(i128::MIN)),
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Overflow {});
+    }
+
+    let neg = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Fn(NumFnValue {
+            op: IntFnOp::Neg,
+            right: Box::new(NumValue::Simple(i128::MAX)),
+        }),
+    )
+    .unwrap();
+    assert_eq!(neg, -i128::MAX);
+}
+
+fn decimal(value: &str) -> Box<NumValue<Decimal256, NumExprOp, DecimalFnOp>> {
+    Box::new(NumValue::Simple(Decimal256::from_str(value).unwrap()))
+}
+
+#[test]
+fn test_resolve_num_expr_pct_change_and_pow() {
+    let deps = mock_dependencies();
+
+    let pct = resolve_num_value_decimal(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: decimal("1.05125"),
+            op: NumExprOp::PctChange,
+            right: decimal("1.0"),
+            overflow: None,
+        }),
+    )
+    .unwrap();
+    assert_eq!(pct, Decimal256::from_str("512.5").unwrap());
+
+    // a decrease cannot be expressed as an unsigned value
+    let err = resolve_num_value_decimal(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: decimal("0.94"),
+            op: NumExprOp::PctChange,
+            right: decimal("1.0"),
+            overflow: None,
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SignLoss {});
+
+    let pct = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        uint_expr(105, NumExprOp::PctChange, 100, None),
+    )
+    .unwrap();
+    assert_eq!(pct, Uint256::from(500u64));
+
+    let err = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        uint_expr(95, NumExprOp::PctChange, 100, None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SignLoss {});
+
+    let pct = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: Box::new(NumValue::Simple(105)),
+            op: NumExprOp::PctChange,
+            right: Box::new(NumValue::Simple(100)),
+            overflow: None,
+        }),
+    )
+    .unwrap();
+    assert_eq!(pct, 500);
+
+    let pct = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: Box::new(NumValue::Simple(-95)),
+            op: NumExprOp::PctChange,
+            right: Box::new(NumValue::Simple(-100)),
+            overflow: None,
+        }),
+    )
+    .unwrap();
+    assert_eq!(pct, -500);
+
+    let pct = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: Box::new(NumValue::Simple(97)),
+            op: NumExprOp::PctChange,
+            right: Box::new(NumValue::Simple(100)),
+            overflow: None,
+        }),
+    )
+    .unwrap();
+    assert_eq!(pct, -300);
+
+    let pow = resolve_num_value_decimal(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: decimal("1.5"),
+            op: NumExprOp::Pow,
+            right: decimal("2"),
+            overflow: None,
+        }),
+    )
+    .unwrap();
+    assert_eq!(pow, Decimal256::from_str("2.25").unwrap());
+
+    let err = resolve_num_value_decimal(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Expr(NumExprValue {
+            left: decimal("1.5"),
+            op: NumExprOp::Pow,
+            right: decimal("0.5"),
+            overflow: None,
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidExponent {});
+}
+
+#[test]
+fn test_resolve_num_fn_round_and_clamp() {
+    let deps = mock_dependencies();
+
+    let rounded = resolve_num_value_decimal(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Fn(NumFnValue {
+            op: DecimalFnOp::Round(2),
+            right: decimal("1.23456"),
+        }),
+    )
+    .unwrap();
+    assert_eq!(rounded, Decimal256::from_str("1.23").unwrap());
+
+    // integers round to a multiple of 10^places
+    let rounded = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Fn(NumFnValue {
+            op: IntFnOp::Round(6),
+            right: Box::new(NumValue::Simple(Uint256::from(2_500_000u64))),
+        }),
+    )
+    .unwrap();
+    assert_eq!(rounded, Uint256::from(3_000_000u64));
+
+    for (value, expected) in [(-1_500, -2_000), (-1_499, -1_000), (1_499, 1_000)] {
+        let rounded = resolve_num_value_int(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Fn(NumFnValue {
+                op: IntFnOp::Round(3),
+                right: Box::new(NumValue::Simple(value)),
+            }),
+        )
+        .unwrap();
+        assert_eq!(rounded, expected);
+    }
+
+    let err = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Fn(NumFnValue {
+            op: IntFnOp::Round(1),
+            right: Box::new(NumValue::Simple(i128::MAX)),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Overflow {});
+
+    let clamped = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Clamp(NumClampValue {
+            value: Box::new(NumValue::Simple(Uint256::zero())),
+            min: Box::new(NumValue::Simple(Uint256::from(1u64))),
+            max: Box::new(NumValue::Simple(Uint256::from(5u64))),
+        }),
+    )
+    .unwrap();
+    assert_eq!(clamped, Uint256::from(1u64));
+
+    let clamped = resolve_num_value_decimal(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Clamp(NumClampValue {
+            value: decimal("7"),
+            min: decimal("1"),
+            max: decimal("5"),
+        }),
+    )
+    .unwrap();
+    assert_eq!(clamped, Decimal256::from_str("5").unwrap());
+
+    let err = resolve_num_value_int(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Clamp(NumClampValue {
+            value: Box::new(NumValue::Simple(0)),
+            min: Box::new(NumValue::Simple(5)),
+            max: Box::new(NumValue::Simple(1)),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidArguments {});
+}
+
+#[test]
+fn test_resolve_cast_between_domains() {
+    let deps = mock_dependencies();
+
+    let res = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        Condition::Expr(Expr::Decimal(GenExpr {
+            left: NumValue::Cast(NumCastValue {
+                value: CastValue::Uint(Box::new(NumValue::Simple(Uint256::from(1_500_000u64)))),
+                decimals: Some(6),
+            }),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Decimal256::from_str("1.2").unwrap()),
+        })),
+    )
+    .unwrap();
+    assert!(res);
+
+    let balance = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Cast(NumCastValue {
+            value: CastValue::Decimal(decimal("1.2345678")),
+            decimals: Some(6),
+        }),
+    )
+    .unwrap();
+    assert_eq!(balance, Uint256::from(1_234_567u64));
+
+    let err = resolve_num_value_uint(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        NumValue::Cast(NumCastValue {
+            value: CastValue::Int(Box::new(NumValue::Simple(-1))),
+            decimals: None,
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SignLoss {});
+}
+
+fn job_with_condition(condition: Condition) -> Job {
+    let env = mock_env();
+    Job {
+        id: Uint64::new(1),
+        owner: Addr::unchecked("vlad"),
+        last_update_time: Uint64::new(env.block.time.seconds() - 100),
+        last_update_height: Some(Uint64::new(env.block.height - 10)),
+        creation_time: Some(Uint64::new(env.block.time.seconds() - 1000)),
+        creation_height: Some(Uint64::new(env.block.height - 100)),
+        name: "job".to_string(),
+        status: JobStatus::Pending,
+        condition,
+        msgs: vec![],
+        reward: Uint128::new(100),
+        template: None,
+        snapshots: None,
+        selectors: None,
+        signer: None,
+        recurrence: None,
+        executions: None,
+        last_execution_time: None,
+        expiry: None,
+        reward_denom: None,
+    }
+}
+
+#[test]
+fn test_resolve_cond_stored_selectors() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"price\":\"1.5\"}".as_bytes(),
+        )))
+    });
+
+    let cond = Condition::Expr(Expr::Exists(pool_query_expr("", "$.missing")));
+    assert!(!resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        cond.clone()
+    )
+    .unwrap());
+
+    // a job walks the segments stored with its condition instead of parsing the selector
+    let mut job = job_with_condition(cond.clone());
+    job.selectors = Some(vec![ParsedSelector {
+        selector: "$.missing".to_string(),
+        path: vec![PathSegment::Key("price".to_string())],
+    }]);
+    assert!(resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::with_job(job),
+        cond
+    )
+    .unwrap());
+}
+
+#[test]
+fn test_resolve_relative_time_and_block() {
+    let deps = mock_dependencies();
+    let condition = Condition::And(vec![
+        Box::new(Condition::Expr(Expr::Timestamp(TimeExpr {
+            comparator: Uint64::new(1000),
+            op: NumOp::Gte,
+            anchor: Some(JobAnchor::Creation),
+        }))),
+        Box::new(Condition::Expr(Expr::Timestamp(TimeExpr {
+            comparator: Uint64::new(100),
+            op: NumOp::Eq,
+            anchor: Some(JobAnchor::LastUpdate),
+        }))),
+        Box::new(Condition::Expr(Expr::BlockHeight(BlockExpr {
+            comparator: Uint64::new(50),
+            op: NumOp::Gt,
+            anchor: Some(JobAnchor::Creation),
+        }))),
+    ]);
+    let job = job_with_condition(condition.clone());
+
+    let res = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::with_job(job),
+        condition.clone(),
+    )
+    .unwrap();
+    assert!(res);
+
+    let err = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        condition,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AnchorUnavailable {});
+}
+
+fn calendar(field: CalendarField, comparator: u64) -> Condition {
+    Condition::Expr(Expr::Calendar(CalendarExpr {
+        field,
+        op: NumOp::Eq,
+        comparator: Uint64::new(comparator),
+    }))
+}
+
+#[test]
+fn test_resolve_calendar() {
+    let deps = mock_dependencies();
+    let mut env = mock_env();
+
+    // (timestamp, day of week, hour of day, day of month)
+    for (time, day_of_week, hour_of_day, day_of_month) in [
+        (1571797419, 3, 2, 23),  // 2019-10-23 02:23:39, wednesday
+        (1709164800, 4, 0, 29),  // 2024-02-29 00:00:00, thursday
+        (951868800, 3, 0, 1),    // 2000-03-01 00:00:00, wednesday
+        (1704063600, 7, 23, 31), // 2023-12-31 23:00:00, sunday
+    ] {
+        env.block.time = Timestamp::from_seconds(time);
+        let condition = Condition::And(vec![
+            Box::new(calendar(CalendarField::DayOfWeek, day_of_week)),
+            Box::new(calendar(CalendarField::HourOfDay, hour_of_day)),
+            Box::new(calendar(CalendarField::DayOfMonth, day_of_month)),
+        ]);
+        assert_eq!(
+            resolve_cond(
+                deps.as_ref(),
+                env.clone(),
+                &mut ResolveContext::default(),
+                condition
+            ),
+            Ok(true),
+            "{}",
+            time
+        );
+    }
+}
+
+#[test]
+fn test_resolve_period() {
+    let deps = mock_dependencies();
+    // created 1000 seconds ago
+    let period = |period: u64, tolerance: u64| {
+        Condition::Expr(Expr::Period(PeriodExpr {
+            period: Uint64::new(period),
+            anchor: JobAnchor::Creation,
+            tolerance: Uint64::new(tolerance),
+        }))
+    };
+
+    for (condition, expected) in [
+        (period(300, 100), true),
+        (period(300, 99), false),
+        (period(500, 0), true),
+        (period(2000, 2000), false),
+    ] {
+        let job = job_with_condition(condition.clone());
+        assert_eq!(
+            resolve_cond(
+                deps.as_ref(),
+                mock_env(),
+                &mut ResolveContext::with_job(job),
+                condition
+            ),
+            Ok(expected)
+        );
+    }
+
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            period(300, 100)
+        ),
+        Err(ContractError::AnchorUnavailable {})
+    );
+}
+
+#[test]
+fn test_resolve_block_env_values() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let end_time = env.block.time.seconds() + 1800;
+    deps.querier.update_wasm(move |_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            format!("{{\"end_time\":\"{}\"}}", end_time).as_bytes(),
+        )))
+    });
+
+    let condition = Condition::And(vec![
+        Box::new(Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Expr(NumExprValue {
+                left: Box::new(NumValue::Query(pool_query_expr("vesting", "$.end_time"))),
+                op: NumExprOp::Sub,
+                right: Box::new(NumValue::Env(NumEnvValue::Time)),
+                overflow: None,
+            }),
+            op: NumOp::Lt,
+            right: NumValue::Simple(Uint256::from(3600u64)),
+        }))),
+        Box::new(Condition::Expr(Expr::String(GenExpr {
+            left: Value::Env(StringEnvValue::ChainId),
+            op: StringOp::Eq,
+            right: Value::Simple(env.block.chain_id.clone()),
+        }))),
+    ]);
+
+    let res = resolve_cond(
+        deps.as_ref(),
+        env,
+        &mut ResolveContext::default(),
+        condition,
+    )
+    .unwrap();
+    assert!(res);
+}
+
+#[test]
+fn test_resolve_list_and_string_ops() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(move |_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"ids\":[\"1\",\"2\",\"3\"],\"denom\":\"ULUNA\"}".as_bytes(),
+        )))
+    });
+
+    let condition = Condition::And(vec![
+        Box::new(Condition::Expr(Expr::UintList(ListExpr {
+            left: NumValue::Simple(Uint256::from(2u64)),
+            op: ListOp::In,
+            right: ListValue::Query(pool_query_expr("pool", "$.ids")),
+        }))),
+        Box::new(Condition::Expr(Expr::StringList(ListExpr {
+            left: Value::Query(pool_query_expr("pool", "$.denom")),
+            op: ListOp::NotIn,
+            right: ListValue::Simple(vec!["uusd".to_string(), "ukrw".to_string()]),
+        }))),
+        Box::new(Condition::Expr(Expr::String(GenExpr {
+            left: Value::Query(pool_query_expr("pool", "$.denom")),
+            op: StringOp::EqIgnoreCase,
+            right: Value::Simple("uluna".to_string()),
+        }))),
+        Box::new(Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::StringLen(Value::Query(pool_query_expr("pool", "$.denom"))),
+            op: NumOp::Eq,
+            right: NumValue::Simple(Uint256::from(5u64)),
+        }))),
+    ]);
+
+    let res = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        condition,
+    )
+    .unwrap();
+    assert!(res);
+}
+
+fn health_below(threshold: &str) -> Box<Condition> {
+    Box::new(Condition::Expr(Expr::Decimal(GenExpr {
+        left: NumValue::Element("$.health".to_string()),
+        op: NumOp::Lt,
+        right: NumValue::Simple(Decimal256::from_str(threshold).unwrap()),
+    })))
+}
+
+#[test]
+fn test_resolve_array_quantifiers_and_aggregates() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(move |_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"positions\":[{\"health\":\"1.5\",\"debt\":\"100\"},{\"health\":\"1.05\",\"debt\":\"50\"}]}"
+                .as_bytes(),
+        )))
+    });
+    let positions = || ArrayValue::Query(pool_query_expr("positions", "$.positions"));
+
+    let any = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        Condition::Any(ArrayCondition {
+            array: positions(),
+            condition: health_below("1.1"),
+        }),
+    )
+    .unwrap();
+    assert!(any);
+
+    let all = resolve_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        Condition::All(ArrayCondition {
+            array: positions(),
+            condition: health_below("1.1"),
+        }),
+    )
+    .unwrap();
+    assert!(!all);
+
+    let aggregate = |op: AggregateOp, selector: Option<&str>, condition: Option<Box<Condition>>| {
+        resolve_num_value_uint(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Aggregate(ArrayAggregate {
+                array: positions(),
+                op,
+                selector: selector.map(|s| s.to_string()),
+                condition,
+            }),
+        )
+    };
+
+    assert_eq!(
+        aggregate(AggregateOp::Sum, Some("$.debt"), None).unwrap(),
+        Uint256::from(150u64)
+    );
+    assert_eq!(
+        aggregate(AggregateOp::Count, None, Some(health_below("1.1"))).unwrap(),
+        Uint256::from(1u64)
+    );
+    assert_eq!(
+        aggregate(AggregateOp::Max, Some("$.debt"), Some(health_below("1"))).unwrap_err(),
+        ContractError::EmptyArray {}
+    );
+}
+
+#[test]
+fn test_resolve_exists_and_is_null() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"price\":\"1.5\",\"owner\":null}".as_bytes(),
+        )))
+    });
+
+    let resolve = |expr: Expr| {
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            Condition::Expr(expr),
+        )
+        .unwrap()
+    };
+
+    assert!(resolve(Expr::Exists(pool_query_expr("", "$.price"))));
+    assert!(resolve(Expr::Exists(pool_query_expr("", "$.owner"))));
+    assert!(!resolve(Expr::Exists(pool_query_expr("", "$.reserve"))));
+    assert!(resolve(Expr::IsNull(pool_query_expr("", "$.owner"))));
+    assert!(!resolve(Expr::IsNull(pool_query_expr("", "$.price"))));
+    assert!(!resolve(Expr::IsNull(pool_query_expr("", "$.reserve"))));
+}
+
+#[test]
+fn test_resolve_query_expr_default() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"price\":\"1.5\",\"reserve\":null}".as_bytes(),
+        )))
+    });
+
+    let with_default = |selector: &str, default: Option<&str>| QueryExpr {
+        default: default.map(|d| d.to_string()),
+        ..pool_query_expr("", selector)
+    };
+
+    let resolve = |expr: QueryExpr| {
+        resolve_num_value_uint(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Query(expr),
+        )
+    };
+
+    assert_eq!(
+        resolve(with_default("$.reserve", Some("\"7\""))),
+        Ok(Uint256::from(7u64))
+    );
+    assert_eq!(
+        resolve(with_default("$.fee", Some("\"0\""))),
+        Ok(Uint256::zero())
+    );
+    assert_eq!(
+        resolve(with_default("$.fee", None)),
+        Err(ContractError::DecodeError {})
+    );
+    assert_eq!(
+        resolve(with_default("$.fee", Some("\"0"))),
+        Err(ContractError::InvalidQueryDefault {})
+    );
+    assert_eq!(
+        resolve_num_value_decimal(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Query(with_default("$.price", Some("\"2\""))),
+        ),
+        Ok(Decimal256::from_str("1.5").unwrap())
+    );
+}
+
+#[test]
+fn test_resolve_query_expr_numeric_coercion() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            "{\"amount\":\"1234567\",\"count\":42,\"delta\":\"-3\",\"ratio\":\"0.5\",\"denom\":\"uluna\"}"
+                .as_bytes(),
+        )))
+    });
+
+    let query = |selector: &str, decimals: Option<u32>| QueryExpr {
+        decimals,
+        ..pool_query_expr("", selector)
+    };
+    let uint = |expr: QueryExpr| {
+        resolve_num_value_uint(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Query(expr),
+        )
+    };
+    let int = |expr: QueryExpr| {
+        resolve_num_value_int(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Query(expr),
+        )
+    };
+    let dec = |expr: QueryExpr| {
+        resolve_num_value_decimal(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            NumValue::Query(expr),
+        )
+    };
+
+    assert_eq!(uint(query("$.amount", None)), Ok(Uint256::from(1234567u64)));
+    assert_eq!(uint(query("$.count", None)), Ok(Uint256::from(42u64)));
+    assert_eq!(int(query("$.count", None)), Ok(42));
+    assert_eq!(int(query("$.delta", None)), Ok(-3));
+    assert_eq!(
+        dec(query("$.ratio", None)),
+        Ok(Decimal256::from_str("0.5").unwrap())
+    );
+    assert_eq!(
+        dec(query("$.count", None)),
+        Ok(Decimal256::from_str("42").unwrap())
+    );
+    assert_eq!(
+        dec(query("$.amount", Some(6))),
+        Ok(Decimal256::from_str("1.234567").unwrap())
+    );
+    assert_eq!(
+        dec(query("$.count", Some(1))),
+        Ok(Decimal256::from_str("4.2").unwrap())
+    );
+
+    assert_eq!(
+        uint(query("$.delta", None)),
+        Err(ContractError::InvalidSelectorValue {
+            selector: "$.delta".to_string(),
+            expected: "uint".to_string(),
+        })
+    );
+    assert_eq!(
+        dec(query("$.denom", None)),
+        Err(ContractError::InvalidSelectorValue {
+            selector: "$.denom".to_string(),
+            expected: "decimal".to_string(),
+        })
+    );
+    assert_eq!(
+        dec(query("$.ratio", Some(6))),
+        Err(ContractError::InvalidSelectorValue {
+            selector: "$.ratio".to_string(),
+            expected: "decimal".to_string(),
+        })
+    );
+}
@@ -0,0 +1,145 @@
+use crate::state::{FINISHED_JOBS, PENDING_JOBS};
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::dependency::validate_dependencies;
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Addr, Storage, Uint128, Uint64};
+use warp_protocol::controller::condition::{
+    Condition, Expr, JobStatusExpr, NumOp, StatusOp, TimeExpr,
+};
+use warp_protocol::controller::job::{Job, JobStatus};
+
+fn job_status(job_id: u64, op: StatusOp, status: JobStatus) -> Condition {
+    Condition::Expr(Expr::JobStatus(JobStatusExpr {
+        job_id: Uint64::new(job_id),
+        op,
+        status,
+    }))
+}
+
+fn no_dependencies() -> Condition {
+    Condition::Expr(Expr::Timestamp(TimeExpr {
+        comparator: Uint64::zero(),
+        op: NumOp::Gt,
+        anchor: None,
+    }))
+}
+
+fn save_job(storage: &mut dyn Storage, id: u64, status: JobStatus, condition: Condition) {
+    let job = Job {
+        id: Uint64::new(id),
+        owner: Addr::unchecked("vlad"),
+        last_update_time: Uint64::zero(),
+        last_update_height: None,
+        creation_time: None,
+        creation_height: None,
+        name: "job".to_string(),
+        status: status.clone(),
+        condition,
+        msgs: vec![],
+        reward: Uint128::new(100),
+        template: None,
+        snapshots: None,
+        selectors: None,
+        signer: None,
+        recurrence: None,
+        executions: None,
+        last_execution_time: None,
+        expiry: None,
+        reward_denom: None,
+    };
+    match status {
+        JobStatus::Pending => PENDING_JOBS().save(storage, id, &job).unwrap(),
+        _ => FINISHED_JOBS().save(storage, id, &job).unwrap(),
+    }
+}
+
+#[test]
+fn test_resolve_job_status() {
+    let mut deps = mock_dependencies();
+    save_job(&mut deps.storage, 1, JobStatus::Executed, no_dependencies());
+    save_job(&mut deps.storage, 2, JobStatus::Pending, no_dependencies());
+
+    for (condition, expected) in [
+        (job_status(1, StatusOp::Eq, JobStatus::Executed), Ok(true)),
+        (job_status(1, StatusOp::Neq, JobStatus::Pending), Ok(true)),
+        (job_status(2, StatusOp::Neq, JobStatus::Pending), Ok(false)),
+        (
+            job_status(3, StatusOp::Eq, JobStatus::Executed),
+            Err(ContractError::DependencyDoesNotExist {
+                job_id: Uint64::new(3),
+            }),
+        ),
+    ] {
+        assert_eq!(
+            resolve_cond(
+                deps.as_ref(),
+                mock_env(),
+                &mut ResolveContext::default(),
+                condition
+            ),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_validate_dependencies() {
+    let mut deps = mock_dependencies();
+    // 3 -> 2 -> 1, 1 is finished
+    save_job(&mut deps.storage, 1, JobStatus::Executed, no_dependencies());
+    save_job(
+        &mut deps.storage,
+        2,
+        JobStatus::Pending,
+        job_status(1, StatusOp::Eq, JobStatus::Executed),
+    );
+    save_job(
+        &mut deps.storage,
+        3,
+        JobStatus::Pending,
+        Condition::Not(Box::new(job_status(2, StatusOp::Eq, JobStatus::Pending))),
+    );
+
+    assert_eq!(
+        validate_dependencies(
+            deps.as_ref(),
+            Uint64::new(4),
+            &job_status(3, StatusOp::Eq, JobStatus::Executed)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        validate_dependencies(
+            deps.as_ref(),
+            Uint64::new(2),
+            &Condition::And(vec![
+                Box::new(no_dependencies()),
+                Box::new(job_status(3, StatusOp::Eq, JobStatus::Executed)),
+            ])
+        ),
+        Err(ContractError::DependencyCycle {
+            job_id: Uint64::new(2)
+        })
+    );
+    assert_eq!(
+        validate_dependencies(
+            deps.as_ref(),
+            Uint64::new(4),
+            &job_status(4, StatusOp::Eq, JobStatus::Executed)
+        ),
+        Err(ContractError::DependencyCycle {
+            job_id: Uint64::new(4)
+        })
+    );
+    assert_eq!(
+        validate_dependencies(
+            deps.as_ref(),
+            Uint64::new(4),
+            &job_status(9, StatusOp::Eq, JobStatus::Executed)
+        ),
+        Err(ContractError::DependencyDoesNotExist {
+            job_id: Uint64::new(9)
+        })
+    );
+}
@@ -0,0 +1,193 @@
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::explain::explain_cond;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    Binary, ContractResult, Decimal256, QueryRequest, SystemResult, Uint256, Uint64, WasmQuery,
+};
+use std::str::FromStr;
+use warp_protocol::controller::condition::{
+    ArrayCondition, ArrayValue, CalendarExpr, CalendarField, Condition, Expr, ExprTrace, GenExpr,
+    NumOp, NumValue, QueryExpr, StringOp, TraceNode, Value,
+};
+
+fn pool_query_expr(selector: &str) -> QueryExpr {
+    QueryExpr {
+        selector: selector.to_string(),
+        query: QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1pool".to_string(),
+            msg: Binary::from("{\"pool\":{}}".as_bytes()),
+        })
+        .into(),
+        name: "pool".to_string(),
+        default: None,
+        decimals: None,
+        snapshot: None,
+    }
+}
+
+const POOL_RESPONSE: &str =
+    "{\"price\":\"1.5\",\"assets\":[{\"amount\":\"5\"},{\"amount\":\"50\"}]}";
+
+#[test]
+fn test_explain_cond() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(POOL_RESPONSE.as_bytes())))
+    });
+
+    let cond = Condition::And(vec![
+        Box::new(Condition::Expr(Expr::Decimal(GenExpr {
+            left: NumValue::Query(pool_query_expr("$.price")),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Decimal256::from_str("1.05").unwrap()),
+        }))),
+        Box::new(Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Query(pool_query_expr("$.reserve")),
+            op: NumOp::Gte,
+            right: NumValue::Simple(Uint256::from(100u64)),
+        }))),
+    ]);
+
+    let mut ctx = ResolveContext::default();
+    let trace = explain_cond(deps.as_ref(), mock_env(), &mut ctx, cond);
+
+    assert_eq!(trace.node, TraceNode::And);
+    assert_eq!(trace.result, None);
+    assert_eq!(trace.error, Some("Error decoding JSON result".to_string()));
+    assert!(trace.queries.is_empty());
+    assert!(ctx.trace.is_none());
+
+    let price = &trace.children[0];
+    assert_eq!(price.result, Some(true));
+    assert_eq!(
+        price.expr,
+        Some(ExprTrace {
+            kind: "decimal".to_string(),
+            op: Some("gt".to_string()),
+            left: Some("1.5".to_string()),
+            right: Some("1.05".to_string()),
+        })
+    );
+    assert_eq!(price.queries.len(), 1);
+    assert_eq!(price.queries[0].selector, "$.price");
+    assert_eq!(price.queries[0].response, POOL_RESPONSE);
+    assert_eq!(price.queries[0].value, Some("\"1.5\"".to_string()));
+
+    let reserve = &trace.children[1];
+    assert_eq!(reserve.result, None);
+    assert_eq!(reserve.error, trace.error);
+    assert_eq!(reserve.expr.as_ref().unwrap().left, None);
+    assert_eq!(
+        reserve.expr.as_ref().unwrap().right,
+        Some("100".to_string())
+    );
+    assert_eq!(reserve.queries[0].value, None);
+}
+
+#[test]
+fn test_explain_cond_array_elements() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(POOL_RESPONSE.as_bytes())))
+    });
+
+    let cond = Condition::Any(ArrayCondition {
+        array: ArrayValue::Query(pool_query_expr("$.assets")),
+        condition: Box::new(Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Element("$.amount".to_string()),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Uint256::from(10u64)),
+        }))),
+    });
+
+    let trace = explain_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        cond,
+    );
+
+    assert_eq!(trace.node, TraceNode::Any);
+    assert_eq!(trace.result, Some(true));
+    assert_eq!(trace.queries.len(), 1);
+    assert_eq!(trace.children.len(), 2);
+    assert_eq!(trace.children[0].result, Some(false));
+    assert_eq!(
+        trace.children[0].expr.as_ref().unwrap().left,
+        Some("5".to_string())
+    );
+    assert_eq!(trace.children[1].result, Some(true));
+}
+
+#[test]
+fn test_explain_cond_non_json_response() {
+    let mut deps = mock_dependencies();
+    deps.querier
+        .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(Binary::from("not json".as_bytes()))));
+
+    let trace = explain_cond(
+        deps.as_ref(),
+        mock_env(),
+        &mut ResolveContext::default(),
+        Condition::Expr(Expr::Decimal(GenExpr {
+            left: NumValue::Query(pool_query_expr("$.price")),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Decimal256::from_str("1.05").unwrap()),
+        })),
+    );
+
+    assert_eq!(trace.result, None);
+    assert!(trace.error.is_some());
+    assert_eq!(trace.queries.len(), 1);
+    assert_eq!(trace.queries[0].response, "not json");
+    assert_eq!(trace.queries[0].value, None);
+}
+
+#[test]
+fn test_explain_cond_matches_resolution() {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(POOL_RESPONSE.as_bytes())))
+    });
+
+    let price = Condition::Expr(Expr::Decimal(GenExpr {
+        left: NumValue::Query(pool_query_expr("$.price")),
+        op: NumOp::Gt,
+        right: NumValue::Simple(Decimal256::from_str("1.05").unwrap()),
+    }));
+    let missing = Condition::Expr(Expr::String(GenExpr {
+        left: Value::Query(pool_query_expr("$.missing")),
+        op: StringOp::Eq,
+        right: Value::Simple("x".to_string()),
+    }));
+    let calendar = Condition::Expr(Expr::Calendar(CalendarExpr {
+        field: CalendarField::DayOfWeek,
+        op: NumOp::Lte,
+        comparator: Uint64::new(5),
+    }));
+
+    let conds = vec![
+        Condition::Or(vec![Box::new(missing.clone()), Box::new(price.clone())]),
+        Condition::Or(vec![Box::new(price.clone()), Box::new(missing.clone())]),
+        Condition::And(vec![Box::new(calendar.clone()), Box::new(missing)]),
+        Condition::Not(Box::new(calendar)),
+        price,
+    ];
+
+    for cond in conds {
+        let trace = explain_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            cond.clone(),
+        );
+        let result = resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            cond,
+        );
+        assert_eq!(trace.result, result.as_ref().ok().copied());
+        assert_eq!(trace.error, result.err().map(|err| err.to_string()));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::normalize::normalize_condition;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Binary, QueryRequest, Uint256, Uint64, WasmQuery};
+use warp_protocol::controller::condition::{
+    Condition, Expr, GenExpr, IntFnOp, NumEnvValue, NumExprOp, NumExprValue, NumOp, NumValue,
+    OverflowPolicy, QueryExpr, TimeExpr,
+};
+
+fn query_cond(selector: &str) -> Box<Condition> {
+    Box::new(Condition::Expr(Expr::Bool(QueryExpr {
+        selector: selector.to_string(),
+        query: QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1pool".to_string(),
+            msg: Binary::from("{\"pool\":{}}".as_bytes()),
+        })
+        .into(),
+        name: "".to_string(),
+        default: None,
+        decimals: None,
+        snapshot: None,
+    })))
+}
+
+fn time_cond(comparator: u64) -> Box<Condition> {
+    Box::new(Condition::Expr(Expr::Timestamp(TimeExpr {
+        comparator: Uint64::new(comparator),
+        op: NumOp::Gt,
+        anchor: None,
+    })))
+}
+
+fn uint(value: u64) -> Box<NumValue<Uint256, NumExprOp, IntFnOp>> {
+    Box::new(NumValue::Simple(Uint256::from(value)))
+}
+
+fn uint_cond(left: NumValue<Uint256, NumExprOp, IntFnOp>) -> Condition {
+    Condition::Expr(Expr::Uint(GenExpr {
+        left,
+        op: NumOp::Gt,
+        right: NumValue::Simple(Uint256::zero()),
+    }))
+}
+
+#[test]
+fn test_normalize_condition_structure() {
+    let deps = mock_dependencies();
+    let env = mock_env();
+
+    // double negation, single children and nesting of the same kind
+    assert_eq!(
+        normalize_condition(
+            deps.as_ref(),
+            &env,
+            Condition::Not(Box::new(Condition::Not(Box::new(Condition::And(vec![
+                Box::new(Condition::Or(vec![time_cond(1)])),
+                Box::new(Condition::And(vec![time_cond(2), time_cond(3)])),
+            ])))))
+        ),
+        Condition::And(vec![time_cond(1), time_cond(2), time_cond(3)])
+    );
+
+    // repeated children are dropped, nesting of the other kind is kept
+    assert_eq!(
+        normalize_condition(
+            deps.as_ref(),
+            &env,
+            Condition::Or(vec![
+                time_cond(1),
+                Box::new(Condition::And(vec![time_cond(2), time_cond(3)])),
+                time_cond(1),
+            ])
+        ),
+        Condition::Or(vec![
+            time_cond(1),
+            Box::new(Condition::And(vec![time_cond(2), time_cond(3)])),
+        ])
+    );
+}
+
+#[test]
+fn test_normalize_condition_keeps_results() {
+    let deps = mock_dependencies();
+    let env = mock_env();
+
+    // the mock querier fails every query
+    let conds = vec![
+        Condition::Or(vec![query_cond("$.a"), time_cond(0)]),
+        Condition::And(vec![query_cond("$.a"), time_cond(u64::MAX)]),
+        Condition::And(vec![time_cond(u64::MAX), query_cond("$.a")]),
+        Condition::Or(vec![
+            Box::new(Condition::Not(Box::new(Condition::Not(time_cond(0))))),
+            Box::new(Condition::Or(vec![query_cond("$.a")])),
+        ]),
+        Condition::And(vec![
+            Box::new(Condition::And(vec![time_cond(0), query_cond("$.a")])),
+            time_cond(u64::MAX),
+            time_cond(0),
+        ]),
+        uint_cond(NumValue::Expr(NumExprValue {
+            left: uint(0),
+            op: NumExprOp::Sub,
+            right: uint(1),
+            overflow: Some(OverflowPolicy::Error),
+        })),
+    ];
+
+    for cond in conds {
+        let normalized = normalize_condition(deps.as_ref(), &env, cond.clone());
+        assert_eq!(
+            resolve_cond(
+                deps.as_ref(),
+                env.clone(),
+                &mut ResolveContext::default(),
+                normalized
+            ),
+            resolve_cond(
+                deps.as_ref(),
+                env.clone(),
+                &mut ResolveContext::default(),
+                cond
+            )
+        );
+    }
+
+    // children keep their order
+    assert_eq!(
+        normalize_condition(
+            deps.as_ref(),
+            &env,
+            Condition::And(vec![query_cond("$.a"), time_cond(1)])
+        ),
+        Condition::And(vec![query_cond("$.a"), time_cond(1)])
+    );
+}
+
+#[test]
+fn test_normalize_condition_folds_constants() {
+    let deps = mock_dependencies();
+    let env = mock_env();
+
+    // (2 + 3) * block.time, only the literal subtree is folded
+    assert_eq!(
+        normalize_condition(
+            deps.as_ref(),
+            &env,
+            uint_cond(NumValue::Expr(NumExprValue {
+                left: Box::new(NumValue::Expr(NumExprValue {
+                    left: uint(2),
+                    op: NumExprOp::Add,
+                    right: uint(3),
+                    overflow: None,
+                })),
+                op: NumExprOp::Mul,
+                right: Box::new(NumValue::Env(NumEnvValue::Time)),
+                overflow: None,
+            }))
+        ),
+        uint_cond(NumValue::Expr(NumExprValue {
+            left: uint(5),
+            op: NumExprOp::Mul,
+            right: Box::new(NumValue::Env(NumEnvValue::Time)),
+            overflow: None,
+        }))
+    );
+
+    // literal subtrees fold all the way up
+    assert_eq!(
+        normalize_condition(
+            deps.as_ref(),
+            &env,
+            uint_cond(NumValue::Expr(NumExprValue {
+                left: Box::new(NumValue::Expr(NumExprValue {
+                    left: uint(2),
+                    op: NumExprOp::Pow,
+                    right: uint(10),
+                    overflow: None,
+                })),
+                op: NumExprOp::Sub,
+                right: uint(24),
+                overflow: None,
+            }))
+        ),
+        uint_cond(NumValue::Simple(Uint256::from(1000u64)))
+    );
+
+    // failures are kept so they still fail when the job is executed
+    let failing = uint_cond(NumValue::Expr(NumExprValue {
+        left: uint(0),
+        op: NumExprOp::Sub,
+        right: uint(1),
+        overflow: Some(OverflowPolicy::Error),
+    }));
+    assert_eq!(
+        normalize_condition(deps.as_ref(), &env, failing.clone()),
+        failing
+    );
+}
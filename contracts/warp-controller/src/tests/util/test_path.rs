@@ -1 +1,119 @@
+use crate::util::path::resolve_path;
+use crate::ContractError;
+use json_codec_wasm::ast::Json;
+use json_codec_wasm::Decoder;
+use warp_protocol::controller::path::{parse_path, PathError, PathSegment};
 
+fn json(s: &str) -> Json {
+    Decoder::default(s.chars()).decode().unwrap()
+}
+
+fn string(s: &str) -> Option<Json> {
+    Some(Json::String(s.to_string()))
+}
+
+#[test]
+fn test_parse_path() {
+    assert_eq!(
+        parse_path("$.a[0][-1]['b.c'].length()").unwrap(),
+        vec![
+            PathSegment::Key("a".to_string()),
+            PathSegment::Index(0),
+            PathSegment::Index(-1),
+            PathSegment::Key("b.c".to_string()),
+            PathSegment::Length,
+        ]
+    );
+
+    // legacy selectors without the root
+    assert_eq!(
+        parse_path("a.b").unwrap(),
+        vec![
+            PathSegment::Key("a".to_string()),
+            PathSegment::Key("b".to_string()),
+        ]
+    );
+    // stored before the grammar, with the root but no dot
+    assert_eq!(
+        parse_path("$a.b[1]").unwrap(),
+        vec![
+            PathSegment::Key("a".to_string()),
+            PathSegment::Key("b".to_string()),
+            PathSegment::Index(1),
+        ]
+    );
+    assert_eq!(parse_path("").unwrap(), vec![]);
+    assert_eq!(parse_path("$").unwrap(), vec![]);
+}
+
+#[test]
+fn test_parse_path_errors() {
+    assert_eq!(
+        parse_path("$.a[0"),
+        Err(PathError::UnexpectedEnd { position: 5 })
+    );
+    assert_eq!(
+        parse_path("$.a[\"b]"),
+        Err(PathError::UnexpectedEnd { position: 7 })
+    );
+    assert_eq!(
+        parse_path("$.a..b"),
+        Err(PathError::EmptyKey { position: 4 })
+    );
+    assert_eq!(
+        parse_path("$.a[x]"),
+        Err(PathError::InvalidIndex { position: 4 })
+    );
+    assert_eq!(
+        parse_path("$.a]"),
+        Err(PathError::UnexpectedCharacter {
+            character: ']',
+            position: 3
+        })
+    );
+    assert_eq!(
+        parse_path("$.a.length().b"),
+        Err(PathError::LengthNotLast { position: 12 })
+    );
+}
+
+fn select(value: &Json, selector: &str) -> Result<Option<Json>, ContractError> {
+    resolve_path(value, &parse_path(selector)?)
+}
+
+#[test]
+fn test_resolve_path() {
+    let value =
+        json(r#"{"pools":[{"assets":["uluna","uusd"]},{"assets":["ukrw"]}],"a.b":{"c":"dotted"}}"#);
+
+    assert_eq!(
+        select(&value, "$.pools[0].assets[1]").unwrap(),
+        string("uusd")
+    );
+    assert_eq!(
+        select(&value, "$.pools[-1].assets[0]").unwrap(),
+        string("ukrw")
+    );
+    assert_eq!(select(&value, "$['a.b'].c").unwrap(), string("dotted"));
+    assert_eq!(
+        select(&value, "$.pools.length()").unwrap(),
+        Some(Json::U128(2))
+    );
+    assert_eq!(
+        select(&value, "$.pools[0].assets[0].length()").unwrap(),
+        Some(Json::U128(5))
+    );
+    assert_eq!(
+        select(&value, "$pools[1].assets[0]").unwrap(),
+        string("ukrw")
+    );
+    assert_eq!(select(&value, "$.pools[2]").unwrap(), None);
+    assert_eq!(select(&value, "$.pools[-3]").unwrap(), None);
+    assert_eq!(select(&value, "$.missing.length()").unwrap(), None);
+    assert_eq!(
+        select(&value, "$.pools[0"),
+        Err(ContractError::InvalidSelector(PathError::UnexpectedEnd {
+            position: 9
+        }))
+    );
+}
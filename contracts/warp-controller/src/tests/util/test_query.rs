@@ -0,0 +1,286 @@
+use crate::util::condition::{lower_query, resolve_cond, ResolveContext};
+use crate::util::validation::validate_condition;
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, MockStorage};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, BankQuery, Binary, Coin, ContractResult, QueryRequest, Storage,
+    SystemResult, Uint128, Uint256, Uint64, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use cw_storage_plus::Map;
+use warp_protocol::controller::condition::{
+    Condition, Expr, GenExpr, NumOp, NumValue, QueryExpr, QuerySource, TypedQuery,
+};
+use warp_protocol::controller::controller::Config;
+use warp_protocol::controller::storage::{KeyPart, StorageKey};
+
+fn balance_above(query: TypedQuery, selector: &str, amount: u128) -> Condition {
+    Condition::Expr(Expr::Uint(GenExpr {
+        left: NumValue::Query(QueryExpr {
+            selector: selector.to_string(),
+            query: QuerySource::Typed(query),
+            name: "".to_string(),
+            default: None,
+            decimals: None,
+            snapshot: None,
+        }),
+        op: NumOp::Gt,
+        right: NumValue::Simple(Uint256::from(amount)),
+    }))
+}
+
+#[test]
+fn test_lower_query() {
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::NativeBalance {
+            address: "terra1vlad".to_string(),
+            denom: "uluna".to_string(),
+        })),
+        Ok(Some(QueryRequest::Bank(BankQuery::Balance {
+            address: "terra1vlad".to_string(),
+            denom: "uluna".to_string(),
+        })))
+    );
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::Cw20Balance {
+            contract: "terra1token".to_string(),
+            address: "terra1vlad".to_string(),
+        })),
+        Ok(Some(QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1token".to_string(),
+            msg: to_binary(&Cw20QueryMsg::Balance {
+                address: "terra1vlad".to_string(),
+            })
+            .unwrap(),
+        })))
+    );
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::Cw20TokenInfo {
+            contract: "terra1token".to_string(),
+        })),
+        Ok(Some(QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1token".to_string(),
+            msg: to_binary(&Cw20QueryMsg::TokenInfo {}).unwrap(),
+        })))
+    );
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::Smart {
+            contract: "terra1pool".to_string(),
+            msg: "{\"pool\":{}}".to_string(),
+        })),
+        Ok(Some(QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1pool".to_string(),
+            msg: Binary::from("{\"pool\":{}}".as_bytes()),
+        })))
+    );
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::ContractInfo {
+            contract: "terra1pool".to_string(),
+        })),
+        Ok(Some(QueryRequest::Wasm(WasmQuery::ContractInfo {
+            contract_addr: "terra1pool".to_string(),
+        })))
+    );
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::Smart {
+            contract: "terra1pool".to_string(),
+            msg: "{\"pool\":".to_string(),
+        })),
+        Err(ContractError::InvalidQueryMsg {})
+    );
+}
+
+#[test]
+fn test_query_source_json() {
+    // requests stored before typed queries still read as requests
+    let request = QueryRequest::Bank(BankQuery::Balance {
+        address: "terra1vlad".to_string(),
+        denom: "uluna".to_string(),
+    });
+    assert_eq!(
+        serde_json_wasm::from_str::<QuerySource>(&serde_json_wasm::to_string(&request).unwrap()),
+        Ok(QuerySource::Request(request))
+    );
+    assert_eq!(
+        serde_json_wasm::from_str::<QuerySource>(
+            r#"{"cw20_balance":{"contract":"terra1token","address":"terra1vlad"}}"#
+        ),
+        Ok(QuerySource::Typed(TypedQuery::Cw20Balance {
+            contract: "terra1token".to_string(),
+            address: "terra1vlad".to_string(),
+        }))
+    );
+}
+
+#[test]
+fn test_resolve_typed_query() {
+    let mut deps = mock_dependencies();
+    deps.querier
+        .update_balance("terra1vlad", vec![Coin::new(1000, "uluna")]);
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == "terra1token" => {
+            match from_binary(msg).unwrap() {
+                Cw20QueryMsg::Balance { address } if address == "terra1vlad" => SystemResult::Ok(
+                    ContractResult::Ok(Binary::from("{\"balance\":\"500\"}".as_bytes())),
+                ),
+                _ => SystemResult::Ok(ContractResult::Err("unexpected query".to_string())),
+            }
+        }
+        _ => SystemResult::Ok(ContractResult::Err("unexpected contract".to_string())),
+    });
+
+    let native = TypedQuery::NativeBalance {
+        address: "terra1vlad".to_string(),
+        denom: "uluna".to_string(),
+    };
+    let cw20 = TypedQuery::Cw20Balance {
+        contract: "terra1token".to_string(),
+        address: "terra1vlad".to_string(),
+    };
+
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            balance_above(native.clone(), "$.amount.amount", 999)
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            balance_above(native, "$.amount.amount", 1000)
+        ),
+        Ok(false)
+    );
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            balance_above(cw20, "$.balance", 100)
+        ),
+        Ok(true)
+    );
+}
+
+#[test]
+fn test_resolve_raw_query() {
+    let mut storage = MockStorage::new();
+    let positions: Map<(&Addr, u64), BalanceResponse> = Map::new("positions");
+    positions
+        .save(
+            &mut storage,
+            (&Addr::unchecked("terra1vlad"), 7),
+            &BalanceResponse {
+                balance: Uint128::new(250),
+            },
+        )
+        .unwrap();
+
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Raw { contract_addr, key } if contract_addr == "terra1positions" => {
+            SystemResult::Ok(ContractResult::Ok(Binary::from(
+                storage.get(key).unwrap_or_default(),
+            )))
+        }
+        _ => SystemResult::Ok(ContractResult::Err("unexpected query".to_string())),
+    });
+
+    let raw = |id: u64| TypedQuery::Raw {
+        contract: "terra1positions".to_string(),
+        key: StorageKey::Map {
+            namespace: "positions".to_string(),
+            key: vec![
+                KeyPart::Addr("terra1vlad".to_string()),
+                KeyPart::U64(Uint64::new(id)),
+            ],
+        },
+    };
+
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            balance_above(raw(7), "$.balance", 200)
+        ),
+        Ok(true)
+    );
+
+    // a missing entry reads as null, which the default stands in for
+    let mut missing = balance_above(raw(8), "$.balance", 200);
+    if let Condition::Expr(Expr::Uint(GenExpr {
+        left: NumValue::Query(query),
+        ..
+    })) = &mut missing
+    {
+        query.default = Some("\"300\"".to_string());
+    }
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            mock_env(),
+            &mut ResolveContext::default(),
+            missing
+        ),
+        Ok(true)
+    );
+
+    assert_eq!(
+        lower_query(QuerySource::Typed(TypedQuery::Raw {
+            contract: "terra1positions".to_string(),
+            key: StorageKey::Map {
+                namespace: "positions".to_string(),
+                key: vec![],
+            },
+        })),
+        Err(ContractError::InvalidStorageKey {})
+    );
+}
+
+#[test]
+fn test_validate_typed_query() {
+    let config = Config {
+        owner: Addr::unchecked("owner"),
+        warp_account_code_id: Uint64::zero(),
+        reward_denoms: vec![],
+        creation_fee_percentage: Uint128::zero(),
+        cancellation_fee_percentage: Uint128::zero(),
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: None,
+    };
+
+    assert_eq!(
+        validate_condition(
+            &config,
+            &balance_above(
+                TypedQuery::Smart {
+                    contract: "terra1pool".to_string(),
+                    msg: "pool".to_string(),
+                },
+                "$.price",
+                0
+            )
+        ),
+        Err(ContractError::InvalidQueryMsg {})
+    );
+    assert!(validate_condition(
+        &config,
+        &balance_above(
+            TypedQuery::Smart {
+                contract: "terra1pool".to_string(),
+                msg: "{\"pool\":{}}".to_string(),
+            },
+            "$.reserve",
+            0
+        )
+    )
+    .is_ok());
+}
@@ -0,0 +1,265 @@
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::signed::{validate_signer, verify_signed_data};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Addr, Binary, Decimal256, Env, Uint128, Uint64};
+use ed25519_zebra::{SigningKey as Ed25519Key, VerificationKey};
+use k256::ecdsa::signature::Signer;
+use k256::ecdsa::{Signature, SigningKey as Secp256k1Key};
+use std::str::FromStr;
+use warp_protocol::controller::condition::{
+    Condition, Expr, GenExpr, NumOp, NumValue, QueryExpr, QuerySource, TypedQuery,
+};
+use warp_protocol::controller::job::{DataSigner, Job, JobStatus, SignatureAlgorithm, SignedData};
+
+// price reported by the signer above 1.5
+fn price_condition() -> Condition {
+    Condition::Expr(Expr::Decimal(GenExpr {
+        left: NumValue::Query(QueryExpr {
+            selector: "$.price".to_string(),
+            query: QuerySource::Typed(TypedQuery::SignedData {}),
+            name: "".to_string(),
+            default: None,
+            decimals: None,
+            snapshot: None,
+        }),
+        op: NumOp::Gt,
+        right: NumValue::Simple(Decimal256::from_str("1.5").unwrap()),
+    }))
+}
+
+fn job(signer: Option<DataSigner>) -> Job {
+    Job {
+        id: Uint64::new(3),
+        owner: Addr::unchecked("vlad"),
+        last_update_time: Uint64::zero(),
+        last_update_height: None,
+        creation_time: None,
+        creation_height: None,
+        name: "job".to_string(),
+        status: JobStatus::Pending,
+        condition: price_condition(),
+        msgs: vec![],
+        reward: Uint128::new(100),
+        template: None,
+        snapshots: None,
+        selectors: None,
+        signer,
+        recurrence: None,
+        executions: None,
+        last_execution_time: None,
+        expiry: None,
+        reward_denom: None,
+    }
+}
+
+fn payload(env: &Env, job_id: u64, age: u64, price: &str) -> String {
+    format!(
+        "{{\"job_id\":\"{}\",\"timestamp\":\"{}\",\"data\":{{\"price\":\"{}\"}}}}",
+        job_id,
+        env.block.time.seconds() - age,
+        price
+    )
+}
+
+fn secp256k1_signer() -> (Secp256k1Key, DataSigner) {
+    let key = Secp256k1Key::from_bytes(&[7u8; 32]).unwrap();
+    let signer = DataSigner {
+        algorithm: SignatureAlgorithm::Secp256k1,
+        public_key: Binary::from(key.verifying_key().to_bytes().as_slice()),
+        max_age: Uint64::new(60),
+    };
+    (key, signer)
+}
+
+fn secp256k1_sign(key: &Secp256k1Key, payload: String) -> SignedData {
+    let signature: Signature = key.sign(payload.as_bytes());
+    SignedData {
+        payload,
+        signature: Binary::from(signature.as_ref()),
+    }
+}
+
+#[test]
+fn test_verify_signed_data_secp256k1() {
+    let deps = mock_dependencies();
+    let env = mock_env();
+    let (key, signer) = secp256k1_signer();
+    let job = job(Some(signer));
+
+    let data = verify_signed_data(
+        &deps.api,
+        &env,
+        &job,
+        Some(secp256k1_sign(&key, payload(&env, 3, 10, "1.7"))),
+    )
+    .unwrap();
+    assert_eq!(data, Some("{\"price\":\"1.7\"}".to_string()));
+
+    let mut ctx = ResolveContext::with_job(job.clone());
+    ctx.signed_data = data;
+    assert_eq!(
+        resolve_cond(deps.as_ref(), env.clone(), &mut ctx, job.condition.clone()),
+        Ok(true)
+    );
+
+    // signed for another job
+    assert_eq!(
+        verify_signed_data(
+            &deps.api,
+            &env,
+            &job,
+            Some(secp256k1_sign(&key, payload(&env, 4, 10, "1.7")))
+        ),
+        Err(ContractError::InvalidSignedData {})
+    );
+
+    assert_eq!(
+        verify_signed_data(
+            &deps.api,
+            &env,
+            &job,
+            Some(secp256k1_sign(&key, payload(&env, 3, 61, "1.7")))
+        ),
+        Err(ContractError::StaleSignedData {})
+    );
+
+    let mut tampered = secp256k1_sign(&key, payload(&env, 3, 10, "1.7"));
+    tampered.payload = payload(&env, 3, 10, "1.4");
+    assert_eq!(
+        verify_signed_data(&deps.api, &env, &job, Some(tampered)),
+        Err(ContractError::InvalidSignature {})
+    );
+
+    let other = Secp256k1Key::from_bytes(&[8u8; 32]).unwrap();
+    assert_eq!(
+        verify_signed_data(
+            &deps.api,
+            &env,
+            &job,
+            Some(secp256k1_sign(&other, payload(&env, 3, 10, "1.7")))
+        ),
+        Err(ContractError::InvalidSignature {})
+    );
+}
+
+#[test]
+fn test_verify_signed_data_replay() {
+    let deps = mock_dependencies();
+    let mut env = mock_env();
+    let (key, signer) = secp256k1_signer();
+    let mut job = job(Some(signer));
+
+    let signed_data = secp256k1_sign(&key, payload(&env, 3, 10, "1.7"));
+    assert!(verify_signed_data(&deps.api, &env, &job, Some(signed_data.clone())).is_ok());
+
+    // the job executed with the payload, it cannot be used for the next execution
+    job.last_execution_time = Some(Uint64::new(env.block.time.seconds()));
+    env.block.time = env.block.time.plus_seconds(5);
+    assert_eq!(
+        verify_signed_data(&deps.api, &env, &job, Some(signed_data)),
+        Err(ContractError::StaleSignedData {})
+    );
+    assert_eq!(
+        verify_signed_data(
+            &deps.api,
+            &env,
+            &job,
+            Some(secp256k1_sign(&key, payload(&env, 3, 5, "1.7")))
+        ),
+        Err(ContractError::StaleSignedData {})
+    );
+
+    // signed after the last execution
+    assert!(verify_signed_data(
+        &deps.api,
+        &env,
+        &job,
+        Some(secp256k1_sign(&key, payload(&env, 3, 4, "1.7")))
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_verify_signed_data_ed25519() {
+    let deps = mock_dependencies();
+    let env = mock_env();
+    let key = Ed25519Key::from([9u8; 32]);
+    let job = job(Some(DataSigner {
+        algorithm: SignatureAlgorithm::Ed25519,
+        public_key: Binary::from(<[u8; 32]>::from(VerificationKey::from(&key)).as_slice()),
+        max_age: Uint64::new(60),
+    }));
+
+    let payload = payload(&env, 3, 0, "1.2");
+    let signed_data = SignedData {
+        signature: Binary::from(<[u8; 64]>::from(key.sign(payload.as_bytes())).as_slice()),
+        payload,
+    };
+    let data = verify_signed_data(&deps.api, &env, &job, Some(signed_data)).unwrap();
+
+    let mut ctx = ResolveContext::with_job(job.clone());
+    ctx.signed_data = data;
+    assert_eq!(
+        resolve_cond(deps.as_ref(), env, &mut ctx, job.condition),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_verify_signed_data_attached() {
+    let deps = mock_dependencies();
+    let env = mock_env();
+    let (key, signer) = secp256k1_signer();
+
+    assert_eq!(
+        verify_signed_data(&deps.api, &env, &job(None), None),
+        Ok(None)
+    );
+    assert_eq!(
+        verify_signed_data(
+            &deps.api,
+            &env,
+            &job(None),
+            Some(secp256k1_sign(&key, payload(&env, 3, 10, "1.7")))
+        ),
+        Err(ContractError::UnexpectedSignedData {})
+    );
+    assert_eq!(
+        verify_signed_data(&deps.api, &env, &job(Some(signer)), None),
+        Err(ContractError::SignedDataMissing {})
+    );
+
+    // outside of an execution there is nothing to read
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            env,
+            &mut ResolveContext::default(),
+            price_condition()
+        ),
+        Err(ContractError::SignedDataMissing {})
+    );
+}
+
+#[test]
+fn test_validate_signer() {
+    let (_, signer) = secp256k1_signer();
+
+    assert_eq!(validate_signer(&price_condition(), Some(&signer)), Ok(()));
+    assert_eq!(
+        validate_signer(&price_condition(), None),
+        Err(ContractError::SignerRequired {})
+    );
+    assert_eq!(validate_signer(&Condition::And(vec![]), None), Ok(()));
+    assert_eq!(
+        validate_signer(
+            &price_condition(),
+            Some(&DataSigner {
+                algorithm: SignatureAlgorithm::Ed25519,
+                ..signer
+            })
+        ),
+        Err(ContractError::InvalidPublicKey {})
+    );
+}
@@ -0,0 +1,184 @@
+use crate::util::condition::{resolve_cond, ResolveContext};
+use crate::util::snapshot::capture_snapshots;
+use crate::util::validation::validate_condition;
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    Addr, Binary, ContractResult, Decimal256, QueryRequest, SystemResult, Uint128, Uint64,
+    WasmQuery,
+};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::str::FromStr;
+use warp_protocol::controller::condition::{
+    Condition, Expr, GenExpr, NumExprOp, NumExprValue, NumOp, NumValue, QueryExpr,
+};
+use warp_protocol::controller::controller::Config;
+use warp_protocol::controller::job::{Job, JobStatus};
+
+fn price_query(name: &str, snapshot: Option<bool>) -> QueryExpr {
+    QueryExpr {
+        selector: "$.price".to_string(),
+        query: QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "terra1pool".to_string(),
+            msg: Binary::from("{\"pool\":{}}".as_bytes()),
+        })
+        .into(),
+        name: name.to_string(),
+        default: None,
+        decimals: None,
+        snapshot,
+    }
+}
+
+// price fell below 90% of the baseline
+fn price_drop(live: QueryExpr, baseline: QueryExpr) -> Condition {
+    Condition::Expr(Expr::Decimal(GenExpr {
+        left: NumValue::Query(live),
+        op: NumOp::Lt,
+        right: NumValue::Expr(NumExprValue {
+            left: Box::new(NumValue::Query(baseline)),
+            op: NumExprOp::Mul,
+            right: Box::new(NumValue::Simple(Decimal256::from_str("0.9").unwrap())),
+            overflow: None,
+        }),
+    }))
+}
+
+fn config() -> Config {
+    Config {
+        owner: Addr::unchecked("owner"),
+        warp_account_code_id: Uint64::zero(),
+        reward_denoms: vec![],
+        creation_fee_percentage: Uint128::zero(),
+        cancellation_fee_percentage: Uint128::zero(),
+        max_condition_depth: None,
+        max_condition_queries: None,
+        max_condition_size: None,
+        cleanup_tip_percentage: None,
+    }
+}
+
+#[test]
+fn test_snapshot_resolves_from_job() {
+    let mut deps = mock_dependencies();
+    let price = Rc::new(Cell::new("2.0"));
+    let current = price.clone();
+    deps.querier.update_wasm(move |_| {
+        SystemResult::Ok(ContractResult::Ok(Binary::from(
+            format!("{{\"price\":\"{}\"}}", current.get()).as_bytes(),
+        )))
+    });
+
+    let condition = price_drop(price_query("", None), price_query("baseline", Some(true)));
+    let snapshots = capture_snapshots(deps.as_ref(), &mock_env(), &condition)
+        .unwrap()
+        .unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].name, "baseline");
+    assert_eq!(snapshots[0].response, "{\"price\":\"2.0\"}");
+
+    let env = mock_env();
+    let job = Job {
+        id: Uint64::new(1),
+        owner: Addr::unchecked("vlad"),
+        last_update_time: Uint64::new(env.block.time.seconds()),
+        last_update_height: Some(Uint64::new(env.block.height)),
+        creation_time: Some(Uint64::new(env.block.time.seconds())),
+        creation_height: Some(Uint64::new(env.block.height)),
+        name: "job".to_string(),
+        status: JobStatus::Pending,
+        condition: condition.clone(),
+        msgs: vec![],
+        reward: Uint128::new(100),
+        template: None,
+        snapshots: Some(snapshots),
+        selectors: None,
+        signer: None,
+        recurrence: None,
+        executions: None,
+        last_execution_time: None,
+        expiry: None,
+        reward_denom: None,
+    };
+
+    price.set("1.7");
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            env.clone(),
+            &mut ResolveContext::with_job(job),
+            condition.clone()
+        ),
+        Ok(true)
+    );
+
+    // without a job the baseline is the current price
+    assert_eq!(
+        resolve_cond(
+            deps.as_ref(),
+            env,
+            &mut ResolveContext::default(),
+            condition
+        ),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_capture_snapshots_none() {
+    let deps = mock_dependencies();
+    let condition = price_drop(price_query("", None), price_query("", None));
+
+    assert_eq!(
+        capture_snapshots(deps.as_ref(), &mock_env(), &condition),
+        Ok(None)
+    );
+}
+
+#[test]
+fn test_validate_snapshot_names() {
+    let config = config();
+
+    assert_eq!(
+        validate_condition(
+            &config,
+            &price_drop(price_query("", None), price_query("", Some(true)))
+        ),
+        Err(ContractError::UnnamedSnapshotQuery {})
+    );
+    assert_eq!(
+        validate_condition(
+            &config,
+            &price_drop(price_query("pool", None), price_query("pool", Some(true)))
+        ),
+        Err(ContractError::ConflictingSnapshotQuery {
+            name: "pool".to_string()
+        })
+    );
+
+    let mut other = price_query("baseline", Some(true));
+    other.query = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: "terra1other".to_string(),
+        msg: Binary::from("{\"pool\":{}}".as_bytes()),
+    })
+    .into();
+    assert_eq!(
+        validate_condition(
+            &config,
+            &price_drop(price_query("baseline", Some(true)), other)
+        ),
+        Err(ContractError::ConflictingSnapshotQuery {
+            name: "baseline".to_string()
+        })
+    );
+
+    assert!(validate_condition(
+        &config,
+        &price_drop(
+            price_query("baseline", Some(true)),
+            price_query("baseline", Some(true))
+        )
+    )
+    .is_ok());
+}
@@ -0,0 +1,200 @@
+use crate::util::validation::validate_condition;
+use crate::ContractError;
+use cosmwasm_std::{Addr, Binary, QueryRequest, Uint128, Uint256, Uint64, WasmQuery};
+use warp_protocol::controller::condition::{
+    Condition, Expr, GenExpr, JobAnchor, NumExprOp, NumExprValue, NumOp, NumValue, PeriodExpr,
+    QueryExpr,
+};
+use warp_protocol::controller::controller::Config;
+use warp_protocol::controller::path::{ParsedSelector, PathError, PathSegment};
+
+fn config(depth: Option<u64>, queries: Option<u64>, size: Option<u64>) -> Config {
+    Config {
+        owner: Addr::unchecked("owner"),
+        warp_account_code_id: Uint64::zero(),
+        reward_denoms: vec![],
+        creation_fee_percentage: Uint128::zero(),
+        cancellation_fee_percentage: Uint128::zero(),
+        max_condition_depth: depth.map(Uint64::new),
+        max_condition_queries: queries.map(Uint64::new),
+        max_condition_size: size.map(Uint64::new),
+        cleanup_tip_percentage: None,
+    }
+}
+
+fn query_cond(selector: &str) -> Condition {
+    named_query_cond("", "terra1pool", selector)
+}
+
+fn named_query_cond(name: &str, contract: &str, selector: &str) -> Condition {
+    Condition::Expr(Expr::Uint(GenExpr {
+        left: NumValue::Query(QueryExpr {
+            selector: selector.to_string(),
+            query: QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: contract.to_string(),
+                msg: Binary::from("{\"pool\":{}}".as_bytes()),
+            })
+            .into(),
+            name: name.to_string(),
+            default: None,
+            decimals: None,
+            snapshot: None,
+        }),
+        op: NumOp::Gt,
+        right: NumValue::Simple(Uint256::zero()),
+    }))
+}
+
+#[test]
+fn test_validate_condition_selectors() {
+    let config = config(None, None, None);
+
+    // kept once per selector, to be stored with the condition
+    let parsed = ParsedSelector {
+        selector: "$.assets[0].amount".to_string(),
+        path: vec![
+            PathSegment::Key("assets".to_string()),
+            PathSegment::Index(0),
+            PathSegment::Key("amount".to_string()),
+        ],
+    };
+    assert_eq!(
+        validate_condition(&config, &query_cond("$.assets[0].amount")),
+        Ok(vec![parsed.clone()])
+    );
+    assert_eq!(
+        validate_condition(
+            &config,
+            &Condition::Or(vec![
+                Box::new(query_cond("$.assets[0].amount")),
+                Box::new(query_cond("$.assets[0].amount")),
+            ])
+        ),
+        Ok(vec![parsed])
+    );
+    assert_eq!(
+        validate_condition(
+            &config,
+            &Condition::Not(Box::new(query_cond("$.assets[x].amount")))
+        ),
+        Err(ContractError::InvalidSelector(PathError::InvalidIndex {
+            position: 9
+        }))
+    );
+}
+
+#[test]
+fn test_validate_condition_query_names() {
+    let config = config(None, None, None);
+
+    // one named query read with several selectors
+    assert!(validate_condition(
+        &config,
+        &Condition::And(vec![
+            Box::new(named_query_cond("pool", "terra1pool", "$.price")),
+            Box::new(named_query_cond("pool", "terra1pool", "$.reserve")),
+        ])
+    )
+    .is_ok());
+    assert_eq!(
+        validate_condition(
+            &config,
+            &Condition::And(vec![
+                Box::new(named_query_cond("pool", "terra1pool", "$.price")),
+                Box::new(named_query_cond("pool", "terra1other", "$.price")),
+            ])
+        ),
+        Err(ContractError::ConflictingNamedQuery {
+            name: "pool".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_validate_condition_empty_list() {
+    assert_eq!(
+        validate_condition(&config(None, None, None), &Condition::And(vec![])),
+        Err(ContractError::EmptyCondition {})
+    );
+    assert_eq!(
+        validate_condition(
+            &config(None, None, None),
+            &Condition::And(vec![Box::new(Condition::Or(vec![]))])
+        ),
+        Err(ContractError::EmptyCondition {})
+    );
+}
+
+#[test]
+fn test_validate_condition_depth() {
+    // condition, expression and value
+    let cond = query_cond("$.amount");
+    assert!(validate_condition(&config(Some(3), None, None), &cond).is_ok());
+
+    let cond = Condition::Not(Box::new(cond));
+    assert_eq!(
+        validate_condition(&config(Some(3), None, None), &cond),
+        Err(ContractError::ConditionTooDeep { max_depth: 3 })
+    );
+
+    let mut value = NumValue::Simple(Uint256::one());
+    for _ in 0..30 {
+        value = NumValue::Expr(NumExprValue {
+            left: Box::new(value),
+            op: NumExprOp::Add,
+            right: Box::new(NumValue::Simple(Uint256::one())),
+            overflow: None,
+        });
+    }
+    let cond = Condition::Expr(Expr::Uint(GenExpr {
+        left: value,
+        op: NumOp::Gt,
+        right: NumValue::Simple(Uint256::zero()),
+    }));
+    assert_eq!(
+        validate_condition(&config(None, None, None), &cond),
+        Err(ContractError::ConditionTooDeep { max_depth: 20 })
+    );
+}
+
+#[test]
+fn test_validate_condition_queries_and_size() {
+    let cond = Condition::Or(vec![
+        Box::new(query_cond("$.a")),
+        Box::new(query_cond("$.b")),
+        Box::new(query_cond("$.c")),
+    ]);
+
+    assert!(validate_condition(&config(None, Some(3), None), &cond).is_ok());
+    assert_eq!(
+        validate_condition(&config(None, Some(2), None), &cond),
+        Err(ContractError::TooManyQueries { max_queries: 2 })
+    );
+
+    let size = serde_json_wasm::to_vec(&cond).unwrap().len() as u64;
+    assert!(validate_condition(&config(None, None, Some(size)), &cond).is_ok());
+    assert_eq!(
+        validate_condition(&config(None, None, Some(size - 1)), &cond),
+        Err(ContractError::ConditionTooLarge {
+            size,
+            max_size: size - 1
+        })
+    );
+}
+
+#[test]
+fn test_validate_condition_period() {
+    let period = |period: u64| {
+        Condition::Expr(Expr::Period(PeriodExpr {
+            period: Uint64::new(period),
+            anchor: JobAnchor::Creation,
+            tolerance: Uint64::zero(),
+        }))
+    };
+
+    assert!(validate_condition(&config(None, None, None), &period(60)).is_ok());
+    assert_eq!(
+        validate_condition(&config(None, None, None), &period(0)),
+        Err(ContractError::InvalidPeriod {})
+    );
+}
@@ -0,0 +1,83 @@
+use cosmwasm_std::{Decimal256, Uint256};
+use json_codec_wasm::ast::Json;
+use json_codec_wasm::Encoder;
+use std::str::FromStr;
+
+// typed decoding of selected JSON values, numbers are accepted both as JSON numbers and as strings
+pub trait FromJson: Sized {
+    // type name reported in errors
+    const TYPE: &'static str;
+
+    // decimals - the value is an integer scaled by 10^decimals, only applies to decimals
+    fn from_json(value: &Json, decimals: Option<u32>) -> Option<Self>;
+}
+
+impl FromJson for bool {
+    const TYPE: &'static str = "bool";
+
+    fn from_json(value: &Json, _decimals: Option<u32>) -> Option<Self> {
+        match value {
+            Json::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for String {
+    const TYPE: &'static str = "string";
+
+    fn from_json(value: &Json, _decimals: Option<u32>) -> Option<Self> {
+        match value {
+            Json::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for Uint256 {
+    const TYPE: &'static str = "uint";
+
+    fn from_json(value: &Json, _decimals: Option<u32>) -> Option<Self> {
+        match value {
+            Json::U128(value) => Some(Uint256::from(*value)),
+            Json::I128(value) => u128::try_from(*value).ok().map(Uint256::from),
+            Json::String(value) => Uint256::from_str(value).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for i128 {
+    const TYPE: &'static str = "int";
+
+    fn from_json(value: &Json, _decimals: Option<u32>) -> Option<Self> {
+        match value {
+            Json::U128(value) => i128::try_from(*value).ok(),
+            Json::I128(value) => Some(*value),
+            Json::String(value) => i128::from_str(value).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromJson for Decimal256 {
+    const TYPE: &'static str = "decimal";
+
+    fn from_json(value: &Json, decimals: Option<u32>) -> Option<Self> {
+        match (value, decimals) {
+            (Json::String(value), None) => Decimal256::from_str(value).ok(),
+            (value, decimals) => {
+                Decimal256::from_atomics(Uint256::from_json(value, None)?, decimals.unwrap_or(0))
+                    .ok()
+            }
+        }
+    }
+}
+
+pub fn encode_json(value: &Json) -> String {
+    let mut encoder = Encoder::new(Vec::new());
+    match encoder.encode(value) {
+        Ok(()) => String::from_utf8_lossy(&encoder.into_writer()).to_string(),
+        Err(_) => String::new(),
+    }
+}
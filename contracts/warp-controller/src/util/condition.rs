@@ -1,190 +1,671 @@
+use crate::state::{FINISHED_JOBS, PENDING_JOBS};
+use crate::util::coerce::{encode_json, FromJson};
+use crate::util::explain::{
+    serde_name, show, show_list, trace_cond, trace_op, trace_value, TraceFrame,
+};
 use crate::util::path::resolve_path;
 use crate::ContractError;
 use cosmwasm_std::{
-    to_vec, ContractResult, Decimal256, Deps, Env, StdError, SystemResult, Uint256,
+    to_binary, to_vec, BankQuery, Binary, ContractResult, Decimal256, Deps, Env, QueryRequest,
+    StdError, SystemResult, Uint128, Uint256, Uint64, WasmQuery,
 };
+use cw20::Cw20QueryMsg;
 use cw_storage_plus::KeyDeserialize;
-use json_codec_wasm::ast::Ref;
+use json_codec_wasm::ast::Json;
 use json_codec_wasm::Decoder;
-use std::str::FromStr;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use warp_protocol::controller::condition::{
-    BlockExpr, Condition, DecimalFnOp, Expr, GenExpr, IntFnOp, NumExprOp, NumExprValue, NumFnValue,
-    NumOp, NumValue, QueryExpr, StringOp, TimeExpr, TimeOp, Value,
+    AggregateOp, ArrayAggregate, ArrayValue, BlockExpr, CalendarExpr, CalendarField, CastValue,
+    Condition, DecimalFnOp, Expr, GenExpr, IntFnOp, JobAnchor, JobStatusExpr, ListOp, ListValue,
+    NumEnvValue, NumExprOp, NumExprValue, NumFnValue, NumOp, NumValue, OverflowPolicy, PeriodExpr,
+    QueryExpr, QuerySource, QueryTrace, StatusOp, StringEnvValue, StringOp, TimeExpr, TypedQuery,
+    Value,
 };
+use warp_protocol::controller::job::{Job, JobStatus};
+use warp_protocol::controller::path::{parse_path, PathSegment};
+
+// state shared by every node of a single condition resolution
+#[derive(Default)]
+pub struct ResolveContext {
+    // raw responses of named queries, keyed by `QueryExpr.name`
+    pub queries: BTreeMap<String, (QuerySource, String)>,
+    // job whose condition is being resolved, if any
+    pub job: Option<Job>,
+    // elements of the enclosing array conditions, innermost last
+    pub elements: Vec<Json>,
+    // node being resolved, only recorded when explaining a condition
+    pub trace: Option<TraceFrame>,
+    // `data` of the signed payload attached to the execution, once verified
+    pub signed_data: Option<String>,
+}
+
+impl ResolveContext {
+    pub fn with_job(job: Job) -> Self {
+        ResolveContext {
+            job: Some(job),
+            ..Default::default()
+        }
+    }
+
+    // the segments of a selector as parsed when the job's condition was stored, parsed here
+    // for conditions resolved without a job and jobs stored before selectors were kept
+    pub fn path(&self, selector: &str) -> Result<Cow<'_, [PathSegment]>, ContractError> {
+        let parsed = self
+            .job
+            .as_ref()
+            .and_then(|job| job.selectors.as_ref())
+            .and_then(|selectors| selectors.iter().find(|parsed| parsed.selector == selector));
+
+        match parsed {
+            Some(parsed) => Ok(Cow::Borrowed(&parsed.path)),
+            None => Ok(Cow::Owned(parse_path(selector)?)),
+        }
+    }
+}
 
-pub fn resolve_cond(deps: Deps, env: Env, cond: Condition) -> Result<bool, ContractError> {
+pub fn resolve_cond(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    cond: Condition,
+) -> Result<bool, ContractError> {
+    if ctx.trace.is_none() {
+        return resolve_cond_node(deps, env, ctx, cond);
+    }
+
+    let (trace, result) = trace_cond(deps, env, ctx, cond);
+    if let Some(frame) = ctx.trace.as_mut() {
+        frame.children.push(trace);
+    }
+    result
+}
+
+pub fn resolve_cond_node(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    cond: Condition,
+) -> Result<bool, ContractError> {
     match cond {
-        Condition::And(conds) => {
-            for cond in conds {
-                if !resolve_cond(deps, env.clone(), *cond)? {
-                    return Ok(false);
-                }
+        Condition::And(conds) => resolve_children(ctx, conds, false, |ctx, cond| {
+            resolve_cond(deps, env.clone(), ctx, *cond)
+        }),
+        Condition::Or(conds) => resolve_children(ctx, conds, true, |ctx, cond| {
+            resolve_cond(deps, env.clone(), ctx, *cond)
+        }),
+        Condition::Not(cond) => Ok(!resolve_cond(deps, env, ctx, *cond)?),
+        Condition::Expr(expr) => Ok(resolve_expr(deps, env, ctx, expr)?),
+        Condition::Any(cond) => {
+            let elements = resolve_array_value(deps, env.clone(), ctx, cond.array)?;
+            resolve_children(ctx, elements, true, |ctx, element| {
+                resolve_element_cond(deps, env.clone(), ctx, element, &cond.condition)
+            })
+        }
+        Condition::All(cond) => {
+            let elements = resolve_array_value(deps, env.clone(), ctx, cond.array)?;
+            resolve_children(ctx, elements, false, |ctx, element| {
+                resolve_element_cond(deps, env.clone(), ctx, element, &cond.condition)
+            })
+        }
+    }
+}
+
+// the first child resolving to `stop_on` decides and a failing child before it fails the node,
+// when explaining the children after it are still resolved so that the whole tree is reported
+fn resolve_children<T>(
+    ctx: &mut ResolveContext,
+    children: Vec<T>,
+    stop_on: bool,
+    mut resolve: impl FnMut(&mut ResolveContext, T) -> Result<bool, ContractError>,
+) -> Result<bool, ContractError> {
+    let mut decided = None;
+    for child in children {
+        if decided.is_some() && ctx.trace.is_none() {
+            break;
+        }
+        match resolve(ctx, child) {
+            Ok(result) if result != stop_on => {}
+            result => {
+                decided.get_or_insert(result);
             }
-            return Ok(true);
         }
-        Condition::Or(conds) => {
-            for cond in conds {
-                if resolve_cond(deps, env.clone(), *cond)? {
-                    return Ok(true);
-                }
+    }
+    decided.unwrap_or(Ok(!stop_on))
+}
+
+pub fn resolve_element_cond(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    element: Json,
+    cond: &Condition,
+) -> Result<bool, ContractError> {
+    ctx.elements.push(element);
+    let res = resolve_cond(deps, env, ctx, cond.clone());
+    ctx.elements.pop();
+    res
+}
+
+pub fn resolve_array_value(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    value: ArrayValue,
+) -> Result<Vec<Json>, ContractError> {
+    let (selector, array) = match value {
+        ArrayValue::Query(expr) => (
+            expr.selector.clone(),
+            resolve_query_expr_json(deps, env, ctx, expr)?,
+        ),
+        ArrayValue::Element(selector) => (selector.clone(), resolve_element(ctx, selector)?),
+    };
+
+    match array {
+        Json::Array(elements) => Ok(elements),
+        _ => Err(invalid_selector_value(&selector, "array")),
+    }
+}
+
+pub fn resolve_element(ctx: &ResolveContext, selector: String) -> Result<Json, ContractError> {
+    let element = ctx
+        .elements
+        .last()
+        .ok_or(ContractError::ElementUnavailable {})?;
+
+    resolve_path(element, &ctx.path(&selector)?)?.ok_or(ContractError::DecodeError {})
+}
+
+fn resolve_element_value<T: FromJson>(
+    ctx: &ResolveContext,
+    selector: String,
+) -> Result<T, ContractError> {
+    decode_value(&resolve_element(ctx, selector.clone())?, &selector, None)
+}
+
+// values the aggregate operates on: the selected field of every matching element
+fn resolve_aggregate_values(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: ArrayAggregate,
+) -> Result<Vec<Json>, ContractError> {
+    let mut values = vec![];
+    for element in resolve_array_value(deps, env.clone(), ctx, expr.array)? {
+        if let Some(cond) = &expr.condition {
+            if !resolve_element_cond(deps, env.clone(), ctx, element.clone(), cond)? {
+                continue;
             }
-            return Ok(false);
         }
-        Condition::Not(cond) => Ok(!resolve_cond(deps, env, *cond)?),
-        Condition::Expr(expr) => Ok(resolve_expr(deps, env, expr)?),
+
+        values.push(match &expr.selector {
+            None => element,
+            Some(selector) => resolve_path(&element, &ctx.path(selector)?)?
+                .ok_or(ContractError::DecodeError {})?,
+        });
     }
+
+    Ok(values)
 }
 
-pub fn resolve_expr(deps: Deps, env: Env, expr: Expr) -> Result<bool, ContractError> {
+fn resolve_aggregate<T: Ord + FromJson>(
+    op: AggregateOp,
+    selector: Option<String>,
+    values: Vec<Json>,
+    count: fn(usize) -> Result<T, ContractError>,
+    add: fn(T, T) -> Option<T>,
+) -> Result<T, ContractError> {
+    let decoded = || {
+        values
+            .iter()
+            .map(|value| decode_value(value, selector.as_deref().unwrap_or_default(), None))
+            .collect::<Result<Vec<T>, ContractError>>()
+    };
+
+    match op {
+        AggregateOp::Count => count(values.len()),
+        AggregateOp::Sum => decoded()?.into_iter().try_fold(count(0)?, |acc, value| {
+            add(acc, value).ok_or(ContractError::Overflow {})
+        }),
+        AggregateOp::Min => decoded()?
+            .into_iter()
+            .min()
+            .ok_or(ContractError::EmptyArray {}),
+        AggregateOp::Max => decoded()?
+            .into_iter()
+            .max()
+            .ok_or(ContractError::EmptyArray {}),
+    }
+}
+
+pub fn resolve_expr(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: Expr,
+) -> Result<bool, ContractError> {
     match expr {
-        Expr::String(expr) => resolve_string_expr(deps, env, expr),
-        Expr::Uint(expr) => resolve_uint_expr(deps, env, expr),
-        Expr::Int(expr) => resolve_int_expr(deps, env, expr),
-        Expr::Decimal(expr) => resolve_decimal_expr(deps, env, expr),
-        Expr::Timestamp(expr) => resolve_timestamp_expr(deps, env, expr),
-        Expr::BlockHeight(expr) => resolve_block_expr(deps, env, expr),
-        Expr::Bool(expr) => resolve_query_expr_bool(deps, env, expr),
+        Expr::String(expr) => resolve_string_expr(deps, env, ctx, expr),
+        Expr::Uint(expr) => resolve_uint_expr(deps, env, ctx, expr),
+        Expr::Int(expr) => resolve_int_expr(deps, env, ctx, expr),
+        Expr::Decimal(expr) => resolve_decimal_expr(deps, env, ctx, expr),
+        Expr::Timestamp(expr) => resolve_timestamp_expr(deps, env, ctx, expr),
+        Expr::BlockHeight(expr) => resolve_block_expr(deps, env, ctx, expr),
+        Expr::Calendar(expr) => resolve_calendar_expr(env, ctx, expr),
+        Expr::Period(expr) => resolve_period_expr(env, ctx, expr),
+        Expr::JobStatus(expr) => resolve_job_status_expr(deps, ctx, expr),
+        Expr::Bool(expr) => {
+            let value = resolve_query_expr_bool(deps, env, ctx, expr);
+            trace_value(ctx, "bool", &value, |value| Some(value.to_string()));
+            value
+        }
+        Expr::Exists(expr) => {
+            let value = resolve_query_expr_selected(deps, env, ctx, expr);
+            trace_value(ctx, "exists", &value, |value| {
+                value.as_ref().map(encode_json)
+            });
+            Ok(value?.is_some())
+        }
+        Expr::IsNull(expr) => {
+            let value = resolve_query_expr_selected(deps, env, ctx, expr);
+            trace_value(ctx, "is_null", &value, |value| {
+                value.as_ref().map(encode_json)
+            });
+            Ok(matches!(value?, Some(Json::Null)))
+        }
+        Expr::StringList(expr) => {
+            let left = resolve_value_string(deps, env.clone(), ctx, expr.left);
+            let right = resolve_list_value::<String>(deps, env, ctx, expr.right);
+            trace_op(ctx, "string_list", &expr.op, &left, &right, show, show_list);
+            Ok(resolve_list_op(left?, right?, expr.op))
+        }
+        Expr::UintList(expr) => {
+            let left = resolve_num_value_uint(deps, env.clone(), ctx, expr.left);
+            let right = resolve_list_value::<Uint256>(deps, env, ctx, expr.right);
+            trace_op(ctx, "uint_list", &expr.op, &left, &right, show, show_list);
+            Ok(resolve_list_op(left?, right?, expr.op))
+        }
+        Expr::IntList(expr) => {
+            let left = resolve_num_value_int(deps, env.clone(), ctx, expr.left);
+            let right = resolve_list_value::<i128>(deps, env, ctx, expr.right);
+            trace_op(ctx, "int_list", &expr.op, &left, &right, show, show_list);
+            Ok(resolve_list_op(left?, right?, expr.op))
+        }
+        Expr::DecimalList(expr) => {
+            let left = resolve_num_value_decimal(deps, env.clone(), ctx, expr.left);
+            let right = resolve_list_value::<Decimal256>(deps, env, ctx, expr.right);
+            trace_op(
+                ctx,
+                "decimal_list",
+                &expr.op,
+                &left,
+                &right,
+                show,
+                show_list,
+            );
+            Ok(resolve_list_op(left?, right?, expr.op))
+        }
+    }
+}
+
+pub fn resolve_list_value<T: FromJson>(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    value: ListValue<T>,
+) -> Result<Vec<T>, ContractError> {
+    match value {
+        ListValue::Simple(values) => Ok(values),
+        ListValue::Query(expr) => resolve_query_expr_list(deps, env, ctx, expr),
+    }
+}
+
+pub fn resolve_list_op<T: PartialEq>(left: T, right: Vec<T>, op: ListOp) -> bool {
+    match op {
+        ListOp::In => right.contains(&left),
+        ListOp::NotIn => !right.contains(&left),
     }
 }
 
 pub fn resolve_int_expr(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: GenExpr<NumValue<i128, NumExprOp, IntFnOp>, NumOp>,
 ) -> Result<bool, ContractError> {
-    let left = resolve_num_value_int(deps, env.clone(), expr.left)?;
-    let right = resolve_num_value_int(deps, env.clone(), expr.right)?;
+    let left = resolve_num_value_int(deps, env.clone(), ctx, expr.left);
+    let right = resolve_num_value_int(deps, env.clone(), ctx, expr.right);
+    trace_op(ctx, "int", &expr.op, &left, &right, show, show);
 
-    Ok(resolve_int_op(deps, env, left, right, expr.op))
+    Ok(resolve_int_op(deps, env, left?, right?, expr.op))
 }
 
 pub fn resolve_num_value_int(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     value: NumValue<i128, NumExprOp, IntFnOp>,
 ) -> Result<i128, ContractError> {
     match value {
         NumValue::Simple(value) => Ok(value),
-        NumValue::Expr(expr) => resolve_num_expr_int(deps, env, expr),
-        NumValue::Query(expr) => resolve_query_expr_int(deps, env, expr),
-        NumValue::Fn(expr) => resolve_num_fn_int(deps, env, expr),
+        NumValue::Expr(expr) => resolve_num_expr_int(deps, env, ctx, expr),
+        NumValue::Query(expr) => resolve_query_expr_int(deps, env, ctx, expr),
+        NumValue::Fn(expr) => resolve_num_fn_int(deps, env, ctx, expr),
+        NumValue::Clamp(expr) => {
+            let value = resolve_num_value_int(deps, env.clone(), ctx, *expr.value)?;
+            let min = resolve_num_value_int(deps, env.clone(), ctx, *expr.min)?;
+            let max = resolve_num_value_int(deps, env, ctx, *expr.max)?;
+            resolve_clamp(value, min, max)
+        }
+        NumValue::Cast(expr) => {
+            let decimals = expr.decimals.unwrap_or(0);
+            match resolve_cast_value(deps, env, ctx, expr.value)? {
+                CastResult::Uint(value) => uint_to_int(value),
+                CastResult::Int(value) => Ok(value),
+                CastResult::Decimal(value) => uint_to_int(decimal_to_uint(value, decimals)?),
+            }
+        }
+        NumValue::Env(value) => Ok(i128::from(resolve_num_env_value(env, value))),
+        NumValue::StringLen(value) => {
+            let len = resolve_value_string(deps, env, ctx, value)?.chars().count();
+            i128::try_from(len).map_err(|_| ContractError::Overflow {})
+        }
+        NumValue::Element(selector) => resolve_element_value(ctx, selector),
+        NumValue::Aggregate(expr) => resolve_aggregate(
+            expr.op.clone(),
+            expr.selector.clone(),
+            resolve_aggregate_values(deps, env, ctx, expr)?,
+            |count| i128::try_from(count).map_err(|_| ContractError::Overflow {}),
+            i128::checked_add,
+        ),
     }
 }
 
 fn resolve_num_fn_int(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: NumFnValue<i128, NumExprOp, IntFnOp>,
 ) -> Result<i128, ContractError> {
-    let right = resolve_num_value_int(deps, env, *expr.right)?;
+    let right = resolve_num_value_int(deps, env, ctx, *expr.right)?;
 
     match expr.op {
-        IntFnOp::Abs => Ok(right.abs()),
-        IntFnOp::Neg => Ok(right.saturating_mul(i128::from(-1i64))),
+        IntFnOp::Abs => right.checked_abs().ok_or(ContractError::Overflow {}),
+        IntFnOp::Neg => right.checked_neg().ok_or(ContractError::Overflow {}),
+        IntFnOp::Round(places) => resolve_int_round(right, places),
     }
 }
 
 pub fn resolve_num_expr_int(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: NumExprValue<i128, NumExprOp, IntFnOp>,
 ) -> Result<i128, ContractError> {
-    let left = resolve_num_value_int(deps, env.clone(), *expr.left)?;
-    let right = resolve_num_value_int(deps, env.clone(), *expr.right)?;
+    let left = resolve_num_value_int(deps, env.clone(), ctx, *expr.left)?;
+    let right = resolve_num_value_int(deps, env.clone(), ctx, *expr.right)?;
+    let policy = expr.overflow.unwrap_or(OverflowPolicy::Saturate);
 
     match expr.op {
-        NumExprOp::Sub => Ok(left.saturating_sub(right)),
-        NumExprOp::Add => Ok(left.saturating_add(right)),
-        NumExprOp::Div => Ok(left.checked_div(right).unwrap()),
-        NumExprOp::Mul => Ok(left.saturating_mul(right)),
-        NumExprOp::Mod => Ok(left.checked_rem(right).unwrap()),
+        NumExprOp::Sub => resolve_overflow(
+            policy,
+            left.checked_sub(right),
+            left.saturating_sub(right),
+            right > 0,
+        ),
+        NumExprOp::Add => resolve_overflow(
+            policy,
+            left.checked_add(right),
+            left.saturating_add(right),
+            right < 0,
+        ),
+        NumExprOp::Div => {
+            if right == 0 {
+                return Err(ContractError::DivisionByZero {});
+            }
+            // i128::MIN / -1
+            resolve_overflow(policy, left.checked_div(right), i128::MAX, false)
+        }
+        NumExprOp::Mul => resolve_overflow(
+            policy,
+            left.checked_mul(right),
+            left.saturating_mul(right),
+            (left < 0) != (right < 0),
+        ),
+        NumExprOp::Mod => {
+            if right == 0 {
+                return Err(ContractError::DivisionByZero {});
+            }
+            resolve_overflow(policy, left.checked_rem(right), 0, false)
+        }
+        NumExprOp::Min => Ok(left.min(right)),
+        NumExprOp::Max => Ok(left.max(right)),
+        NumExprOp::Pow => {
+            let exp = u32::try_from(right).map_err(|_| ContractError::InvalidExponent {})?;
+            resolve_overflow(
+                policy,
+                left.checked_pow(exp),
+                left.saturating_pow(exp),
+                left < 0 && exp % 2 == 1,
+            )
+        }
+        NumExprOp::PctChange => {
+            if right == 0 {
+                return Err(ContractError::DivisionByZero {});
+            }
+            let diff = resolve_overflow(
+                policy.clone(),
+                left.checked_sub(right),
+                left.saturating_sub(right),
+                right > 0,
+            )?;
+            let negative = (diff < 0) != (right < 0);
+            resolve_overflow(
+                policy,
+                diff.checked_mul(BASIS_POINTS as i128)
+                    .and_then(|diff| diff.checked_div(right)),
+                if negative { i128::MIN } else { i128::MAX },
+                negative,
+            )
+        }
     }
 }
 
 pub fn resolve_uint_expr(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: GenExpr<NumValue<Uint256, NumExprOp, IntFnOp>, NumOp>,
 ) -> Result<bool, ContractError> {
-    let left = resolve_num_value_uint(deps, env.clone(), expr.left)?;
-    let right = resolve_num_value_uint(deps, env.clone(), expr.right)?;
+    let left = resolve_num_value_uint(deps, env.clone(), ctx, expr.left);
+    let right = resolve_num_value_uint(deps, env.clone(), ctx, expr.right);
+    trace_op(ctx, "uint", &expr.op, &left, &right, show, show);
 
-    Ok(resolve_uint_op(deps, env, left, right, expr.op))
+    Ok(resolve_uint_op(deps, env, left?, right?, expr.op))
 }
 
 pub fn resolve_num_value_uint(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     value: NumValue<Uint256, NumExprOp, IntFnOp>,
 ) -> Result<Uint256, ContractError> {
     match value {
         NumValue::Simple(value) => Ok(value),
-        NumValue::Expr(expr) => resolve_num_expr_uint(deps, env, expr),
-        NumValue::Query(expr) => resolve_query_expr_uint(deps, env, expr),
-        NumValue::Fn(expr) => resolve_num_fn_uint(deps, env, expr),
+        NumValue::Expr(expr) => resolve_num_expr_uint(deps, env, ctx, expr),
+        NumValue::Query(expr) => resolve_query_expr_uint(deps, env, ctx, expr),
+        NumValue::Fn(expr) => resolve_num_fn_uint(deps, env, ctx, expr),
+        NumValue::Clamp(expr) => {
+            let value = resolve_num_value_uint(deps, env.clone(), ctx, *expr.value)?;
+            let min = resolve_num_value_uint(deps, env.clone(), ctx, *expr.min)?;
+            let max = resolve_num_value_uint(deps, env, ctx, *expr.max)?;
+            resolve_clamp(value, min, max)
+        }
+        NumValue::Cast(expr) => {
+            let decimals = expr.decimals.unwrap_or(0);
+            match resolve_cast_value(deps, env, ctx, expr.value)? {
+                CastResult::Uint(value) => Ok(value),
+                CastResult::Int(value) => int_to_uint(value),
+                CastResult::Decimal(value) => decimal_to_uint(value, decimals),
+            }
+        }
+        NumValue::Env(value) => Ok(Uint256::from(resolve_num_env_value(env, value))),
+        NumValue::StringLen(value) => {
+            let len = resolve_value_string(deps, env, ctx, value)?.chars().count();
+            Ok(Uint256::from(len as u128))
+        }
+        NumValue::Element(selector) => resolve_element_value(ctx, selector),
+        NumValue::Aggregate(expr) => resolve_aggregate(
+            expr.op.clone(),
+            expr.selector.clone(),
+            resolve_aggregate_values(deps, env, ctx, expr)?,
+            |count| Ok(Uint256::from(count as u128)),
+            |left, right| left.checked_add(right).ok(),
+        ),
     }
 }
 
 fn resolve_num_fn_uint(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: NumFnValue<Uint256, NumExprOp, IntFnOp>,
 ) -> Result<Uint256, ContractError> {
-    let right = resolve_num_value_uint(deps, env, *expr.right)?;
+    let right = resolve_num_value_uint(deps, env, ctx, *expr.right)?;
 
     match expr.op {
         IntFnOp::Abs => Ok(right.abs_diff(Uint256::zero())),
         IntFnOp::Neg => Ok(right.saturating_mul(Uint256::zero().saturating_sub(Uint256::one()))),
+        IntFnOp::Round(places) => resolve_uint_round(right, places),
     }
 }
 
 pub fn resolve_num_expr_uint(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: NumExprValue<Uint256, NumExprOp, IntFnOp>,
 ) -> Result<Uint256, ContractError> {
-    let left = resolve_num_value_uint(deps, env.clone(), *expr.left)?;
-    let right = resolve_num_value_uint(deps, env.clone(), *expr.right)?;
+    let left = resolve_num_value_uint(deps, env.clone(), ctx, *expr.left)?;
+    let right = resolve_num_value_uint(deps, env.clone(), ctx, *expr.right)?;
+    let policy = expr.overflow.unwrap_or(OverflowPolicy::Saturate);
 
     match expr.op {
-        NumExprOp::Sub => Ok(left.saturating_sub(right)),
-        NumExprOp::Add => Ok(left.saturating_add(right)),
-        NumExprOp::Div => Ok(left.checked_div(right).unwrap()),
-        NumExprOp::Mul => Ok(left.saturating_mul(right)),
-        NumExprOp::Mod => Ok(left.checked_rem(right).unwrap()),
+        NumExprOp::Sub => resolve_overflow(
+            policy,
+            left.checked_sub(right).ok(),
+            left.saturating_sub(right),
+            true,
+        ),
+        NumExprOp::Add => resolve_overflow(
+            policy,
+            left.checked_add(right).ok(),
+            left.saturating_add(right),
+            false,
+        ),
+        NumExprOp::Div => Ok(left.checked_div(right)?),
+        NumExprOp::Mul => resolve_overflow(
+            policy,
+            left.checked_mul(right).ok(),
+            left.saturating_mul(right),
+            false,
+        ),
+        NumExprOp::Mod => Ok(left.checked_rem(right)?),
+        NumExprOp::Min => Ok(left.min(right)),
+        NumExprOp::Max => Ok(left.max(right)),
+        NumExprOp::Pow => {
+            let exp = resolve_exponent(right)?;
+            resolve_overflow(
+                policy,
+                left.checked_pow(exp).ok(),
+                left.saturating_pow(exp),
+                false,
+            )
+        }
+        NumExprOp::PctChange => {
+            if right.is_zero() {
+                return Err(ContractError::DivisionByZero {});
+            }
+            // a decrease is negative, which an unsigned value cannot hold
+            if left < right {
+                return Err(ContractError::SignLoss {});
+            }
+            resolve_overflow(
+                policy,
+                (left - right)
+                    .checked_mul(Uint256::from(BASIS_POINTS))
+                    .ok()
+                    .map(|diff| diff / right),
+                Uint256::MAX,
+                false,
+            )
+        }
     }
 }
 
 pub fn resolve_decimal_expr(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: GenExpr<NumValue<Decimal256, NumExprOp, DecimalFnOp>, NumOp>,
 ) -> Result<bool, ContractError> {
-    let left = resolve_num_value_decimal(deps, env.clone(), expr.left)?;
-    let right = resolve_num_value_decimal(deps, env.clone(), expr.right)?;
+    let left = resolve_num_value_decimal(deps, env.clone(), ctx, expr.left);
+    let right = resolve_num_value_decimal(deps, env.clone(), ctx, expr.right);
+    trace_op(ctx, "decimal", &expr.op, &left, &right, show, show);
 
-    Ok(resolve_decimal_op(deps, env, left, right, expr.op))
+    Ok(resolve_decimal_op(deps, env, left?, right?, expr.op))
 }
 
 pub fn resolve_num_value_decimal(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     value: NumValue<Decimal256, NumExprOp, DecimalFnOp>,
 ) -> Result<Decimal256, ContractError> {
     match value {
         NumValue::Simple(value) => Ok(value),
-        NumValue::Expr(expr) => resolve_num_expr_decimal(deps, env, expr),
-        NumValue::Query(expr) => resolve_query_expr_decimal(deps, env, expr),
-        NumValue::Fn(expr) => resolve_num_fn_decimal(deps, env, expr),
+        NumValue::Expr(expr) => resolve_num_expr_decimal(deps, env, ctx, expr),
+        NumValue::Query(expr) => resolve_query_expr_decimal(deps, env, ctx, expr),
+        NumValue::Fn(expr) => resolve_num_fn_decimal(deps, env, ctx, expr),
+        NumValue::Clamp(expr) => {
+            let value = resolve_num_value_decimal(deps, env.clone(), ctx, *expr.value)?;
+            let min = resolve_num_value_decimal(deps, env.clone(), ctx, *expr.min)?;
+            let max = resolve_num_value_decimal(deps, env, ctx, *expr.max)?;
+            resolve_clamp(value, min, max)
+        }
+        NumValue::Cast(expr) => {
+            let decimals = expr.decimals.unwrap_or(0);
+            match resolve_cast_value(deps, env, ctx, expr.value)? {
+                CastResult::Uint(value) => uint_to_decimal(value, decimals),
+                CastResult::Int(value) => uint_to_decimal(int_to_uint(value)?, decimals),
+                CastResult::Decimal(value) => Ok(value),
+            }
+        }
+        NumValue::Env(value) => {
+            uint_to_decimal(Uint256::from(resolve_num_env_value(env, value)), 0)
+        }
+        NumValue::StringLen(value) => {
+            let len = resolve_value_string(deps, env, ctx, value)?.chars().count();
+            uint_to_decimal(Uint256::from(len as u128), 0)
+        }
+        NumValue::Element(selector) => resolve_element_value(ctx, selector),
+        NumValue::Aggregate(expr) => resolve_aggregate(
+            expr.op.clone(),
+            expr.selector.clone(),
+            resolve_aggregate_values(deps, env, ctx, expr)?,
+            |count| uint_to_decimal(Uint256::from(count as u128), 0),
+            |left, right| left.checked_add(right).ok(),
+        ),
     }
 }
 
 fn resolve_num_fn_decimal(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: NumFnValue<Decimal256, NumExprOp, DecimalFnOp>,
 ) -> Result<Decimal256, ContractError> {
-    let right = resolve_num_value_decimal(deps, env, *expr.right)?;
+    let right = resolve_num_value_decimal(deps, env, ctx, *expr.right)?;
 
     match expr.op {
         DecimalFnOp::Abs => Ok(right.abs_diff(Decimal256::zero())),
@@ -193,51 +674,404 @@ fn resolve_num_fn_decimal(
         }
         DecimalFnOp::Floor => Ok(right.floor()),
         DecimalFnOp::Sqrt => Ok(right.sqrt()),
-        DecimalFnOp::Ceil => Ok(right.ceil()),
+        DecimalFnOp::Ceil => right.checked_ceil().map_err(|_| ContractError::Overflow {}),
+        DecimalFnOp::Round(places) => resolve_decimal_round(right, places),
     }
 }
 
 pub fn resolve_num_expr_decimal(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: NumExprValue<Decimal256, NumExprOp, DecimalFnOp>,
 ) -> Result<Decimal256, ContractError> {
-    let left = resolve_num_value_decimal(deps, env.clone(), *expr.left)?;
-    let right = resolve_num_value_decimal(deps, env.clone(), *expr.right)?;
+    let left = resolve_num_value_decimal(deps, env.clone(), ctx, *expr.left)?;
+    let right = resolve_num_value_decimal(deps, env.clone(), ctx, *expr.right)?;
+    let policy = expr.overflow.unwrap_or(OverflowPolicy::Saturate);
 
     match expr.op {
-        NumExprOp::Sub => Ok(left.saturating_sub(right)),
-        NumExprOp::Add => Ok(left.saturating_add(right)),
-        NumExprOp::Div => Ok(left.checked_div(right).unwrap()),
-        NumExprOp::Mul => Ok(left.saturating_mul(right)),
-        NumExprOp::Mod => Ok(left.checked_rem(right).unwrap()),
+        NumExprOp::Sub => resolve_overflow(
+            policy,
+            left.checked_sub(right).ok(),
+            left.saturating_sub(right),
+            true,
+        ),
+        NumExprOp::Add => resolve_overflow(
+            policy,
+            left.checked_add(right).ok(),
+            left.saturating_add(right),
+            false,
+        ),
+        NumExprOp::Div => {
+            if right.is_zero() {
+                return Err(ContractError::DivisionByZero {});
+            }
+            resolve_overflow(policy, left.checked_div(right).ok(), Decimal256::MAX, false)
+        }
+        NumExprOp::Mul => resolve_overflow(
+            policy,
+            left.checked_mul(right).ok(),
+            left.saturating_mul(right),
+            false,
+        ),
+        NumExprOp::Mod => Ok(left.checked_rem(right)?),
+        NumExprOp::Min => Ok(left.min(right)),
+        NumExprOp::Max => Ok(left.max(right)),
+        NumExprOp::Pow => {
+            if right.floor() != right {
+                return Err(ContractError::InvalidExponent {});
+            }
+            let exp = resolve_exponent(right.to_uint_floor())?;
+            resolve_overflow(
+                policy,
+                left.checked_pow(exp).ok(),
+                left.saturating_pow(exp),
+                false,
+            )
+        }
+        NumExprOp::PctChange => {
+            if right.is_zero() {
+                return Err(ContractError::DivisionByZero {});
+            }
+            if left < right {
+                return Err(ContractError::SignLoss {});
+            }
+            resolve_overflow(
+                policy,
+                (left - right).checked_div(right).ok().and_then(|pct| {
+                    pct.checked_mul(Decimal256::from_ratio(BASIS_POINTS, 1u128))
+                        .ok()
+                }),
+                Decimal256::MAX,
+                false,
+            )
+        }
+    }
+}
+
+fn resolve_num_env_value(env: Env, value: NumEnvValue) -> u64 {
+    match value {
+        NumEnvValue::Time => env.block.time.seconds(),
+        NumEnvValue::BlockHeight => env.block.height,
+    }
+}
+
+enum CastResult {
+    Uint(Uint256),
+    Int(i128),
+    Decimal(Decimal256),
+}
+
+fn resolve_cast_value(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    value: CastValue,
+) -> Result<CastResult, ContractError> {
+    Ok(match value {
+        CastValue::Uint(value) => CastResult::Uint(resolve_num_value_uint(deps, env, ctx, *value)?),
+        CastValue::Int(value) => CastResult::Int(resolve_num_value_int(deps, env, ctx, *value)?),
+        CastValue::Decimal(value) => {
+            CastResult::Decimal(resolve_num_value_decimal(deps, env, ctx, *value)?)
+        }
+    })
+}
+
+fn uint_to_int(value: Uint256) -> Result<i128, ContractError> {
+    Uint128::try_from(value)
+        .ok()
+        .and_then(|value| i128::try_from(value.u128()).ok())
+        .ok_or(ContractError::Overflow {})
+}
+
+fn int_to_uint(value: i128) -> Result<Uint256, ContractError> {
+    u128::try_from(value)
+        .map(Uint256::from)
+        .map_err(|_| ContractError::SignLoss {})
+}
+
+fn uint_to_decimal(value: Uint256, decimals: u32) -> Result<Decimal256, ContractError> {
+    Decimal256::from_atomics(value, decimals).map_err(|_| ContractError::Overflow {})
+}
+
+// rounds towards zero
+fn decimal_to_uint(value: Decimal256, decimals: u32) -> Result<Uint256, ContractError> {
+    let ten = Uint256::from(10u64);
+    if decimals <= Decimal256::DECIMAL_PLACES {
+        Ok(value.atomics() / ten.pow(Decimal256::DECIMAL_PLACES - decimals))
+    } else {
+        ten.checked_pow(decimals - Decimal256::DECIMAL_PLACES)
+            .and_then(|factor| value.atomics().checked_mul(factor))
+            .map_err(|_| ContractError::Overflow {})
+    }
+}
+
+fn resolve_exponent(value: Uint256) -> Result<u32, ContractError> {
+    Uint128::try_from(value)
+        .ok()
+        .and_then(|value| u32::try_from(value.u128()).ok())
+        .ok_or(ContractError::InvalidExponent {})
+}
+
+fn resolve_decimal_round(value: Decimal256, places: u32) -> Result<Decimal256, ContractError> {
+    if places >= Decimal256::DECIMAL_PLACES {
+        return Ok(value);
+    }
+
+    let unit = Uint256::from(10u64).pow(Decimal256::DECIMAL_PLACES - places);
+    let half = unit / Uint256::from(2u64);
+    let rounded = value
+        .atomics()
+        .checked_add(half)
+        .map_err(|_| ContractError::Overflow {})?
+        / unit
+        * unit;
+
+    Ok(Decimal256::new(rounded))
+}
+
+// a unit too large for the type rounds every value to zero
+fn resolve_uint_round(value: Uint256, places: u32) -> Result<Uint256, ContractError> {
+    let unit = match Uint256::from(10u64).checked_pow(places) {
+        Ok(unit) => unit,
+        Err(_) => return Ok(Uint256::zero()),
+    };
+
+    let rounded = value
+        .checked_add(unit / Uint256::from(2u64))
+        .map_err(|_| ContractError::Overflow {})?
+        / unit
+        * unit;
+
+    Ok(rounded)
+}
+
+// half away from zero
+fn resolve_int_round(value: i128, places: u32) -> Result<i128, ContractError> {
+    let unit = match 10i128.checked_pow(places) {
+        Some(unit) => unit,
+        None => return Ok(0),
+    };
+
+    let rounded = match value < 0 {
+        true => value.checked_sub(unit / 2),
+        false => value.checked_add(unit / 2),
+    }
+    .ok_or(ContractError::Overflow {})?;
+
+    Ok(rounded / unit * unit)
+}
+
+fn resolve_clamp<T: PartialOrd>(value: T, min: T, max: T) -> Result<T, ContractError> {
+    if min > max {
+        return Err(ContractError::InvalidArguments {});
+    }
+
+    if value < min {
+        Ok(min)
+    } else if value > max {
+        Ok(max)
+    } else {
+        Ok(value)
+    }
+}
+
+// percentage changes are in basis points, so that integer changes under 100% are not lost
+const BASIS_POINTS: u128 = 10000;
+
+// falls back to the saturated value or a typed error when the checked op fails
+fn resolve_overflow<T>(
+    policy: OverflowPolicy,
+    checked: Option<T>,
+    saturated: T,
+    underflow: bool,
+) -> Result<T, ContractError> {
+    match (checked, policy) {
+        (Some(value), _) => Ok(value),
+        (None, OverflowPolicy::Saturate) => Ok(saturated),
+        (None, OverflowPolicy::Error) if underflow => Err(ContractError::Underflow {}),
+        (None, OverflowPolicy::Error) => Err(ContractError::Overflow {}),
     }
 }
 
 pub fn resolve_timestamp_expr(
     _deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: TimeExpr,
 ) -> Result<bool, ContractError> {
-    let res = match expr.op {
-        TimeOp::Lt => env.block.time.seconds().lt(&expr.comparator.u64()),
-        TimeOp::Gt => env.block.time.seconds().gt(&expr.comparator.u64()),
-    };
+    let time = resolve_time_value(env, ctx, expr.anchor);
+    let comparator = Ok(expr.comparator.u64());
+    trace_op(ctx, "timestamp", &expr.op, &time, &comparator, show, show);
 
-    Ok(res)
+    Ok(resolve_u64_op(time?, expr.comparator.u64(), expr.op))
 }
 
-pub fn resolve_block_expr(_deps: Deps, env: Env, expr: BlockExpr) -> Result<bool, ContractError> {
-    let res = match expr.op {
-        NumOp::Eq => env.block.height.eq(&expr.comparator.u64()),
-        NumOp::Neq => env.block.height.ne(&expr.comparator.u64()),
-        NumOp::Lt => env.block.height.lt(&expr.comparator.u64()),
-        NumOp::Gt => env.block.height.gt(&expr.comparator.u64()),
-        NumOp::Gte => env.block.height.ge(&expr.comparator.u64()),
-        NumOp::Lte => env.block.height.le(&expr.comparator.u64()),
-    };
+// block time, or seconds elapsed since the anchor
+pub fn resolve_time_value(
+    env: Env,
+    ctx: &ResolveContext,
+    anchor: Option<JobAnchor>,
+) -> Result<u64, ContractError> {
+    Ok(match anchor {
+        None => env.block.time.seconds(),
+        Some(anchor) => {
+            let job = ctx
+                .job
+                .as_ref()
+                .ok_or(ContractError::AnchorUnavailable {})?;
+            let since = match anchor {
+                JobAnchor::Creation => job.creation_time,
+                JobAnchor::LastUpdate => Some(job.last_update_time),
+            }
+            .ok_or(ContractError::AnchorUnavailable {})?;
+            env.block.time.seconds().saturating_sub(since.u64())
+        }
+    })
+}
+
+pub fn resolve_block_expr(
+    _deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: BlockExpr,
+) -> Result<bool, ContractError> {
+    let height = resolve_block_value(env, ctx, expr.anchor);
+    let comparator = Ok(expr.comparator.u64());
+    trace_op(
+        ctx,
+        "block_height",
+        &expr.op,
+        &height,
+        &comparator,
+        show,
+        show,
+    );
+
+    Ok(resolve_u64_op(height?, expr.comparator.u64(), expr.op))
+}
+
+// block height, or blocks elapsed since the anchor
+pub fn resolve_block_value(
+    env: Env,
+    ctx: &ResolveContext,
+    anchor: Option<JobAnchor>,
+) -> Result<u64, ContractError> {
+    Ok(match anchor {
+        None => env.block.height,
+        Some(anchor) => {
+            let job = ctx
+                .job
+                .as_ref()
+                .ok_or(ContractError::AnchorUnavailable {})?;
+            let since = match anchor {
+                JobAnchor::Creation => job.creation_height,
+                JobAnchor::LastUpdate => job.last_update_height,
+            }
+            .ok_or(ContractError::AnchorUnavailable {})?;
+            env.block.height.saturating_sub(since.u64())
+        }
+    })
+}
 
-    Ok(res)
+pub fn resolve_calendar_expr(
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: CalendarExpr,
+) -> Result<bool, ContractError> {
+    let value = Ok(resolve_calendar_value(&env, &expr.field));
+    let comparator = Ok(expr.comparator.u64());
+    let kind = serde_name(&expr.field);
+    trace_op(ctx, &kind, &expr.op, &value, &comparator, show, show);
+
+    Ok(resolve_u64_op(value?, expr.comparator.u64(), expr.op))
+}
+
+pub fn resolve_calendar_value(env: &Env, field: &CalendarField) -> u64 {
+    let seconds = env.block.time.seconds();
+    let days = seconds / 86400;
+    match field {
+        // 1970-01-01 was a thursday
+        CalendarField::DayOfWeek => (days + 3) % 7 + 1,
+        CalendarField::HourOfDay => seconds % 86400 / 3600,
+        CalendarField::DayOfMonth => day_of_month(days),
+    }
+}
+
+// days since 1970-01-01 to the day of the month, see http://howardhinnant.github.io/date_algorithms.html
+fn day_of_month(days: u64) -> u64 {
+    // shifted to eras of 400 years starting on 0000-03-01
+    let days = days + 719468;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    day_of_year - (153 * month + 2) / 5 + 1
+}
+
+pub fn resolve_period_expr(
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: PeriodExpr,
+) -> Result<bool, ContractError> {
+    let elapsed = resolve_time_value(env, ctx, Some(expr.anchor));
+    trace_value(ctx, "period", &elapsed, |elapsed| Some(elapsed.to_string()));
+
+    Ok(resolve_period(
+        elapsed?,
+        expr.period.u64(),
+        expr.tolerance.u64(),
+    ))
+}
+
+// the first period ends one period after the anchor
+pub fn resolve_period(elapsed: u64, period: u64, tolerance: u64) -> bool {
+    elapsed >= period
+        && elapsed
+            .checked_rem(period)
+            .is_some_and(|into_period| into_period <= tolerance)
+}
+
+pub fn resolve_job_status_expr(
+    deps: Deps,
+    ctx: &mut ResolveContext,
+    expr: JobStatusExpr,
+) -> Result<bool, ContractError> {
+    let status = resolve_job_status(deps, expr.job_id);
+    let expected = Ok(expr.status.clone());
+    trace_op(ctx, "job_status", &expr.op, &status, &expected, show, show);
+
+    Ok(resolve_status_op(status?, expr.status, expr.op))
+}
+
+pub fn resolve_job_status(deps: Deps, job_id: Uint64) -> Result<JobStatus, ContractError> {
+    if let Some(job) = PENDING_JOBS().may_load(deps.storage, job_id.u64())? {
+        return Ok(job.status);
+    }
+
+    FINISHED_JOBS()
+        .may_load(deps.storage, job_id.u64())?
+        .map(|job| job.status)
+        .ok_or(ContractError::DependencyDoesNotExist { job_id })
+}
+
+pub fn resolve_status_op(left: JobStatus, right: JobStatus, op: StatusOp) -> bool {
+    match op {
+        StatusOp::Eq => left.eq(&right),
+        StatusOp::Neq => left.ne(&right),
+    }
+}
+
+pub fn resolve_u64_op(left: u64, right: u64, op: NumOp) -> bool {
+    match op {
+        NumOp::Eq => left.eq(&right),
+        NumOp::Neq => left.ne(&right),
+        NumOp::Lt => left.lt(&right),
+        NumOp::Gt => left.gt(&right),
+        NumOp::Gte => left.ge(&right),
+        NumOp::Lte => left.le(&right),
+    }
 }
 
 pub fn resolve_uint_op(_deps: Deps, _env: Env, left: Uint256, right: Uint256, op: NumOp) -> bool {
@@ -282,33 +1116,27 @@ pub fn resolve_decimal_op(
 pub fn resolve_string_expr(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: GenExpr<Value<String>, StringOp>,
 ) -> Result<bool, ContractError> {
-    match (expr.left, expr.right) {
-        (Value::Simple(left), Value::Simple(right)) => {
-            Ok(resolve_str_op(deps, env, left, right, expr.op))
-        }
-        (Value::Simple(left), Value::Query(right)) => Ok(resolve_str_op(
-            deps,
-            env.clone(),
-            left,
-            resolve_query_expr_string(deps, env, right)?,
-            expr.op,
-        )),
-        (Value::Query(left), Value::Simple(right)) => Ok(resolve_str_op(
-            deps,
-            env.clone(),
-            resolve_query_expr_string(deps, env, left)?,
-            right,
-            expr.op,
-        )),
-        (Value::Query(left), Value::Query(right)) => Ok(resolve_str_op(
-            deps,
-            env.clone(),
-            resolve_query_expr_string(deps, env.clone(), left)?,
-            resolve_query_expr_string(deps, env, right)?,
-            expr.op,
-        )),
+    let left = resolve_value_string(deps, env.clone(), ctx, expr.left);
+    let right = resolve_value_string(deps, env.clone(), ctx, expr.right);
+    trace_op(ctx, "string", &expr.op, &left, &right, show, show);
+
+    Ok(resolve_str_op(deps, env, left?, right?, expr.op))
+}
+
+pub fn resolve_value_string(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    value: Value<String>,
+) -> Result<String, ContractError> {
+    match value {
+        Value::Simple(value) => Ok(value),
+        Value::Query(expr) => resolve_query_expr_string(deps, env, ctx, expr),
+        Value::Env(StringEnvValue::ChainId) => Ok(env.block.chain_id),
+        Value::Element(selector) => resolve_element_value(ctx, selector),
     }
 }
 
@@ -319,11 +1147,53 @@ pub fn resolve_str_op(_deps: Deps, _env: Env, left: String, right: String, op: S
         StringOp::Contains => left.contains(&right),
         StringOp::Eq => left.eq(&right),
         StringOp::Neq => left.ne(&right),
+        StringOp::EqIgnoreCase => left.to_lowercase().eq(&right.to_lowercase()),
+        StringOp::NeqIgnoreCase => left.to_lowercase().ne(&right.to_lowercase()),
     }
 }
 
-pub fn resolve_query_expr(deps: Deps, _env: Env, expr: QueryExpr) -> Result<String, ContractError> {
-    let raw = to_vec(&expr.query).map_err(|serialize_err| {
+pub fn resolve_query_expr(
+    deps: Deps,
+    _env: Env,
+    ctx: &mut ResolveContext,
+    expr: QueryExpr,
+) -> Result<String, ContractError> {
+    // snapshots answer from the job, outside of a job they are issued like any other query
+    if expr.snapshot == Some(true) {
+        let snapshot = ctx
+            .job
+            .as_ref()
+            .and_then(|job| job.snapshots.as_ref())
+            .and_then(|snapshots| {
+                snapshots
+                    .iter()
+                    .find(|snapshot| snapshot.name == expr.name && snapshot.query == expr.query)
+            });
+        if let Some(snapshot) = snapshot {
+            return Ok(snapshot.response.clone());
+        }
+    }
+
+    // named queries are issued once per resolution, unnamed ones every time
+    if !expr.name.is_empty() {
+        if let Some((query, response)) = ctx.queries.get(&expr.name) {
+            return match *query == expr.query {
+                true => Ok(response.clone()),
+                false => Err(ContractError::ConflictingNamedQuery { name: expr.name }),
+            };
+        }
+    }
+
+    let request = match lower_query(expr.query.clone())? {
+        Some(request) => request,
+        None => {
+            return ctx
+                .signed_data
+                .clone()
+                .ok_or(ContractError::SignedDataMissing {})
+        }
+    };
+    let raw = to_vec(&request).map_err(|serialize_err| {
         StdError::generic_err(format!("Serializing QueryRequest: {}", serialize_err))
     })?;
 
@@ -339,79 +1209,203 @@ pub fn resolve_query_expr(deps: Deps, _env: Env, expr: QueryExpr) -> Result<Stri
         SystemResult::Ok(ContractResult::Ok(value)) => Ok(value),
     }?;
 
-    let query_result_str = String::from_vec(base64::decode(query_result_binary.to_string())?)?;
+    let mut query_result_str = String::from_vec(base64::decode(query_result_binary.to_string())?)?;
+
+    // raw reads of a missing key are empty, stored values are JSON
+    if query_result_str.is_empty() && matches!(request, QueryRequest::Wasm(WasmQuery::Raw { .. })) {
+        query_result_str = "null".to_string();
+    }
+
+    if !expr.name.is_empty() {
+        ctx.queries
+            .insert(expr.name, (expr.query, query_result_str.clone()));
+    }
 
     Ok(query_result_str)
 }
 
+// the request a typed shorthand stands for, raw requests are passed through,
+// None for signed data which is attached to the execution instead of queried
+pub fn lower_query(query: QuerySource) -> Result<Option<QueryRequest<String>>, ContractError> {
+    let typed = match query {
+        QuerySource::Request(request) => return Ok(Some(request)),
+        QuerySource::Typed(typed) => typed,
+    };
+
+    Ok(Some(match typed {
+        TypedQuery::NativeBalance { address, denom } => {
+            QueryRequest::Bank(BankQuery::Balance { address, denom })
+        }
+        TypedQuery::Cw20Balance { contract, address } => QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: contract,
+            msg: to_binary(&Cw20QueryMsg::Balance { address })?,
+        }),
+        TypedQuery::Cw20TokenInfo { contract } => QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: contract,
+            msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+        }),
+        TypedQuery::Smart { contract, msg } => {
+            Decoder::default(msg.chars())
+                .decode()
+                .map_err(|_| ContractError::InvalidQueryMsg {})?;
+            QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: contract,
+                msg: Binary::from(msg.as_bytes()),
+            })
+        }
+        TypedQuery::ContractInfo { contract } => QueryRequest::Wasm(WasmQuery::ContractInfo {
+            contract_addr: contract,
+        }),
+        TypedQuery::Raw { contract, key } => QueryRequest::Wasm(WasmQuery::Raw {
+            contract_addr: contract,
+            key: Binary::from(key.to_bytes().ok_or(ContractError::InvalidStorageKey {})?),
+        }),
+        TypedQuery::SignedData {} => return Ok(None),
+    }))
+}
+
+// the selected value as is, None when it is missing
+pub fn resolve_query_expr_selected(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: QueryExpr,
+) -> Result<Option<Json>, ContractError> {
+    let query_result_str = resolve_query_expr(deps, env, ctx, expr.clone())?;
+    // traced before failing, a response that is not JSON is what explain is there to show
+    let selected = Decoder::default(query_result_str.chars())
+        .decode()
+        .map_err(ContractError::from)
+        .and_then(|value| resolve_path(&value, &ctx.path(&expr.selector)?));
+
+    if let Some(frame) = ctx.trace.as_mut() {
+        frame.queries.push(QueryTrace {
+            name: expr.name,
+            query: expr.query,
+            selector: expr.selector,
+            response: query_result_str,
+            value: selected
+                .as_ref()
+                .ok()
+                .cloned()
+                .flatten()
+                .map(|value| encode_json(&value)),
+        });
+    }
+
+    selected
+}
+
+pub fn resolve_query_expr_json(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: QueryExpr,
+) -> Result<Json, ContractError> {
+    let default = expr.default.clone();
+
+    match (resolve_query_expr_selected(deps, env, ctx, expr)?, default) {
+        (Some(Json::Null) | None, Some(default)) => decode_query_default(&default),
+        (Some(value), _) => Ok(value),
+        (None, None) => Err(ContractError::DecodeError {}),
+    }
+}
+
+pub fn decode_query_default(default: &str) -> Result<Json, ContractError> {
+    Decoder::default(default.chars())
+        .decode()
+        .map_err(|_| ContractError::InvalidQueryDefault {})
+}
+
+pub fn resolve_query_expr_value<T: FromJson>(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: QueryExpr,
+) -> Result<T, ContractError> {
+    let selector = expr.selector.clone();
+    let decimals = expr.decimals;
+    decode_value(
+        &resolve_query_expr_json(deps, env, ctx, expr)?,
+        &selector,
+        decimals,
+    )
+}
+
 pub fn resolve_query_expr_bool(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: QueryExpr,
 ) -> Result<bool, ContractError> {
-    let query_result_str = resolve_query_expr(deps, env, expr.clone())?;
-    let value = Decoder::default(query_result_str.chars()).decode()?;
-    let r = Ref::new(&value);
-    let resolved = resolve_path(r, expr.selector)?;
-
-    resolved.bool().ok_or(ContractError::DecodeError {})
+    resolve_query_expr_value(deps, env, ctx, expr)
 }
 
 pub fn resolve_query_expr_uint(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: QueryExpr,
 ) -> Result<Uint256, ContractError> {
-    let query_result_str = resolve_query_expr(deps, env, expr.clone())?;
-    let value = Decoder::default(query_result_str.chars()).decode()?;
-    let r = Ref::new(&value);
-    let resolved = resolve_path(r, expr.selector)?;
-
-    Ok(Uint256::from_str(
-        resolved.string().ok_or(ContractError::DecodeError {})?,
-    )?)
+    resolve_query_expr_value(deps, env, ctx, expr)
 }
 
 pub fn resolve_query_expr_int(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: QueryExpr,
 ) -> Result<i128, ContractError> {
-    let query_result_str = resolve_query_expr(deps, env, expr.clone())?;
-    let value = Decoder::default(query_result_str.chars()).decode()?;
-    let r = Ref::new(&value);
-    let resolved = resolve_path(r, expr.selector)?;
-
-    resolved.i128().ok_or(ContractError::DecodeError {})
+    resolve_query_expr_value(deps, env, ctx, expr)
 }
 
 pub fn resolve_query_expr_decimal(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: QueryExpr,
 ) -> Result<Decimal256, ContractError> {
-    let query_result_str = resolve_query_expr(deps, env, expr.clone())?;
-    let value = Decoder::default(query_result_str.chars()).decode()?;
-    let r = Ref::new(&value);
-    let resolved = resolve_path(r, expr.selector)?;
-
-    Ok(Decimal256::from_str(
-        resolved.string().ok_or(ContractError::Unauthorized {})?,
-    )?)
+    resolve_query_expr_value(deps, env, ctx, expr)
 }
 
 pub fn resolve_query_expr_string(
     deps: Deps,
     env: Env,
+    ctx: &mut ResolveContext,
     expr: QueryExpr,
 ) -> Result<String, ContractError> {
-    let query_result_str = resolve_query_expr(deps, env, expr.clone())?;
-    let value = Decoder::default(query_result_str.chars()).decode()?;
-    let r = Ref::new(&value);
-    let resolved = resolve_path(r, expr.selector)?;
-
-    Ok(resolved
-        .string()
-        .ok_or(ContractError::DecodeError {})?
-        .to_string())
+    resolve_query_expr_value(deps, env, ctx, expr)
+}
+
+// decodes every entry of the JSON array the selector points at
+pub fn resolve_query_expr_list<T: FromJson>(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    expr: QueryExpr,
+) -> Result<Vec<T>, ContractError> {
+    let selector = expr.selector.clone();
+    let decimals = expr.decimals;
+    match resolve_query_expr_json(deps, env, ctx, expr)? {
+        Json::Array(values) => values
+            .iter()
+            .map(|value| decode_value(value, &selector, decimals))
+            .collect(),
+        _ => Err(invalid_selector_value(&selector, "array")),
+    }
+}
+
+fn decode_value<T: FromJson>(
+    value: &Json,
+    selector: &str,
+    decimals: Option<u32>,
+) -> Result<T, ContractError> {
+    T::from_json(value, decimals).ok_or_else(|| invalid_selector_value(selector, T::TYPE))
+}
+
+fn invalid_selector_value(selector: &str, expected: &str) -> ContractError {
+    ContractError::InvalidSelectorValue {
+        selector: selector.to_string(),
+        expected: expected.to_string(),
+    }
 }
@@ -0,0 +1,96 @@
+use crate::state::{FINISHED_JOBS, PENDING_JOBS};
+use crate::ContractError;
+use cosmwasm_std::{Deps, Uint64};
+use std::collections::BTreeSet;
+use warp_protocol::controller::condition::{CastValue, Condition, Expr, NumValue};
+
+// the jobs a condition depends on must exist, and following the conditions of pending ones
+// must not lead back to the job itself
+pub fn validate_dependencies(
+    deps: Deps,
+    job_id: Uint64,
+    cond: &Condition,
+) -> Result<(), ContractError> {
+    let mut pending = vec![];
+    collect_cond(cond, &mut pending);
+
+    let mut visited = BTreeSet::new();
+    while let Some(id) = pending.pop() {
+        if id == job_id {
+            return Err(ContractError::DependencyCycle { job_id });
+        }
+        if !visited.insert(id) {
+            continue;
+        }
+
+        match PENDING_JOBS().may_load(deps.storage, id.u64())? {
+            Some(job) => collect_cond(&job.condition, &mut pending),
+            None if FINISHED_JOBS().has(deps.storage, id.u64()) => {}
+            None => return Err(ContractError::DependencyDoesNotExist { job_id: id }),
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_cond(cond: &Condition, ids: &mut Vec<Uint64>) {
+    match cond {
+        Condition::And(conds) | Condition::Or(conds) => {
+            for cond in conds {
+                collect_cond(cond, ids);
+            }
+        }
+        Condition::Not(cond) => collect_cond(cond, ids),
+        Condition::Expr(expr) => collect_expr(expr, ids),
+        Condition::Any(cond) | Condition::All(cond) => collect_cond(&cond.condition, ids),
+    }
+}
+
+// job status expressions can also sit in the conditions of aggregates
+fn collect_expr(expr: &Expr, ids: &mut Vec<Uint64>) {
+    match expr {
+        Expr::JobStatus(expr) => ids.push(expr.job_id),
+        Expr::Uint(expr) => {
+            collect_num(&expr.left, ids);
+            collect_num(&expr.right, ids);
+        }
+        Expr::Int(expr) => {
+            collect_num(&expr.left, ids);
+            collect_num(&expr.right, ids);
+        }
+        Expr::Decimal(expr) => {
+            collect_num(&expr.left, ids);
+            collect_num(&expr.right, ids);
+        }
+        Expr::UintList(expr) => collect_num(&expr.left, ids),
+        Expr::IntList(expr) => collect_num(&expr.left, ids),
+        Expr::DecimalList(expr) => collect_num(&expr.left, ids),
+        _ => {}
+    }
+}
+
+fn collect_num<T, ExprOp, FnOp>(value: &NumValue<T, ExprOp, FnOp>, ids: &mut Vec<Uint64>) {
+    match value {
+        NumValue::Expr(expr) => {
+            collect_num(&expr.left, ids);
+            collect_num(&expr.right, ids);
+        }
+        NumValue::Fn(expr) => collect_num(&expr.right, ids),
+        NumValue::Clamp(expr) => {
+            collect_num(&expr.value, ids);
+            collect_num(&expr.min, ids);
+            collect_num(&expr.max, ids);
+        }
+        NumValue::Cast(expr) => match &expr.value {
+            CastValue::Uint(value) => collect_num(value, ids),
+            CastValue::Int(value) => collect_num(value, ids),
+            CastValue::Decimal(value) => collect_num(value, ids),
+        },
+        NumValue::Aggregate(expr) => {
+            if let Some(cond) = &expr.condition {
+                collect_cond(cond, ids);
+            }
+        }
+        _ => {}
+    }
+}
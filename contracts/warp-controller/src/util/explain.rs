@@ -0,0 +1,115 @@
+use crate::util::condition::{resolve_cond_node, ResolveContext};
+use crate::ContractError;
+use cosmwasm_schema::serde::Serialize;
+use cosmwasm_std::{Deps, Env};
+use std::fmt::Display;
+use warp_protocol::controller::condition::{
+    Condition, ConditionTrace, ExprTrace, QueryTrace, TraceNode,
+};
+
+// what the resolver records for the node being explained, see `ResolveContext.trace`
+#[derive(Default)]
+pub struct TraceFrame {
+    pub expr: Option<ExprTrace>,
+    pub queries: Vec<QueryTrace>,
+    pub children: Vec<ConditionTrace>,
+}
+
+// resolves a condition like `resolve_cond` while keeping every intermediate value,
+// all children are evaluated so the whole tree is reported, results still short-circuit
+pub fn explain_cond(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    cond: Condition,
+) -> ConditionTrace {
+    let outer = ctx.trace.replace(TraceFrame::default());
+    let (trace, _) = trace_cond(deps, env, ctx, cond);
+    ctx.trace = outer;
+    trace
+}
+
+// resolves a node of a condition being explained into its own frame
+pub fn trace_cond(
+    deps: Deps,
+    env: Env,
+    ctx: &mut ResolveContext,
+    cond: Condition,
+) -> (ConditionTrace, Result<bool, ContractError>) {
+    let node = match &cond {
+        Condition::And(_) => TraceNode::And,
+        Condition::Or(_) => TraceNode::Or,
+        Condition::Not(_) => TraceNode::Not,
+        Condition::Expr(_) => TraceNode::Expr,
+        Condition::Any(_) => TraceNode::Any,
+        Condition::All(_) => TraceNode::All,
+    };
+
+    // values recorded from here on belong to this node, children swap in their own frame
+    let outer = ctx.trace.replace(TraceFrame::default());
+    let result = resolve_cond_node(deps, env, ctx, cond);
+    let frame = std::mem::replace(&mut ctx.trace, outer).unwrap_or_default();
+
+    let trace = ConditionTrace {
+        node,
+        result: result.as_ref().ok().copied(),
+        error: result.as_ref().err().map(|err| err.to_string()),
+        expr: frame.expr,
+        queries: frame.queries,
+        children: frame.children,
+    };
+    (trace, result)
+}
+
+// records the operands of the expression being resolved, when explaining
+pub fn trace_op<L, R>(
+    ctx: &mut ResolveContext,
+    kind: &str,
+    op: &impl Serialize,
+    left: &Result<L, ContractError>,
+    right: &Result<R, ContractError>,
+    show_left: fn(&L) -> String,
+    show_right: fn(&R) -> String,
+) {
+    if let Some(frame) = ctx.trace.as_mut() {
+        frame.expr = Some(ExprTrace {
+            kind: kind.to_string(),
+            op: Some(serde_name(op)),
+            left: left.as_ref().ok().map(show_left),
+            right: right.as_ref().ok().map(show_right),
+        });
+    }
+}
+
+// records the value an expression checking a single value resolved to, when explaining
+pub fn trace_value<T>(
+    ctx: &mut ResolveContext,
+    kind: &str,
+    value: &Result<T, ContractError>,
+    show: fn(&T) -> Option<String>,
+) {
+    if let Some(frame) = ctx.trace.as_mut() {
+        frame.expr = Some(ExprTrace {
+            kind: kind.to_string(),
+            op: None,
+            left: value.as_ref().ok().and_then(show),
+            right: None,
+        });
+    }
+}
+
+pub fn serde_name(value: &impl Serialize) -> String {
+    serde_json_wasm::to_string(value)
+        .unwrap_or_default()
+        .trim_matches('"')
+        .to_string()
+}
+
+pub fn show<T: Display>(value: &T) -> String {
+    value.to_string()
+}
+
+pub fn show_list<T: Display, V: AsRef<[T]>>(values: &V) -> String {
+    let values: Vec<String> = values.as_ref().iter().map(show).collect();
+    format!("[{}]", values.join(", "))
+}
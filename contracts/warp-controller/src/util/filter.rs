@@ -1,4 +1,4 @@
-use crate::util::condition::resolve_cond;
+use crate::util::condition::{resolve_cond, ResolveContext};
 use cosmwasm_std::{Addr, Deps, Env};
 use warp_protocol::controller::job::{Job, JobStatus};
 
@@ -25,10 +25,16 @@ pub fn resolve_filters(
     }
     if condition_status.is_some()
         && condition_status.unwrap()
-            != resolve_cond(deps, env, job.condition).unwrap_or(!condition_status.unwrap())
+            != resolve_cond(
+                deps,
+                env,
+                &mut ResolveContext::with_job(job.clone()),
+                job.condition,
+            )
+            .unwrap_or(!condition_status.unwrap())
     {
         return false;
     }
 
-    return true;
+    true
 }
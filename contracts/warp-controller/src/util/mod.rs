@@ -1,3 +1,11 @@
+pub(crate) mod coerce;
 pub(crate) mod condition;
+pub(crate) mod dependency;
+pub(crate) mod explain;
 pub(crate) mod filter;
+pub(crate) mod normalize;
 pub(crate) mod path;
+pub(crate) mod signed;
+pub(crate) mod snapshot;
+pub(crate) mod template;
+pub(crate) mod validation;
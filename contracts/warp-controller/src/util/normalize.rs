@@ -0,0 +1,220 @@
+use crate::util::condition::{
+    resolve_num_value_decimal, resolve_num_value_int, resolve_num_value_uint, ResolveContext,
+};
+use crate::ContractError;
+use cosmwasm_std::{Decimal256, Deps, Env, Uint256};
+use warp_protocol::controller::condition::{
+    ArrayAggregate, ArrayCondition, CastValue, Condition, DecimalFnOp, Expr, GenExpr, IntFnOp,
+    ListExpr, NumCastValue, NumClampValue, NumExprOp, NumExprValue, NumFnValue, NumValue, Value,
+};
+
+type NormalizeNum<T, FnOp> =
+    fn(Deps, &Env, NumValue<T, NumExprOp, FnOp>) -> NumValue<T, NumExprOp, FnOp>;
+type ResolveNum<T, FnOp> =
+    fn(Deps, Env, &mut ResolveContext, NumValue<T, NumExprOp, FnOp>) -> Result<T, ContractError>;
+
+// simplifies a validated condition without changing its result:
+// - `Not(Not(c))` becomes `c`
+// - nested and/or of the same kind are flattened, repeated children dropped and
+//   single children replace their parent
+// - numeric values built only from literals are folded, unless they fail to resolve
+// children keep their order, a query failing before the deciding child still fails the condition
+pub fn normalize_condition(deps: Deps, env: &Env, cond: Condition) -> Condition {
+    match cond {
+        Condition::And(conds) => normalize_conds(deps, env, conds, true),
+        Condition::Or(conds) => normalize_conds(deps, env, conds, false),
+        Condition::Not(cond) => match normalize_condition(deps, env, *cond) {
+            Condition::Not(cond) => *cond,
+            cond => Condition::Not(Box::new(cond)),
+        },
+        Condition::Expr(expr) => Condition::Expr(normalize_expr(deps, env, expr)),
+        Condition::Any(cond) => Condition::Any(normalize_array_cond(deps, env, cond)),
+        Condition::All(cond) => Condition::All(normalize_array_cond(deps, env, cond)),
+    }
+}
+
+fn normalize_conds(
+    deps: Deps,
+    env: &Env,
+    conds: impl IntoIterator<Item = Box<Condition>>,
+    and: bool,
+) -> Condition {
+    let mut children: Vec<Box<Condition>> = vec![];
+    for cond in conds {
+        let nested = match (normalize_condition(deps, env, *cond), and) {
+            (Condition::And(nested), true) | (Condition::Or(nested), false) => nested,
+            (cond, _) => vec![Box::new(cond)],
+        };
+        for child in nested {
+            if !children.contains(&child) {
+                children.push(child);
+            }
+        }
+    }
+
+    if children.len() == 1 {
+        return *children.remove(0);
+    }
+    match and {
+        true => Condition::And(children),
+        false => Condition::Or(children),
+    }
+}
+
+fn normalize_array_cond(deps: Deps, env: &Env, cond: ArrayCondition) -> ArrayCondition {
+    ArrayCondition {
+        array: cond.array,
+        condition: Box::new(normalize_condition(deps, env, *cond.condition)),
+    }
+}
+
+fn normalize_expr(deps: Deps, env: &Env, expr: Expr) -> Expr {
+    match expr {
+        Expr::Uint(expr) => Expr::Uint(GenExpr {
+            left: normalize_num_uint(deps, env, expr.left),
+            op: expr.op,
+            right: normalize_num_uint(deps, env, expr.right),
+        }),
+        Expr::Int(expr) => Expr::Int(GenExpr {
+            left: normalize_num_int(deps, env, expr.left),
+            op: expr.op,
+            right: normalize_num_int(deps, env, expr.right),
+        }),
+        Expr::Decimal(expr) => Expr::Decimal(GenExpr {
+            left: normalize_num_decimal(deps, env, expr.left),
+            op: expr.op,
+            right: normalize_num_decimal(deps, env, expr.right),
+        }),
+        Expr::UintList(expr) => Expr::UintList(ListExpr {
+            left: normalize_num_uint(deps, env, expr.left),
+            op: expr.op,
+            right: expr.right,
+        }),
+        Expr::IntList(expr) => Expr::IntList(ListExpr {
+            left: normalize_num_int(deps, env, expr.left),
+            op: expr.op,
+            right: expr.right,
+        }),
+        Expr::DecimalList(expr) => Expr::DecimalList(ListExpr {
+            left: normalize_num_decimal(deps, env, expr.left),
+            op: expr.op,
+            right: expr.right,
+        }),
+        expr => expr,
+    }
+}
+
+fn normalize_num_uint(
+    deps: Deps,
+    env: &Env,
+    value: NumValue<Uint256, NumExprOp, IntFnOp>,
+) -> NumValue<Uint256, NumExprOp, IntFnOp> {
+    normalize_num(deps, env, value, normalize_num_uint, resolve_num_value_uint)
+}
+
+fn normalize_num_int(
+    deps: Deps,
+    env: &Env,
+    value: NumValue<i128, NumExprOp, IntFnOp>,
+) -> NumValue<i128, NumExprOp, IntFnOp> {
+    normalize_num(deps, env, value, normalize_num_int, resolve_num_value_int)
+}
+
+fn normalize_num_decimal(
+    deps: Deps,
+    env: &Env,
+    value: NumValue<Decimal256, NumExprOp, DecimalFnOp>,
+) -> NumValue<Decimal256, NumExprOp, DecimalFnOp> {
+    normalize_num(
+        deps,
+        env,
+        value,
+        normalize_num_decimal,
+        resolve_num_value_decimal,
+    )
+}
+
+// children are normalized first, a value whose operands all ended up literals is then folded
+fn normalize_num<T: Clone, FnOp: Clone>(
+    deps: Deps,
+    env: &Env,
+    value: NumValue<T, NumExprOp, FnOp>,
+    normalize: NormalizeNum<T, FnOp>,
+    resolve: ResolveNum<T, FnOp>,
+) -> NumValue<T, NumExprOp, FnOp> {
+    let value = match value {
+        NumValue::Expr(expr) => NumValue::Expr(NumExprValue {
+            left: Box::new(normalize(deps, env, *expr.left)),
+            op: expr.op,
+            right: Box::new(normalize(deps, env, *expr.right)),
+            overflow: expr.overflow,
+        }),
+        NumValue::Fn(expr) => NumValue::Fn(NumFnValue {
+            op: expr.op,
+            right: Box::new(normalize(deps, env, *expr.right)),
+        }),
+        NumValue::Clamp(expr) => NumValue::Clamp(NumClampValue {
+            value: Box::new(normalize(deps, env, *expr.value)),
+            min: Box::new(normalize(deps, env, *expr.min)),
+            max: Box::new(normalize(deps, env, *expr.max)),
+        }),
+        NumValue::Cast(expr) => NumValue::Cast(NumCastValue {
+            value: match expr.value {
+                CastValue::Uint(value) => {
+                    CastValue::Uint(Box::new(normalize_num_uint(deps, env, *value)))
+                }
+                CastValue::Int(value) => {
+                    CastValue::Int(Box::new(normalize_num_int(deps, env, *value)))
+                }
+                CastValue::Decimal(value) => {
+                    CastValue::Decimal(Box::new(normalize_num_decimal(deps, env, *value)))
+                }
+            },
+            decimals: expr.decimals,
+        }),
+        NumValue::Aggregate(expr) => NumValue::Aggregate(ArrayAggregate {
+            array: expr.array,
+            op: expr.op,
+            selector: expr.selector,
+            condition: expr
+                .condition
+                .map(|cond| Box::new(normalize_condition(deps, env, *cond))),
+        }),
+        value => value,
+    };
+
+    if !is_constant(&value) {
+        return value;
+    }
+    match resolve(
+        deps,
+        env.clone(),
+        &mut ResolveContext::default(),
+        value.clone(),
+    ) {
+        Ok(folded) => NumValue::Simple(folded),
+        Err(_) => value,
+    }
+}
+
+// operands are literals, plain literals are already as simple as it gets
+fn is_constant<T, ExprOp, FnOp>(value: &NumValue<T, ExprOp, FnOp>) -> bool {
+    match value {
+        NumValue::Expr(expr) => is_literal(&expr.left) && is_literal(&expr.right),
+        NumValue::Fn(expr) => is_literal(&expr.right),
+        NumValue::Clamp(expr) => {
+            is_literal(&expr.value) && is_literal(&expr.min) && is_literal(&expr.max)
+        }
+        NumValue::Cast(expr) => match &expr.value {
+            CastValue::Uint(value) => is_literal(value),
+            CastValue::Int(value) => is_literal(value),
+            CastValue::Decimal(value) => is_literal(value),
+        },
+        NumValue::StringLen(value) => matches!(value, Value::Simple(_)),
+        _ => false,
+    }
+}
+
+fn is_literal<T, ExprOp, FnOp>(value: &NumValue<T, ExprOp, FnOp>) -> bool {
+    matches!(value, NumValue::Simple(_))
+}
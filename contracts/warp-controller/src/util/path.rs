@@ -1,76 +1,34 @@
 use crate::ContractError;
-use cosmwasm_std::StdError;
-use json_codec_wasm::ast::Ref;
-
-// supports custom jsonpath-like expression with options for indexing
-// - fields - $.field
-// - array entries - $.field[0]
-// - multiple nested fields / entries - $.field1.field2[0].field3
-pub fn resolve_path(r: Ref, path: String) -> Result<Ref, ContractError> {
-    let mut obj = r;
-    let mut curr = String::new();
-    let mut idx = 0;
-
-    while idx < path.len() {
-        let co = path.chars().nth(idx);
-
-        if co.is_none() {
-            return Err(ContractError::ResolveError {});
-        } else {
-            let c = co.unwrap();
-
-            match c {
-                '$' => {
-                    idx += 1;
-                }
-                '[' => {
-                    // field[0] case
-                    if !curr.is_empty() {
-                        obj = obj.get(curr);
-                        curr = String::new();
-                    }
-
-                    let (_, array_idx) = read_array_index(path, idx + 1)?;
-                    obj = obj.at(array_idx);
-                    break;
-                }
-                '.' => {
-                    // $ case
-                    if curr.is_empty() {
-                        idx += 1;
-                        continue;
-                    }
-
-                    obj = obj.get(curr);
-                    curr = String::new();
-                    idx += 1;
-                }
-                c => {
-                    curr.push(c);
-                    idx += 1;
+use json_codec_wasm::ast::{Json, Ref};
+use warp_protocol::controller::path::PathSegment;
+
+// resolves a parsed selector (see `warp_protocol::controller::path`) against a JSON value,
+// returns None when the selected value does not exist
+pub fn resolve_path(value: &Json, path: &[PathSegment]) -> Result<Option<Json>, ContractError> {
+    let mut obj = Ref::new(value);
+
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => obj = obj.get(key.as_str()),
+            PathSegment::Index(index) => {
+                let len = obj.slice().map(|entries| entries.len()).unwrap_or(0) as i64;
+                let index = if *index < 0 { len + index } else { *index };
+                if index < 0 {
+                    return Ok(None);
                 }
+                obj = obj.at(index as usize);
+            }
+            PathSegment::Length => {
+                let len = match obj.value() {
+                    Some(Json::Array(entries)) => entries.len(),
+                    Some(Json::String(s)) => s.chars().count(),
+                    Some(Json::Object(entries)) => entries.len(),
+                    _ => return Ok(None),
+                };
+                return Ok(Some(Json::U128(len as u128)));
             }
         }
     }
 
-    if !curr.is_empty() {
-        obj = obj.get(curr);
-    }
-
-    Ok(obj)
-}
-
-fn read_array_index(path: String, from: usize) -> Result<(usize, usize), ContractError> {
-    let mut idx = from;
-    let mut curr = String::new();
-    while path.chars().nth(idx).unwrap() != ']' {
-        curr.push(
-            path.chars()
-                .nth(idx)
-                .ok_or(StdError::generic_err("Array indexing error"))?,
-        );
-        idx += 1;
-    }
-
-    return Ok((idx + 1, str::parse::<usize>(curr.as_str())?));
+    Ok(obj.value().cloned())
 }
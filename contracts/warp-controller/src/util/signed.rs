@@ -0,0 +1,95 @@
+use crate::util::coerce::{encode_json, FromJson};
+use crate::util::path::resolve_path;
+use crate::util::snapshot::collect_queries;
+use crate::ContractError;
+use cosmwasm_std::{Api, Env, Uint256};
+use json_codec_wasm::Decoder;
+use sha2::{Digest, Sha256};
+use warp_protocol::controller::condition::{Condition, QuerySource, TypedQuery};
+use warp_protocol::controller::job::{DataSigner, Job, SignatureAlgorithm, SignedData};
+use warp_protocol::controller::path::PathSegment;
+
+// a condition reading signed data needs a signer, whose key must suit its algorithm
+pub fn validate_signer(cond: &Condition, signer: Option<&DataSigner>) -> Result<(), ContractError> {
+    let signer = match signer {
+        Some(signer) => signer,
+        None if reads_signed_data(cond) => return Err(ContractError::SignerRequired {}),
+        None => return Ok(()),
+    };
+
+    let valid = match signer.algorithm {
+        SignatureAlgorithm::Secp256k1 => matches!(signer.public_key.len(), 33 | 65),
+        SignatureAlgorithm::Ed25519 => signer.public_key.len() == 32,
+    };
+    match valid {
+        true => Ok(()),
+        false => Err(ContractError::InvalidPublicKey {}),
+    }
+}
+
+fn reads_signed_data(cond: &Condition) -> bool {
+    collect_queries(cond)
+        .iter()
+        .any(|expr| expr.query == QuerySource::Typed(TypedQuery::SignedData {}))
+}
+
+// checks the payload a keeper attached to the execution of the job, returns its data as JSON text
+pub fn verify_signed_data(
+    api: &dyn Api,
+    env: &Env,
+    job: &Job,
+    signed_data: Option<SignedData>,
+) -> Result<Option<String>, ContractError> {
+    let (signer, signed_data) = match (&job.signer, signed_data) {
+        (None, None) => return Ok(None),
+        (None, Some(_)) => return Err(ContractError::UnexpectedSignedData {}),
+        (Some(_), None) => return Err(ContractError::SignedDataMissing {}),
+        (Some(signer), Some(signed_data)) => (signer, signed_data),
+    };
+
+    let payload = signed_data.payload.as_bytes();
+    let verified = match signer.algorithm {
+        SignatureAlgorithm::Secp256k1 => api.secp256k1_verify(
+            &Sha256::digest(payload),
+            &signed_data.signature,
+            &signer.public_key,
+        ),
+        SignatureAlgorithm::Ed25519 => {
+            api.ed25519_verify(payload, &signed_data.signature, &signer.public_key)
+        }
+    };
+    if !verified.unwrap_or(false) {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let payload = Decoder::default(signed_data.payload.chars())
+        .decode()
+        .map_err(|_| ContractError::InvalidSignedData {})?;
+    let field = |key: &str| match resolve_path(&payload, &[PathSegment::Key(key.to_string())]) {
+        Ok(Some(value)) => Ok(value),
+        _ => Err(ContractError::InvalidSignedData {}),
+    };
+    let uint =
+        |key| Uint256::from_json(&field(key)?, None).ok_or(ContractError::InvalidSignedData {});
+
+    // the job id keeps a payload signed for one job from being replayed on another
+    if uint("job_id")? != Uint256::from(job.id.u64()) {
+        return Err(ContractError::InvalidSignedData {});
+    }
+
+    let timestamp = uint("timestamp")?;
+    let now = Uint256::from(env.block.time.seconds());
+    if timestamp > now || now - timestamp > Uint256::from(signer.max_age.u64()) {
+        return Err(ContractError::StaleSignedData {});
+    }
+
+    // a payload signed before the last execution of a recurring job was already used, or could have been
+    if job
+        .last_execution_time
+        .is_some_and(|last| timestamp <= Uint256::from(last.u64()))
+    {
+        return Err(ContractError::StaleSignedData {});
+    }
+
+    Ok(Some(encode_json(&field("data")?)))
+}
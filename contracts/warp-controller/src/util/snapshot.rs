@@ -0,0 +1,158 @@
+use crate::util::condition::{resolve_query_expr, ResolveContext};
+use crate::ContractError;
+use cosmwasm_std::{Deps, Env};
+use warp_protocol::controller::condition::{
+    ArrayAggregate, ArrayValue, CastValue, Condition, Expr, ListValue, NumValue, QueryExpr, Value,
+};
+use warp_protocol::controller::job::QuerySnapshot;
+
+// issues the snapshot queries of a validated condition, once per name
+pub fn capture_snapshots(
+    deps: Deps,
+    env: &Env,
+    cond: &Condition,
+) -> Result<Option<Vec<QuerySnapshot>>, ContractError> {
+    let mut ctx = ResolveContext::default();
+    let mut snapshots: Vec<QuerySnapshot> = vec![];
+    for expr in collect_queries(cond) {
+        if expr.snapshot != Some(true) {
+            continue;
+        }
+        if snapshots.iter().any(|snapshot| snapshot.name == expr.name) {
+            continue;
+        }
+        let response = resolve_query_expr(deps, env.clone(), &mut ctx, expr.clone())?;
+        snapshots.push(QuerySnapshot {
+            name: expr.name.clone(),
+            query: expr.query.clone(),
+            response,
+        });
+    }
+
+    match snapshots.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(snapshots)),
+    }
+}
+
+// every query of the condition, in order
+pub fn collect_queries(cond: &Condition) -> Vec<&QueryExpr> {
+    let mut exprs = vec![];
+    collect_cond(cond, &mut exprs);
+    exprs
+}
+
+fn collect_cond<'a>(cond: &'a Condition, exprs: &mut Vec<&'a QueryExpr>) {
+    match cond {
+        Condition::And(conds) | Condition::Or(conds) => {
+            for cond in conds {
+                collect_cond(cond, exprs);
+            }
+        }
+        Condition::Not(cond) => collect_cond(cond, exprs),
+        Condition::Expr(expr) => collect_expr(expr, exprs),
+        Condition::Any(cond) | Condition::All(cond) => {
+            collect_array(&cond.array, exprs);
+            collect_cond(&cond.condition, exprs);
+        }
+    }
+}
+
+fn collect_expr<'a>(expr: &'a Expr, exprs: &mut Vec<&'a QueryExpr>) {
+    match expr {
+        Expr::String(expr) => {
+            collect_value(&expr.left, exprs);
+            collect_value(&expr.right, exprs);
+        }
+        Expr::Uint(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_num(&expr.right, exprs);
+        }
+        Expr::Int(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_num(&expr.right, exprs);
+        }
+        Expr::Decimal(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_num(&expr.right, exprs);
+        }
+        Expr::Timestamp(_)
+        | Expr::BlockHeight(_)
+        | Expr::Calendar(_)
+        | Expr::Period(_)
+        | Expr::JobStatus(_) => {}
+        Expr::Bool(expr) | Expr::Exists(expr) | Expr::IsNull(expr) => collect_query(expr, exprs),
+        Expr::StringList(expr) => {
+            collect_value(&expr.left, exprs);
+            collect_list(&expr.right, exprs);
+        }
+        Expr::UintList(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_list(&expr.right, exprs);
+        }
+        Expr::IntList(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_list(&expr.right, exprs);
+        }
+        Expr::DecimalList(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_list(&expr.right, exprs);
+        }
+    }
+}
+
+fn collect_value<'a, T>(value: &'a Value<T>, exprs: &mut Vec<&'a QueryExpr>) {
+    if let Value::Query(expr) = value {
+        collect_query(expr, exprs);
+    }
+}
+
+fn collect_list<'a, T>(value: &'a ListValue<T>, exprs: &mut Vec<&'a QueryExpr>) {
+    if let ListValue::Query(expr) = value {
+        collect_query(expr, exprs);
+    }
+}
+
+fn collect_num<'a, T, ExprOp, FnOp>(
+    value: &'a NumValue<T, ExprOp, FnOp>,
+    exprs: &mut Vec<&'a QueryExpr>,
+) {
+    match value {
+        NumValue::Simple(_) | NumValue::Env(_) | NumValue::Element(_) => {}
+        NumValue::Expr(expr) => {
+            collect_num(&expr.left, exprs);
+            collect_num(&expr.right, exprs);
+        }
+        NumValue::Query(expr) => collect_query(expr, exprs),
+        NumValue::Fn(expr) => collect_num(&expr.right, exprs),
+        NumValue::Clamp(expr) => {
+            collect_num(&expr.value, exprs);
+            collect_num(&expr.min, exprs);
+            collect_num(&expr.max, exprs);
+        }
+        NumValue::Cast(expr) => match &expr.value {
+            CastValue::Uint(value) => collect_num(value, exprs),
+            CastValue::Int(value) => collect_num(value, exprs),
+            CastValue::Decimal(value) => collect_num(value, exprs),
+        },
+        NumValue::StringLen(value) => collect_value(value, exprs),
+        NumValue::Aggregate(expr) => collect_aggregate(expr, exprs),
+    }
+}
+
+fn collect_aggregate<'a>(expr: &'a ArrayAggregate, exprs: &mut Vec<&'a QueryExpr>) {
+    collect_array(&expr.array, exprs);
+    if let Some(cond) = &expr.condition {
+        collect_cond(cond, exprs);
+    }
+}
+
+fn collect_array<'a>(value: &'a ArrayValue, exprs: &mut Vec<&'a QueryExpr>) {
+    if let ArrayValue::Query(expr) = value {
+        collect_query(expr, exprs);
+    }
+}
+
+fn collect_query<'a>(expr: &'a QueryExpr, exprs: &mut Vec<&'a QueryExpr>) {
+    exprs.push(expr);
+}
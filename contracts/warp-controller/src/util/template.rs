@@ -0,0 +1,153 @@
+use crate::util::validation::validate_condition;
+use crate::ContractError;
+use cosmwasm_std::{Api, CosmosMsg, Decimal256, Env, Uint256};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use warp_protocol::controller::condition::Condition;
+use warp_protocol::controller::controller::Config;
+use warp_protocol::controller::template::{Template, TemplateParam, TemplateVar, TemplateVarKind};
+
+// checks the vars and that the template fills in to a valid job, with every var set to a sample value
+pub fn validate_template(
+    env: &Env,
+    config: &Config,
+    condition: &str,
+    msgs: &[String],
+    vars: &[TemplateVar],
+) -> Result<(), ContractError> {
+    let mut values = BTreeMap::new();
+    for var in vars {
+        let sample = match var.kind {
+            TemplateVarKind::String => "x".to_string(),
+            TemplateVarKind::Uint | TemplateVarKind::Int | TemplateVarKind::Decimal => {
+                "0".to_string()
+            }
+            TemplateVarKind::Bool => "false".to_string(),
+            TemplateVarKind::Address => env.contract.address.to_string(),
+        };
+        if values
+            .insert(var.name.as_str(), (var.kind.clone(), sample))
+            .is_some()
+        {
+            return Err(ContractError::DuplicateTemplateVar {
+                name: var.name.clone(),
+            });
+        }
+    }
+
+    let condition = serde_json_wasm::from_str::<Condition>(&fill(condition, &values)?)?;
+    validate_condition(config, &condition)?;
+    for msg in msgs {
+        serde_json_wasm::from_str::<CosmosMsg>(&fill(msg, &values)?)?;
+    }
+
+    Ok(())
+}
+
+// the template's condition and msgs with every placeholder replaced by its param
+pub fn fill_template(
+    api: &dyn Api,
+    template: &Template,
+    params: &[TemplateParam],
+) -> Result<(String, Vec<String>), ContractError> {
+    let mut given = BTreeMap::new();
+    for param in params {
+        if given
+            .insert(param.name.as_str(), param.value.as_str())
+            .is_some()
+        {
+            return Err(ContractError::DuplicateTemplateParam {
+                name: param.name.clone(),
+            });
+        }
+    }
+
+    let mut values = BTreeMap::new();
+    for var in &template.vars {
+        let value =
+            given
+                .remove(var.name.as_str())
+                .ok_or_else(|| ContractError::MissingTemplateParam {
+                    name: var.name.clone(),
+                })?;
+        validate_param(api, var, value)?;
+        values.insert(var.name.as_str(), (var.kind.clone(), value.to_string()));
+    }
+    if let Some(name) = given.keys().next() {
+        return Err(ContractError::UnknownTemplateParam {
+            name: name.to_string(),
+        });
+    }
+
+    let condition = fill(&template.condition, &values)?;
+    let msgs = template
+        .msgs
+        .iter()
+        .map(|msg| fill(msg, &values))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((condition, msgs))
+}
+
+fn validate_param(api: &dyn Api, var: &TemplateVar, value: &str) -> Result<(), ContractError> {
+    let valid = match var.kind {
+        TemplateVarKind::String => true,
+        TemplateVarKind::Uint => Uint256::from_str(value).is_ok(),
+        TemplateVarKind::Int => i128::from_str(value).is_ok(),
+        TemplateVarKind::Decimal => Decimal256::from_str(value).is_ok(),
+        TemplateVarKind::Bool => value == "true" || value == "false",
+        TemplateVarKind::Address => api.addr_validate(value).is_ok(),
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err(ContractError::InvalidTemplateParam {
+            name: var.name.clone(),
+            kind: serde_json_wasm::to_string(&var.kind)?
+                .trim_matches('"')
+                .to_string(),
+        }),
+    }
+}
+
+// replaces `{{name}}` placeholders in JSON text, values are escaped as JSON string contents
+// except for bool placeholders making up a whole string, which become bare values
+fn fill(
+    text: &str,
+    values: &BTreeMap<&str, (TemplateVarKind, String)>,
+) -> Result<String, ContractError> {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = &rest[start + 2..end];
+        let (kind, value) =
+            values
+                .get(name)
+                .ok_or_else(|| ContractError::UndefinedTemplateVar {
+                    name: name.to_string(),
+                })?;
+
+        let (before, after) = (&rest[..start], &rest[end + 2..]);
+        match kind {
+            TemplateVarKind::Bool if before.ends_with('"') && after.starts_with('"') => {
+                out.push_str(&before[..before.len() - 1]);
+                out.push_str(value);
+                rest = &after[1..];
+            }
+            _ => {
+                let escaped = serde_json_wasm::to_string(value)?;
+                out.push_str(before);
+                out.push_str(&escaped[1..escaped.len() - 1]);
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
@@ -0,0 +1,292 @@
+use crate::util::condition::{decode_query_default, lower_query};
+use crate::ContractError;
+use std::collections::BTreeMap;
+use warp_protocol::controller::condition::{
+    ArrayAggregate, ArrayValue, CastValue, Condition, Expr, ListValue, NumValue, QueryExpr,
+    QuerySource, Value,
+};
+use warp_protocol::controller::controller::{Config, RewardDenom};
+use warp_protocol::controller::path::{parse_path, ParsedSelector};
+
+pub const DEFAULT_MAX_CONDITION_DEPTH: u64 = 20;
+pub const DEFAULT_MAX_CONDITION_QUERIES: u64 = 32;
+pub const DEFAULT_MAX_CONDITION_SIZE: u64 = 10240;
+
+pub struct ConditionLimits {
+    // nesting of conditions, expressions and values, the root condition is at depth 1
+    pub max_depth: u64,
+    pub max_queries: u64,
+    // bytes of the JSON encoded condition
+    pub max_size: u64,
+}
+
+impl ConditionLimits {
+    pub fn from_config(config: &Config) -> Self {
+        ConditionLimits {
+            max_depth: config
+                .max_condition_depth
+                .map_or(DEFAULT_MAX_CONDITION_DEPTH, |max| max.u64()),
+            max_queries: config
+                .max_condition_queries
+                .map_or(DEFAULT_MAX_CONDITION_QUERIES, |max| max.u64()),
+            max_size: config
+                .max_condition_size
+                .map_or(DEFAULT_MAX_CONDITION_SIZE, |max| max.u64()),
+        }
+    }
+}
+
+struct Validator {
+    limits: ConditionLimits,
+    queries: u64,
+    // first request of each query name and whether it is a snapshot
+    named: BTreeMap<String, (QuerySource, bool)>,
+    selectors: Vec<ParsedSelector>,
+}
+
+// checks a condition before it is stored, so that it cannot fail on malformed input at execution,
+// returns its selectors parsed, to be stored with it
+pub fn validate_condition(
+    config: &Config,
+    cond: &Condition,
+) -> Result<Vec<ParsedSelector>, ContractError> {
+    let limits = ConditionLimits::from_config(config);
+
+    let size = serde_json_wasm::to_vec(cond)?.len() as u64;
+    if size > limits.max_size {
+        return Err(ContractError::ConditionTooLarge {
+            size,
+            max_size: limits.max_size,
+        });
+    }
+
+    let mut validator = Validator {
+        limits,
+        queries: 0,
+        named: BTreeMap::new(),
+        selectors: vec![],
+    };
+    validator.condition(cond, 1)?;
+    Ok(validator.selectors)
+}
+
+impl Validator {
+    // depth of the children of a node at the given depth
+    fn enter(&self, depth: u64) -> Result<u64, ContractError> {
+        if depth > self.limits.max_depth {
+            return Err(ContractError::ConditionTooDeep {
+                max_depth: self.limits.max_depth,
+            });
+        }
+        Ok(depth + 1)
+    }
+
+    fn condition(&mut self, cond: &Condition, depth: u64) -> Result<(), ContractError> {
+        let depth = self.enter(depth)?;
+        match cond {
+            Condition::And(conds) | Condition::Or(conds) => {
+                if conds.is_empty() {
+                    return Err(ContractError::EmptyCondition {});
+                }
+                for cond in conds {
+                    self.condition(cond, depth)?;
+                }
+                Ok(())
+            }
+            Condition::Not(cond) => self.condition(cond, depth),
+            Condition::Expr(expr) => self.expr(expr, depth),
+            Condition::Any(cond) | Condition::All(cond) => {
+                self.array_value(&cond.array)?;
+                self.condition(&cond.condition, depth)
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, depth: u64) -> Result<(), ContractError> {
+        let depth = self.enter(depth)?;
+        match expr {
+            Expr::String(expr) => {
+                self.value(&expr.left, depth)?;
+                self.value(&expr.right, depth)
+            }
+            Expr::Uint(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.num_value(&expr.right, depth)
+            }
+            Expr::Int(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.num_value(&expr.right, depth)
+            }
+            Expr::Decimal(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.num_value(&expr.right, depth)
+            }
+            Expr::Timestamp(_) | Expr::BlockHeight(_) | Expr::Calendar(_) | Expr::JobStatus(_) => {
+                Ok(())
+            }
+            Expr::Period(expr) => match expr.period.is_zero() {
+                true => Err(ContractError::InvalidPeriod {}),
+                false => Ok(()),
+            },
+            Expr::Bool(expr) | Expr::Exists(expr) | Expr::IsNull(expr) => self.query_expr(expr),
+            Expr::StringList(expr) => {
+                self.value(&expr.left, depth)?;
+                self.list_value(&expr.right)
+            }
+            Expr::UintList(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.list_value(&expr.right)
+            }
+            Expr::IntList(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.list_value(&expr.right)
+            }
+            Expr::DecimalList(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.list_value(&expr.right)
+            }
+        }
+    }
+
+    fn value<T>(&mut self, value: &Value<T>, depth: u64) -> Result<(), ContractError> {
+        self.enter(depth)?;
+        match value {
+            Value::Simple(_) | Value::Env(_) => Ok(()),
+            Value::Query(expr) => self.query_expr(expr),
+            Value::Element(selector) => self.selector(selector),
+        }
+    }
+
+    fn list_value<T>(&mut self, value: &ListValue<T>) -> Result<(), ContractError> {
+        match value {
+            ListValue::Simple(_) => Ok(()),
+            ListValue::Query(expr) => self.query_expr(expr),
+        }
+    }
+
+    fn num_value<T, ExprOp, FnOp>(
+        &mut self,
+        value: &NumValue<T, ExprOp, FnOp>,
+        depth: u64,
+    ) -> Result<(), ContractError> {
+        let depth = self.enter(depth)?;
+        match value {
+            NumValue::Simple(_) | NumValue::Env(_) => Ok(()),
+            NumValue::Expr(expr) => {
+                self.num_value(&expr.left, depth)?;
+                self.num_value(&expr.right, depth)
+            }
+            NumValue::Query(expr) => self.query_expr(expr),
+            NumValue::Fn(expr) => self.num_value(&expr.right, depth),
+            NumValue::Clamp(expr) => {
+                self.num_value(&expr.value, depth)?;
+                self.num_value(&expr.min, depth)?;
+                self.num_value(&expr.max, depth)
+            }
+            NumValue::Cast(expr) => match &expr.value {
+                CastValue::Uint(value) => self.num_value(value, depth),
+                CastValue::Int(value) => self.num_value(value, depth),
+                CastValue::Decimal(value) => self.num_value(value, depth),
+            },
+            NumValue::StringLen(value) => self.value(value, depth),
+            NumValue::Element(selector) => self.selector(selector),
+            NumValue::Aggregate(expr) => self.array_aggregate(expr, depth),
+        }
+    }
+
+    fn array_aggregate(&mut self, expr: &ArrayAggregate, depth: u64) -> Result<(), ContractError> {
+        self.array_value(&expr.array)?;
+        if let Some(selector) = &expr.selector {
+            self.selector(selector)?;
+        }
+        if let Some(cond) = &expr.condition {
+            self.condition(cond, depth)?;
+        }
+        Ok(())
+    }
+
+    fn array_value(&mut self, value: &ArrayValue) -> Result<(), ContractError> {
+        match value {
+            ArrayValue::Query(expr) => self.query_expr(expr),
+            ArrayValue::Element(selector) => self.selector(selector),
+        }
+    }
+
+    fn query_expr(&mut self, expr: &QueryExpr) -> Result<(), ContractError> {
+        self.queries += 1;
+        if self.queries > self.limits.max_queries {
+            return Err(ContractError::TooManyQueries {
+                max_queries: self.limits.max_queries,
+            });
+        }
+
+        if let Some(default) = &expr.default {
+            decode_query_default(default)?;
+        }
+        lower_query(expr.query.clone())?;
+        self.query_name(expr)?;
+        self.selector(&expr.selector)
+    }
+
+    fn selector(&mut self, selector: &str) -> Result<(), ContractError> {
+        let path = parse_path(selector)?;
+        if !self
+            .selectors
+            .iter()
+            .any(|parsed| parsed.selector == selector)
+        {
+            self.selectors.push(ParsedSelector {
+                selector: selector.to_string(),
+                path,
+            });
+        }
+        Ok(())
+    }
+
+    // a name stands for a single query whose response is shared, a snapshot is stored per name
+    // so its name cannot be shared with a query that is not a snapshot either
+    fn query_name(&mut self, expr: &QueryExpr) -> Result<(), ContractError> {
+        let snapshot = expr.snapshot == Some(true);
+        if expr.name.is_empty() {
+            return match snapshot {
+                true => Err(ContractError::UnnamedSnapshotQuery {}),
+                false => Ok(()),
+            };
+        }
+
+        match self.named.get(&expr.name) {
+            Some((query, named_snapshot))
+                if (snapshot || *named_snapshot)
+                    && (snapshot != *named_snapshot || *query != expr.query) =>
+            {
+                Err(ContractError::ConflictingSnapshotQuery {
+                    name: expr.name.clone(),
+                })
+            }
+            Some((query, _)) if *query != expr.query => Err(ContractError::ConflictingNamedQuery {
+                name: expr.name.clone(),
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.named
+                    .insert(expr.name.clone(), (expr.query.clone(), snapshot));
+                Ok(())
+            }
+        }
+    }
+}
+
+pub fn validate_reward_denoms(reward_denoms: &[RewardDenom]) -> Result<(), ContractError> {
+    let mut denoms = reward_denoms
+        .iter()
+        .map(|reward_denom| reward_denom.denom.as_str())
+        .collect::<Vec<_>>();
+    denoms.sort_unstable();
+    denoms.dedup();
+
+    if denoms.is_empty() || denoms.len() != reward_denoms.len() || denoms.contains(&"") {
+        return Err(ContractError::InvalidRewardDenoms {});
+    }
+
+    Ok(())
+}
@@ -1,12 +1,49 @@
+use crate::controller::job::JobStatus;
+use crate::controller::storage::StorageKey;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Decimal256, QueryRequest, Uint256, Uint64};
 
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum Condition {
     And(Vec<Box<Condition>>),
     Or(Vec<Box<Condition>>),
     Not(Box<Condition>),
     Expr(Expr),
+    Any(ArrayCondition),
+    All(ArrayCondition),
+}
+
+#[cw_serde]
+pub struct ArrayCondition {
+    pub array: ArrayValue,
+    // resolved once per element, see `Value::Element`
+    pub condition: Box<Condition>,
+}
+
+#[cw_serde]
+pub enum ArrayValue {
+    Query(QueryExpr),
+    // selector into the element of the enclosing array condition
+    Element(String),
+}
+
+#[cw_serde]
+pub struct ArrayAggregate {
+    pub array: ArrayValue,
+    pub op: AggregateOp,
+    // field of each element to sum, min or max over; defaults to the element itself
+    pub selector: Option<String>,
+    // only elements matching the condition are aggregated
+    pub condition: Option<Box<Condition>>,
+}
+
+#[cw_serde]
+pub enum AggregateOp {
+    Count,
+    Sum,
+    Min,
+    Max,
 }
 
 #[cw_serde]
@@ -16,22 +53,94 @@ pub struct GenExpr<Type, Op> {
     pub right: Type,
 }
 
+#[cw_serde]
+pub struct ListExpr<Type, ListType> {
+    pub left: Type,
+    pub op: ListOp,
+    pub right: ListType,
+}
+
+#[cw_serde]
+pub enum ListValue<T> {
+    Simple(Vec<T>),
+    // selector must point at a JSON array
+    Query(QueryExpr),
+}
+
 #[cw_serde]
 pub struct TimeExpr {
     pub comparator: Uint64,
-    pub op: TimeOp, //tmp: fix this post-comp
+    pub op: NumOp,
+    // compares the seconds elapsed since the anchor instead of the block time
+    pub anchor: Option<JobAnchor>,
 }
 
 #[cw_serde]
 pub struct BlockExpr {
     pub comparator: Uint64,
     pub op: NumOp,
+    // compares the blocks elapsed since the anchor instead of the block height
+    pub anchor: Option<JobAnchor>,
+}
+
+#[cw_serde]
+pub enum JobAnchor {
+    Creation,
+    LastUpdate,
+}
+
+// field of the block time in UTC
+#[cw_serde]
+pub struct CalendarExpr {
+    pub field: CalendarField,
+    pub op: NumOp,
+    pub comparator: Uint64,
+}
+
+#[cw_serde]
+pub enum CalendarField {
+    // 1 is monday through 7 sunday
+    DayOfWeek,
+    // 0 to 23
+    HourOfDay,
+    // 1 to 31
+    DayOfMonth,
+}
+
+// status of another job, which must exist and must not depend on the job back
+#[cw_serde]
+pub struct JobStatusExpr {
+    pub job_id: Uint64,
+    pub op: StatusOp,
+    pub status: JobStatus,
+}
+
+#[cw_serde]
+pub enum StatusOp {
+    Eq,
+    Neq,
+}
+
+// holds for `tolerance` seconds after every `period` seconds elapsed since the anchor
+#[cw_serde]
+pub struct PeriodExpr {
+    pub period: Uint64,
+    pub anchor: JobAnchor,
+    pub tolerance: Uint64,
 }
 
 #[cw_serde]
 pub enum Value<T> {
     Simple(T),
     Query(QueryExpr),
+    Env(StringEnvValue),
+    // selector into the element of the enclosing array condition
+    Element(String),
+}
+
+#[cw_serde]
+pub enum StringEnvValue {
+    ChainId,
 }
 
 #[cw_serde]
@@ -40,6 +149,20 @@ pub enum NumValue<T, ExprOp, FnOp> {
     Expr(NumExprValue<T, ExprOp, FnOp>),
     Query(QueryExpr),
     Fn(NumFnValue<T, ExprOp, FnOp>),
+    Clamp(NumClampValue<T, ExprOp, FnOp>),
+    Cast(NumCastValue),
+    Env(NumEnvValue),
+    StringLen(Value<String>),
+    // selector into the element of the enclosing array condition
+    Element(String),
+    Aggregate(ArrayAggregate),
+}
+
+#[cw_serde]
+pub enum NumEnvValue {
+    // seconds
+    Time,
+    BlockHeight,
 }
 
 #[cw_serde]
@@ -47,6 +170,14 @@ pub struct NumExprValue<T, ExprOp, FnOp> {
     pub left: Box<NumValue<T, ExprOp, FnOp>>,
     pub op: ExprOp,
     pub right: Box<NumValue<T, ExprOp, FnOp>>,
+    // defaults to saturate
+    pub overflow: Option<OverflowPolicy>,
+}
+
+#[cw_serde]
+pub enum OverflowPolicy {
+    Saturate,
+    Error,
 }
 
 #[cw_serde]
@@ -55,6 +186,27 @@ pub struct NumFnValue<T, ExprOp, FnOp> {
     pub right: Box<NumValue<T, ExprOp, FnOp>>,
 }
 
+#[cw_serde]
+pub struct NumClampValue<T, ExprOp, FnOp> {
+    pub value: Box<NumValue<T, ExprOp, FnOp>>,
+    pub min: Box<NumValue<T, ExprOp, FnOp>>,
+    pub max: Box<NumValue<T, ExprOp, FnOp>>,
+}
+
+// integer = decimal * 10^decimals
+#[cw_serde]
+pub struct NumCastValue {
+    pub value: CastValue,
+    pub decimals: Option<u32>,
+}
+
+#[cw_serde]
+pub enum CastValue {
+    Uint(Box<NumValue<Uint256, NumExprOp, IntFnOp>>),
+    Int(Box<NumValue<i128, NumExprOp, IntFnOp>>),
+    Decimal(Box<NumValue<Decimal256, NumExprOp, DecimalFnOp>>),
+}
+
 #[cw_serde]
 pub enum NumExprOp {
     Add,
@@ -62,6 +214,12 @@ pub enum NumExprOp {
     Div,
     Mul,
     Mod,
+    Min,
+    Max,
+    Pow,
+    // (left - right) / right in basis points, e.g. 500 for a 5% rise and -500 for a 5% fall,
+    // a fall fails for uint and decimal values, which cannot be negative
+    PctChange,
 }
 
 #[cw_serde]
@@ -71,12 +229,17 @@ pub enum DecimalFnOp {
     Floor,
     Sqrt,
     Ceil,
+    // half up, to the given number of decimal places
+    Round(u32),
 }
 
 #[cw_serde]
 pub enum IntFnOp {
     Abs,
     Neg,
+    // half away from zero, to a multiple of 10^places, e.g. an amount with 6 decimals to whole
+    // tokens with 6
+    Round(u32),
 }
 
 #[cw_serde]
@@ -87,14 +250,66 @@ pub enum Expr {
     Decimal(GenExpr<NumValue<Decimal256, NumExprOp, DecimalFnOp>, NumOp>),
     Timestamp(TimeExpr),
     BlockHeight(BlockExpr),
+    Calendar(CalendarExpr),
+    Period(PeriodExpr),
+    JobStatus(JobStatusExpr),
     Bool(QueryExpr),
+    // the selected value is present, null included
+    Exists(QueryExpr),
+    // the selected value is present and null
+    IsNull(QueryExpr),
+    StringList(ListExpr<Value<String>, ListValue<String>>),
+    UintList(ListExpr<NumValue<Uint256, NumExprOp, IntFnOp>, ListValue<Uint256>>),
+    IntList(ListExpr<NumValue<i128, NumExprOp, IntFnOp>, ListValue<i128>>),
+    DecimalList(ListExpr<NumValue<Decimal256, NumExprOp, DecimalFnOp>, ListValue<Decimal256>>),
 }
 
 #[cw_serde]
 pub struct QueryExpr {
     pub selector: String,
-    pub query: QueryRequest<String>,
+    pub query: QuerySource,
+    // non-empty names share one query response per resolution, so a name stands for a single query
     pub name: String,
+    // JSON used when the selected value is missing or null, e.g. `"\"0\""` for a uint
+    pub default: Option<String>,
+    // decimals read as integers scaled by 10^decimals, e.g. `"1234567"` with 6 decimals is 1.234567
+    pub decimals: Option<u32>,
+    // issued once when the job is created or its condition updated, the response is stored with
+    // the job and used by every later resolution, requires a name
+    pub snapshot: Option<bool>,
+}
+
+// a raw request, or a typed shorthand the resolver lowers to one
+#[cw_serde]
+#[serde(untagged)]
+pub enum QuerySource {
+    Request(QueryRequest<String>),
+    Typed(TypedQuery),
+}
+
+impl From<QueryRequest<String>> for QuerySource {
+    fn from(request: QueryRequest<String>) -> Self {
+        QuerySource::Request(request)
+    }
+}
+
+#[cw_serde]
+pub enum TypedQuery {
+    // bank balance of a native denom
+    NativeBalance { address: String, denom: String },
+    // cw20 `balance` query
+    Cw20Balance { contract: String, address: String },
+    // cw20 `token_info` query
+    Cw20TokenInfo { contract: String },
+    // smart query with the message as JSON text instead of base64, e.g. `{"pool":{}}`
+    Smart { contract: String, msg: String },
+    // code id, creator and admin of a contract
+    ContractInfo { contract: String },
+    // value stored in the contract with cw-storage-plus, read without a smart query,
+    // a missing value reads as `null`
+    Raw { contract: String, key: StorageKey },
+    // `data` of the payload the keeper executing the job signed with the job's signer
+    SignedData {},
 }
 
 #[cw_serde]
@@ -107,12 +322,6 @@ pub enum NumOp {
     Lte,
 }
 
-#[cw_serde]
-pub enum TimeOp {
-    Lt,
-    Gt,
-}
-
 #[cw_serde]
 pub enum StringOp {
     StartsWith,
@@ -120,9 +329,66 @@ pub enum StringOp {
     Contains,
     Eq,
     Neq,
+    EqIgnoreCase,
+    NeqIgnoreCase,
+}
+
+#[cw_serde]
+pub enum ListOp {
+    In,
+    NotIn,
 }
 
 #[cw_serde]
 pub struct QueryResolveConditionMsg {
     pub condition: Condition,
 }
+
+#[cw_serde]
+pub struct QueryExplainConditionMsg {
+    pub condition: Condition,
+}
+
+// evaluated condition tree, `result` is None when the node could not be resolved
+#[cw_serde]
+pub struct ConditionTrace {
+    pub node: TraceNode,
+    pub result: Option<bool>,
+    pub error: Option<String>,
+    pub expr: Option<ExprTrace>,
+    // queries issued by this node itself, children record their own
+    pub queries: Vec<QueryTrace>,
+    // sub-conditions, one entry per array element for any/all, and the conditions aggregates
+    // filter elements with
+    pub children: Vec<ConditionTrace>,
+}
+
+#[cw_serde]
+pub enum TraceNode {
+    And,
+    Or,
+    Not,
+    Expr,
+    Any,
+    All,
+}
+
+#[cw_serde]
+pub struct ExprTrace {
+    pub kind: String,
+    // None for expressions checking a single value
+    pub op: Option<String>,
+    // resolved operands, None when missing or failed to resolve
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+#[cw_serde]
+pub struct QueryTrace {
+    pub name: String,
+    pub query: QuerySource,
+    pub selector: String,
+    pub response: String,
+    // selected JSON, None when missing
+    pub value: Option<String>,
+}
@@ -1,13 +1,20 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, QueryRequest, Uint128, Uint64};
+use cosmwasm_std::{Addr, Uint128, Uint64};
 
 use crate::controller::account::{
     AccountResponse, AccountsResponse, CreateAccountMsg, QueryAccountMsg, QueryAccountsMsg,
 };
-use crate::controller::condition::QueryResolveConditionMsg;
+use crate::controller::condition::{
+    ConditionTrace, QueryExplainConditionMsg, QueryResolveConditionMsg, QuerySource,
+};
 use crate::controller::job::{
-    CreateJobMsg, DeleteJobMsg, ExecuteJobMsg, JobResponse, JobsResponse, QueryJobMsg,
-    QueryJobsMsg, QueryResolveJobConditionMsg, UpdateJobMsg,
+    CleanupJobMsg, CreateJobMsg, DeleteJobMsg, ExecuteJobMsg, JobResponse, JobsResponse,
+    QueryExplainJobConditionMsg, QueryJobMsg, QueryJobsMsg, QueryResolveJobConditionMsg,
+    UpdateJobMsg,
+};
+use crate::controller::template::{
+    CreateJobFromTemplateMsg, DeleteTemplateMsg, EditTemplateMsg, QueryTemplateMsg,
+    QueryTemplatesMsg, SubmitTemplateMsg, TemplateResponse, TemplatesResponse,
 };
 
 //objects
@@ -15,14 +22,29 @@ use crate::controller::job::{
 pub struct Config {
     pub owner: Addr,
     pub warp_account_code_id: Uint64,
-    pub minimum_reward: Uint128,
+    // native denoms jobs can be rewarded in
+    pub reward_denoms: Vec<RewardDenom>,
     pub creation_fee_percentage: Uint128,
     pub cancellation_fee_percentage: Uint128,
+    // condition limits checked on job creation and update, None uses the contract default
+    pub max_condition_depth: Option<Uint64>,
+    pub max_condition_queries: Option<Uint64>,
+    pub max_condition_size: Option<Uint64>,
+    // share of an expired job's funds paid to whoever cleans it up, None uses the contract default
+    pub cleanup_tip_percentage: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct RewardDenom {
+    pub denom: String,
+    pub minimum_reward: Uint128,
 }
 
 #[cw_serde]
 pub struct State {
     pub current_job_id: Uint64,
+    // None for state saved before templates were added, ids then start at 1
+    pub current_template_id: Option<Uint64>,
 }
 
 //instantiate
@@ -30,9 +52,13 @@ pub struct State {
 pub struct InstantiateMsg {
     pub owner: Option<String>,
     pub warp_account_code_id: Uint64,
-    pub minimum_reward: Uint128,
+    pub reward_denoms: Vec<RewardDenom>,
     pub creation_fee: Uint128,
     pub cancellation_fee: Uint128,
+    pub max_condition_depth: Option<Uint64>,
+    pub max_condition_queries: Option<Uint64>,
+    pub max_condition_size: Option<Uint64>,
+    pub cleanup_tip: Option<Uint128>,
 }
 
 //execute
@@ -42,23 +68,34 @@ pub enum ExecuteMsg {
     DeleteJob(DeleteJobMsg),
     UpdateJob(UpdateJobMsg),
     ExecuteJob(ExecuteJobMsg),
+    CleanupJob(CleanupJobMsg),
 
     CreateAccount(CreateAccountMsg),
 
+    SubmitTemplate(SubmitTemplateMsg),
+    EditTemplate(EditTemplateMsg),
+    DeleteTemplate(DeleteTemplateMsg),
+    CreateJobFromTemplate(CreateJobFromTemplateMsg),
+
     UpdateConfig(UpdateConfigMsg),
 }
 
 #[cw_serde]
 pub struct UpdateConfigMsg {
     pub owner: Option<String>,
-    pub minimum_reward: Option<Uint128>,
+    pub reward_denoms: Option<Vec<RewardDenom>>,
     pub creation_fee_percentage: Option<Uint128>,
     pub cancellation_fee_percentage: Option<Uint128>,
+    pub max_condition_depth: Option<Uint64>,
+    pub max_condition_queries: Option<Uint64>,
+    pub max_condition_size: Option<Uint64>,
+    pub cleanup_tip_percentage: Option<Uint128>,
 }
 
 //query
 #[derive(QueryResponses)]
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum QueryMsg {
     #[returns(JobResponse)]
     QueryJob(QueryJobMsg),
@@ -68,6 +105,10 @@ pub enum QueryMsg {
     QueryResolveJobCondition(QueryResolveJobConditionMsg),
     #[returns(bool)]
     QueryResolveCondition(QueryResolveConditionMsg),
+    #[returns(ConditionTrace)]
+    QueryExplainJobCondition(QueryExplainJobConditionMsg),
+    #[returns(ConditionTrace)]
+    QueryExplainCondition(QueryExplainConditionMsg),
 
     #[returns(SimulateResponse)]
     SimulateQuery(SimulateQueryMsg),
@@ -77,13 +118,18 @@ pub enum QueryMsg {
     #[returns(AccountsResponse)]
     QueryAccounts(QueryAccountsMsg),
 
+    #[returns(TemplateResponse)]
+    QueryTemplate(QueryTemplateMsg),
+    #[returns(TemplatesResponse)]
+    QueryTemplates(QueryTemplatesMsg),
+
     #[returns(ConfigResponse)]
     QueryConfig(QueryConfigMsg),
 }
 
 #[cw_serde]
 pub struct SimulateQueryMsg {
-    pub query: QueryRequest<String>,
+    pub query: QuerySource,
 }
 
 #[cw_serde]
@@ -0,0 +1,218 @@
+use crate::controller::dsl::DslError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Selector(String),
+    // raw JSON object, e.g. a query request
+    Json(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Semicolon,
+    Assign,
+    And,
+    Or,
+    Not,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Lte,
+    Gte,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Caret,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Spanned {
+    pub token: Token,
+    pub position: usize,
+}
+
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Spanned>, DslError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        let position = idx;
+
+        if c.is_whitespace() {
+            idx += 1;
+            continue;
+        }
+
+        let (token, next) = match c {
+            '"' => {
+                let (value, next) = read_string(&chars, idx)?;
+                (Token::Str(value), next)
+            }
+            '$' => {
+                let (next, _) = scan_selector(&chars, idx);
+                (Token::Selector(chars[idx..next].iter().collect()), next)
+            }
+            '{' => {
+                let next = scan_json(&chars, idx)?;
+                (Token::Json(chars[idx..next].iter().collect()), next)
+            }
+            c if c.is_ascii_digit() => {
+                let mut next = idx;
+                while next < chars.len() && chars[next].is_ascii_digit() {
+                    next += 1;
+                }
+                if next + 1 < chars.len() && chars[next] == '.' && chars[next + 1].is_ascii_digit()
+                {
+                    next += 1;
+                    while next < chars.len() && chars[next].is_ascii_digit() {
+                        next += 1;
+                    }
+                }
+                (Token::Number(chars[idx..next].iter().collect()), next)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut next = idx;
+                while next < chars.len()
+                    && (chars[next].is_ascii_alphanumeric() || matches!(chars[next], '_' | '.'))
+                {
+                    next += 1;
+                }
+                (Token::Ident(chars[idx..next].iter().collect()), next)
+            }
+            _ => {
+                let pair = (c, chars.get(idx + 1).copied());
+                match pair {
+                    ('&', Some('&')) => (Token::And, idx + 2),
+                    ('|', Some('|')) => (Token::Or, idx + 2),
+                    ('=', Some('=')) => (Token::Eq, idx + 2),
+                    ('!', Some('=')) => (Token::Neq, idx + 2),
+                    ('<', Some('=')) => (Token::Lte, idx + 2),
+                    ('>', Some('=')) => (Token::Gte, idx + 2),
+                    ('=', _) => (Token::Assign, idx + 1),
+                    ('!', _) => (Token::Not, idx + 1),
+                    ('<', _) => (Token::Lt, idx + 1),
+                    ('>', _) => (Token::Gt, idx + 1),
+                    ('(', _) => (Token::LParen, idx + 1),
+                    (')', _) => (Token::RParen, idx + 1),
+                    ('[', _) => (Token::LBracket, idx + 1),
+                    (']', _) => (Token::RBracket, idx + 1),
+                    (',', _) => (Token::Comma, idx + 1),
+                    (';', _) => (Token::Semicolon, idx + 1),
+                    ('+', _) => (Token::Plus, idx + 1),
+                    ('-', _) => (Token::Minus, idx + 1),
+                    ('*', _) => (Token::Star, idx + 1),
+                    ('/', _) => (Token::Slash, idx + 1),
+                    ('%', _) => (Token::Percent, idx + 1),
+                    ('^', _) => (Token::Caret, idx + 1),
+                    _ => {
+                        return Err(DslError::UnexpectedCharacter {
+                            character: c,
+                            position,
+                        })
+                    }
+                }
+            }
+        };
+
+        tokens.push(Spanned { token, position });
+        idx = next;
+    }
+
+    Ok(tokens)
+}
+
+fn read_string(chars: &[char], from: usize) -> Result<(String, usize), DslError> {
+    let mut idx = from + 1;
+    let mut value = String::new();
+
+    loop {
+        match chars.get(idx) {
+            None => return Err(DslError::Unterminated { position: from }),
+            Some('"') => return Ok((value, idx + 1)),
+            Some('\\') => {
+                let escaped = chars
+                    .get(idx + 1)
+                    .ok_or(DslError::Unterminated { position: from })?;
+                value.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    c => *c,
+                });
+                idx += 2;
+            }
+            Some(c) => {
+                value.push(*c);
+                idx += 1;
+            }
+        }
+    }
+}
+
+// a selector runs until whitespace, `,` or an unmatched `)` outside of brackets,
+// returns its end and whether its brackets, parentheses and quotes are all closed
+pub(crate) fn scan_selector(chars: &[char], from: usize) -> (usize, bool) {
+    let mut idx = from;
+    let mut brackets = 0;
+    let mut parens = 0;
+
+    while idx < chars.len() {
+        match chars[idx] {
+            '[' => brackets += 1,
+            ']' if brackets > 0 => brackets -= 1,
+            quote @ ('"' | '\'') if brackets > 0 => {
+                idx += 1;
+                while idx < chars.len() && chars[idx] != quote {
+                    if chars[idx] == '\\' {
+                        idx += 1;
+                    }
+                    idx += 1;
+                }
+                if idx >= chars.len() {
+                    return (chars.len(), false);
+                }
+            }
+            '(' if brackets == 0 => parens += 1,
+            ')' if brackets == 0 && parens > 0 => parens -= 1,
+            c if brackets == 0 && (c.is_whitespace() || c == ',' || c == ')') => break,
+            _ => {}
+        }
+        idx += 1;
+    }
+
+    (idx, brackets == 0 && parens == 0)
+}
+
+fn scan_json(chars: &[char], from: usize) -> Result<usize, DslError> {
+    let mut idx = from;
+    let mut depth = 0;
+    let mut in_string = false;
+
+    while idx < chars.len() {
+        match (in_string, chars[idx]) {
+            (true, '\\') => idx += 1,
+            (true, '"') => in_string = false,
+            (false, '"') => in_string = true,
+            (false, '{') => depth += 1,
+            (false, '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(idx + 1);
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+
+    Err(DslError::Unterminated { position: from })
+}
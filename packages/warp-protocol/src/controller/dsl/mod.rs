@@ -0,0 +1,204 @@
+// text syntax for conditions, `parse_condition(&print_condition(&cond)) == Ok(cond)`
+//
+// let "pool" = {"wasm":{"smart":{"contract_addr":"terra1...","msg":"eyJwb29sIjp7fX0="}}};
+// query("pool", $.price) as decimal > 1.05 && block.time > 1700000000
+//
+// - conditions - `a && b`, `a || b`, `!a`, `(a)`, `and(a)`, `or()`, `any(array, cond)`, `all(array, cond)`
+// - comparisons - `left as <uint|int|decimal|string> <op> right`, `query(...) as bool`,
+//   `exists(query(...))`, `is_null(query(...))`, `left as uint in [1, 2]`, `left as uint not in query(...)`
+// - time and height - `block.time > 1700000000`, `block.height since creation >= 100`
+// - schedules - `block.day_of_week <= 5`, `block.hour_of_day == 9`, `block.day_of_month == 1`,
+//   `every(86400, since = creation, within = 600)`
+// - other jobs - `job(42) is executed`, `job(42) is not pending`
+// - queries - `query("name", $.selector)` for a `let` definition, `query("name" {...}, $.selector)`,
+//   `query({...}, $.selector)` for unnamed ones, options `default = "..."`, `decimals = 6` and
+//   `snapshot = true`, typed shorthands such as `{"native_balance":{"address":"...","denom":"uluna"}}`
+//   in place of a request
+// - values - literals, `block.time`, `block.height`, `chain_id`, `elem($.selector)`, `len(string)`,
+//   `(a + b)`, `- * / % ^`, `min(a, b)`, `max(a, b)`, `pct_change(a, b)`, `add(a, b, overflow = error)`,
+//   `abs(a)`, `neg(a)`, `floor(a)`, `ceil(a)`, `sqrt(a)`, `round(a, 2)`, `clamp(a, min, max)`,
+//   `from_uint(a, decimals = 6)`, `from_int(a)`, `from_decimal(a)`,
+//   `count(array, select = $.selector, where = cond)`, `sum(...)`, `min_of(...)`, `max_of(...)`
+mod lexer;
+mod parser;
+mod printer;
+
+pub use parser::parse_condition;
+pub use printer::print_condition;
+
+use crate::controller::condition::{
+    DecimalFnOp, Expr, GenExpr, IntFnOp, ListExpr, ListValue, NumExprOp, NumOp, NumValue,
+};
+use cosmwasm_std::{Decimal256, Uint256};
+use std::str::FromStr;
+use thiserror::Error;
+
+// positions are 0-based character offsets into the input
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DslError {
+    #[error("Unexpected character '{character}' at position {position}")]
+    UnexpectedCharacter { character: char, position: usize },
+
+    #[error("Unterminated string or query at position {position}")]
+    Unterminated { position: usize },
+
+    #[error("Expected {expected} at position {position}")]
+    Expected { expected: String, position: usize },
+
+    #[error("Invalid {kind} literal '{literal}' at position {position}")]
+    InvalidLiteral {
+        kind: String,
+        literal: String,
+        position: usize,
+    },
+
+    #[error("Invalid query request at position {position}")]
+    InvalidQuery { position: usize },
+
+    #[error("Query '{name}' is not defined, at position {position}")]
+    UndefinedQuery { name: String, position: usize },
+
+    #[error("Query '{name}' is defined more than once, at position {position}")]
+    DuplicateQuery { name: String, position: usize },
+
+    #[error("Missing `as <type>` for the comparison at position {position}")]
+    MissingType { position: usize },
+
+    #[error("Unknown function '{name}' at position {position}")]
+    UnknownFunction { name: String, position: usize },
+
+    #[error("Invalid arguments for '{name}' at position {position}")]
+    InvalidArguments { name: String, position: usize },
+}
+
+// numeric domains of comparisons, literals and functions differ per domain
+pub(crate) trait Numeric: Sized {
+    type FnOp;
+    const KIND: &'static str;
+
+    fn parse_literal(literal: &str) -> Option<Self>;
+    fn print_literal(&self) -> String;
+    fn parse_fn(name: &str, places: Option<u32>) -> Option<Self::FnOp>;
+    fn print_fn(op: &Self::FnOp) -> (&'static str, Option<u32>);
+    fn expr(expr: GenExpr<NumValue<Self, NumExprOp, Self::FnOp>, NumOp>) -> Expr;
+    fn list_expr(expr: ListExpr<NumValue<Self, NumExprOp, Self::FnOp>, ListValue<Self>>) -> Expr;
+}
+
+fn parse_int_fn(name: &str, places: Option<u32>) -> Option<IntFnOp> {
+    match (name, places) {
+        ("abs", None) => Some(IntFnOp::Abs),
+        ("neg", None) => Some(IntFnOp::Neg),
+        ("round", Some(places)) => Some(IntFnOp::Round(places)),
+        _ => None,
+    }
+}
+
+fn print_int_fn(op: &IntFnOp) -> (&'static str, Option<u32>) {
+    match op {
+        IntFnOp::Abs => ("abs", None),
+        IntFnOp::Neg => ("neg", None),
+        IntFnOp::Round(places) => ("round", Some(*places)),
+    }
+}
+
+impl Numeric for Uint256 {
+    type FnOp = IntFnOp;
+    const KIND: &'static str = "uint";
+
+    fn parse_literal(literal: &str) -> Option<Self> {
+        Uint256::from_str(literal).ok()
+    }
+
+    fn print_literal(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse_fn(name: &str, places: Option<u32>) -> Option<Self::FnOp> {
+        parse_int_fn(name, places)
+    }
+
+    fn print_fn(op: &Self::FnOp) -> (&'static str, Option<u32>) {
+        print_int_fn(op)
+    }
+
+    fn expr(expr: GenExpr<NumValue<Self, NumExprOp, Self::FnOp>, NumOp>) -> Expr {
+        Expr::Uint(expr)
+    }
+
+    fn list_expr(expr: ListExpr<NumValue<Self, NumExprOp, Self::FnOp>, ListValue<Self>>) -> Expr {
+        Expr::UintList(expr)
+    }
+}
+
+impl Numeric for i128 {
+    type FnOp = IntFnOp;
+    const KIND: &'static str = "int";
+
+    fn parse_literal(literal: &str) -> Option<Self> {
+        i128::from_str(literal).ok()
+    }
+
+    fn print_literal(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse_fn(name: &str, places: Option<u32>) -> Option<Self::FnOp> {
+        parse_int_fn(name, places)
+    }
+
+    fn print_fn(op: &Self::FnOp) -> (&'static str, Option<u32>) {
+        print_int_fn(op)
+    }
+
+    fn expr(expr: GenExpr<NumValue<Self, NumExprOp, Self::FnOp>, NumOp>) -> Expr {
+        Expr::Int(expr)
+    }
+
+    fn list_expr(expr: ListExpr<NumValue<Self, NumExprOp, Self::FnOp>, ListValue<Self>>) -> Expr {
+        Expr::IntList(expr)
+    }
+}
+
+impl Numeric for Decimal256 {
+    type FnOp = DecimalFnOp;
+    const KIND: &'static str = "decimal";
+
+    fn parse_literal(literal: &str) -> Option<Self> {
+        Decimal256::from_str(literal).ok()
+    }
+
+    fn print_literal(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse_fn(name: &str, places: Option<u32>) -> Option<Self::FnOp> {
+        match (name, places) {
+            ("abs", None) => Some(DecimalFnOp::Abs),
+            ("neg", None) => Some(DecimalFnOp::Neg),
+            ("floor", None) => Some(DecimalFnOp::Floor),
+            ("sqrt", None) => Some(DecimalFnOp::Sqrt),
+            ("ceil", None) => Some(DecimalFnOp::Ceil),
+            ("round", Some(places)) => Some(DecimalFnOp::Round(places)),
+            _ => None,
+        }
+    }
+
+    fn print_fn(op: &Self::FnOp) -> (&'static str, Option<u32>) {
+        match op {
+            DecimalFnOp::Abs => ("abs", None),
+            DecimalFnOp::Neg => ("neg", None),
+            DecimalFnOp::Floor => ("floor", None),
+            DecimalFnOp::Sqrt => ("sqrt", None),
+            DecimalFnOp::Ceil => ("ceil", None),
+            DecimalFnOp::Round(places) => ("round", Some(*places)),
+        }
+    }
+
+    fn expr(expr: GenExpr<NumValue<Self, NumExprOp, Self::FnOp>, NumOp>) -> Expr {
+        Expr::Decimal(expr)
+    }
+
+    fn list_expr(expr: ListExpr<NumValue<Self, NumExprOp, Self::FnOp>, ListValue<Self>>) -> Expr {
+        Expr::DecimalList(expr)
+    }
+}
@@ -0,0 +1,1028 @@
+use crate::controller::condition::{
+    AggregateOp, ArrayAggregate, ArrayCondition, ArrayValue, BlockExpr, CalendarExpr,
+    CalendarField, CastValue, Condition, Expr, GenExpr, JobAnchor, JobStatusExpr, ListExpr, ListOp,
+    ListValue, NumCastValue, NumClampValue, NumEnvValue, NumExprOp, NumExprValue, NumFnValue,
+    NumOp, NumValue, OverflowPolicy, PeriodExpr, QueryExpr, QuerySource, StatusOp, StringEnvValue,
+    StringOp, TimeExpr, Value,
+};
+use crate::controller::dsl::lexer::{tokenize, Spanned, Token};
+use crate::controller::dsl::{DslError, Numeric};
+use crate::controller::job::JobStatus;
+use cosmwasm_std::{Decimal256, Uint256, Uint64};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+pub fn parse_condition(input: &str) -> Result<Condition, DslError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        end: input.chars().count(),
+        queries: BTreeMap::new(),
+    };
+
+    parser.definitions()?;
+    let cond = parser.cond()?;
+    if parser.peek().is_some() {
+        return Err(parser.expected("end of input"));
+    }
+
+    Ok(cond)
+}
+
+enum Kind {
+    Uint,
+    Int,
+    Decimal,
+    String,
+    Bool,
+}
+
+// values are parsed untyped, the type is only known once `as <type>` is reached
+struct Node {
+    value: NodeValue,
+    position: usize,
+}
+
+enum NodeValue {
+    Number(String),
+    Str(String),
+    Env(String),
+    Query(QueryExpr),
+    Element(String),
+    Aggregate(ArrayAggregate),
+    Binary {
+        op: NumExprOp,
+        overflow: Option<OverflowPolicy>,
+        left: Box<Node>,
+        right: Box<Node>,
+    },
+    Call {
+        name: String,
+        args: Vec<Node>,
+        decimals: Option<u32>,
+        overflow: Option<OverflowPolicy>,
+    },
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    end: usize,
+    // `let` definitions of named queries
+    queries: BTreeMap<String, QuerySource>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens
+            .get(self.pos + offset)
+            .map(|spanned| &spanned.token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |spanned| spanned.position)
+    }
+
+    fn expected(&self, expected: &str) -> DslError {
+        DslError::Expected {
+            expected: expected.to_string(),
+            position: self.position(),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<(), DslError> {
+        if self.eat(&token) {
+            return Ok(());
+        }
+        Err(self.expected(expected))
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == name)
+    }
+
+    fn is_call(&self, name: &str) -> bool {
+        self.is_ident(name) && self.peek_at(1) == Some(&Token::LParen)
+    }
+
+    fn is_keyword_arg(&self, name: &str) -> bool {
+        self.is_ident(name) && self.peek_at(1) == Some(&Token::Assign)
+    }
+
+    fn eat_ident(&mut self, name: &str) -> bool {
+        if self.is_ident(name) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn string(&mut self, expected: &str) -> Result<String, DslError> {
+        match self.peek().cloned() {
+            Some(Token::Str(value)) => {
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => Err(self.expected(expected)),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, kind: &str) -> Result<T, DslError> {
+        let position = self.position();
+        match self.peek().cloned() {
+            Some(Token::Number(literal)) => {
+                self.pos += 1;
+                literal.parse().map_err(|_| DslError::InvalidLiteral {
+                    kind: kind.to_string(),
+                    literal,
+                    position,
+                })
+            }
+            _ => Err(self.expected(kind)),
+        }
+    }
+
+    fn selector(&mut self) -> Result<String, DslError> {
+        match self.peek().cloned() {
+            Some(Token::Selector(selector)) | Some(Token::Str(selector)) => {
+                self.pos += 1;
+                Ok(selector)
+            }
+            _ => Err(self.expected("selector")),
+        }
+    }
+
+    fn definitions(&mut self) -> Result<(), DslError> {
+        while self.is_ident("let") && matches!(self.peek_at(1), Some(Token::Str(_))) {
+            self.pos += 1;
+            let position = self.position();
+            let name = self.string("query name")?;
+            self.expect(Token::Assign, "'='")?;
+            let query = self.query_request()?;
+            self.expect(Token::Semicolon, "';'")?;
+
+            if self.queries.insert(name.clone(), query).is_some() {
+                return Err(DslError::DuplicateQuery { name, position });
+            }
+        }
+        Ok(())
+    }
+
+    fn query_request(&mut self) -> Result<QuerySource, DslError> {
+        let position = self.position();
+        match self.peek().cloned() {
+            Some(Token::Json(json)) => {
+                self.pos += 1;
+                serde_json_wasm::from_str(&json).map_err(|_| DslError::InvalidQuery { position })
+            }
+            _ => Err(self.expected("query request")),
+        }
+    }
+
+    fn cond(&mut self) -> Result<Condition, DslError> {
+        let first = self.and()?;
+        if self.peek() != Some(&Token::Or) {
+            return Ok(first);
+        }
+
+        let mut conds = vec![Box::new(first)];
+        while self.eat(&Token::Or) {
+            conds.push(Box::new(self.and()?));
+        }
+        Ok(Condition::Or(conds))
+    }
+
+    fn and(&mut self) -> Result<Condition, DslError> {
+        let first = self.unary()?;
+        if self.peek() != Some(&Token::And) {
+            return Ok(first);
+        }
+
+        let mut conds = vec![Box::new(first)];
+        while self.eat(&Token::And) {
+            conds.push(Box::new(self.unary()?));
+        }
+        Ok(Condition::And(conds))
+    }
+
+    fn unary(&mut self) -> Result<Condition, DslError> {
+        if self.eat(&Token::Not) {
+            return Ok(Condition::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Condition, DslError> {
+        // `(` opens either a group of conditions or a parenthesized value
+        if self.peek() == Some(&Token::LParen) {
+            let start = self.pos;
+            if let Ok(cond) = self.group() {
+                if !self.continues_value() {
+                    return Ok(cond);
+                }
+            }
+            self.pos = start;
+            return Ok(Condition::Expr(self.comparison()?));
+        }
+
+        if self.is_call("and") || self.is_call("or") {
+            let or = self.is_ident("or");
+            self.pos += 2;
+
+            let mut conds = vec![];
+            if !self.eat(&Token::RParen) {
+                loop {
+                    conds.push(Box::new(self.cond()?));
+                    if self.eat(&Token::RParen) {
+                        break;
+                    }
+                    self.expect(Token::Comma, "',' or ')'")?;
+                }
+            }
+
+            return Ok(match or {
+                true => Condition::Or(conds),
+                false => Condition::And(conds),
+            });
+        }
+
+        if self.is_call("any") || self.is_call("all") {
+            let any = self.is_ident("any");
+            self.pos += 2;
+
+            let array = self.array()?;
+            self.expect(Token::Comma, "','")?;
+            let condition = Box::new(self.cond()?);
+            self.expect(Token::RParen, "')'")?;
+
+            let cond = ArrayCondition { array, condition };
+            return Ok(match any {
+                true => Condition::Any(cond),
+                false => Condition::All(cond),
+            });
+        }
+
+        Ok(Condition::Expr(self.comparison()?))
+    }
+
+    fn group(&mut self) -> Result<Condition, DslError> {
+        self.expect(Token::LParen, "'('")?;
+        let cond = self.cond()?;
+        self.expect(Token::RParen, "')'")?;
+        Ok(cond)
+    }
+
+    fn continues_value(&self) -> bool {
+        match self.peek() {
+            Some(
+                Token::Eq
+                | Token::Neq
+                | Token::Lt
+                | Token::Gt
+                | Token::Lte
+                | Token::Gte
+                | Token::Plus
+                | Token::Minus
+                | Token::Star
+                | Token::Slash
+                | Token::Percent
+                | Token::Caret,
+            ) => true,
+            Some(Token::Ident(ident)) => matches!(
+                ident.as_str(),
+                "as" | "in"
+                    | "not"
+                    | "starts_with"
+                    | "ends_with"
+                    | "contains"
+                    | "eq_ignore_case"
+                    | "neq_ignore_case"
+            ),
+            _ => false,
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, DslError> {
+        let position = self.position();
+
+        if self.is_call("exists") || self.is_call("is_null") {
+            let exists = self.is_ident("exists");
+            self.pos += 2;
+            let query = into_query(self.atom()?)?;
+            self.expect(Token::RParen, "')'")?;
+            return Ok(match exists {
+                true => Expr::Exists(query),
+                false => Expr::IsNull(query),
+            });
+        }
+
+        if self.is_call("every") {
+            self.pos += 2;
+            return self.period_expr();
+        }
+
+        if self.is_call("job") {
+            self.pos += 2;
+            return self.job_status_expr();
+        }
+
+        let field = match self.peek() {
+            Some(Token::Ident(ident)) => calendar_field(ident),
+            _ => None,
+        };
+        if let Some(field) = field {
+            self.pos += 1;
+            let op = self.num_op()?;
+            let comparator = Uint64::new(self.number("uint")?);
+            return Ok(Expr::Calendar(CalendarExpr {
+                field,
+                op,
+                comparator,
+            }));
+        }
+
+        if (self.is_ident("block.time") || self.is_ident("block.height"))
+            && (matches!(self.peek_at(1), Some(Token::Ident(ident)) if ident == "since")
+                || matches!(
+                    self.peek_at(1),
+                    Some(Token::Eq | Token::Neq | Token::Lt | Token::Gt | Token::Lte | Token::Gte)
+                ))
+        {
+            return self.time_expr();
+        }
+
+        let left = self.arith()?;
+        let kind = match self.eat_ident("as") {
+            true => self.kind()?,
+            false => match &left.value {
+                NodeValue::Str(_) => Kind::String,
+                NodeValue::Env(name) if name == "chain_id" => Kind::String,
+                _ => return Err(DslError::MissingType { position }),
+            },
+        };
+
+        match kind {
+            Kind::Bool => Ok(Expr::Bool(into_query(left)?)),
+            Kind::String => {
+                if let Some(op) = self.list_op() {
+                    let right = self.list_value(into_string_literal)?;
+                    return Ok(Expr::StringList(ListExpr {
+                        left: into_string_value(left)?,
+                        op,
+                        right,
+                    }));
+                }
+
+                let op = self.string_op()?;
+                let right = self.arith()?;
+                Ok(Expr::String(GenExpr {
+                    left: into_string_value(left)?,
+                    op,
+                    right: into_string_value(right)?,
+                }))
+            }
+            Kind::Uint => self.num_comparison::<Uint256>(left),
+            Kind::Int => self.num_comparison::<i128>(left),
+            Kind::Decimal => self.num_comparison::<Decimal256>(left),
+        }
+    }
+
+    fn num_comparison<T: Numeric>(&mut self, left: Node) -> Result<Expr, DslError> {
+        if let Some(op) = self.list_op() {
+            let right = self.list_value(into_literal::<T>)?;
+            return Ok(T::list_expr(ListExpr {
+                left: into_num(left)?,
+                op,
+                right,
+            }));
+        }
+
+        let op = self.num_op()?;
+        let right = self.arith()?;
+        Ok(T::expr(GenExpr {
+            left: into_num(left)?,
+            op,
+            right: into_num(right)?,
+        }))
+    }
+
+    fn time_expr(&mut self) -> Result<Expr, DslError> {
+        let time = self.is_ident("block.time");
+        self.pos += 1;
+
+        let anchor = match self.eat_ident("since") {
+            false => None,
+            true => Some(self.anchor()?),
+        };
+        let op = self.num_op()?;
+        let comparator = Uint64::new(self.number("uint")?);
+
+        Ok(match time {
+            true => Expr::Timestamp(TimeExpr {
+                comparator,
+                op,
+                anchor,
+            }),
+            false => Expr::BlockHeight(BlockExpr {
+                comparator,
+                op,
+                anchor,
+            }),
+        })
+    }
+
+    fn period_expr(&mut self) -> Result<Expr, DslError> {
+        let period = Uint64::new(self.number("uint")?);
+
+        let mut anchor = None;
+        let mut tolerance = None;
+        while self.eat(&Token::Comma) {
+            if self.is_keyword_arg("since") {
+                self.pos += 2;
+                anchor = Some(self.anchor()?);
+            } else if self.is_keyword_arg("within") {
+                self.pos += 2;
+                tolerance = Some(Uint64::new(self.number("uint")?));
+            } else {
+                return Err(self.expected("since or within"));
+            }
+        }
+
+        let (anchor, tolerance) = match (anchor, tolerance) {
+            (Some(anchor), Some(tolerance)) => (anchor, tolerance),
+            _ => return Err(self.expected("since and within")),
+        };
+        self.expect(Token::RParen, "')'")?;
+
+        Ok(Expr::Period(PeriodExpr {
+            period,
+            anchor,
+            tolerance,
+        }))
+    }
+
+    fn job_status_expr(&mut self) -> Result<Expr, DslError> {
+        let job_id = Uint64::new(self.number("uint")?);
+        self.expect(Token::RParen, "')'")?;
+
+        if !self.eat_ident("is") {
+            return Err(self.expected("is"));
+        }
+        let op = match self.eat_ident("not") {
+            true => StatusOp::Neq,
+            false => StatusOp::Eq,
+        };
+        let status = match self.next() {
+            Some(Token::Ident(status)) if status == "pending" => JobStatus::Pending,
+            Some(Token::Ident(status)) if status == "executed" => JobStatus::Executed,
+            Some(Token::Ident(status)) if status == "failed" => JobStatus::Failed,
+            Some(Token::Ident(status)) if status == "cancelled" => JobStatus::Cancelled,
+            Some(Token::Ident(status)) if status == "expired" => JobStatus::Expired,
+            _ => {
+                self.pos -= 1;
+                return Err(self.expected("job status"));
+            }
+        };
+
+        Ok(Expr::JobStatus(JobStatusExpr { job_id, op, status }))
+    }
+
+    fn anchor(&mut self) -> Result<JobAnchor, DslError> {
+        if self.eat_ident("creation") {
+            return Ok(JobAnchor::Creation);
+        }
+        if self.eat_ident("last_update") {
+            return Ok(JobAnchor::LastUpdate);
+        }
+        Err(self.expected("creation or last_update"))
+    }
+
+    fn kind(&mut self) -> Result<Kind, DslError> {
+        let kind = match self.peek() {
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "uint" => Kind::Uint,
+                "int" => Kind::Int,
+                "decimal" => Kind::Decimal,
+                "string" => Kind::String,
+                "bool" => Kind::Bool,
+                _ => return Err(self.expected("uint, int, decimal, string or bool")),
+            },
+            _ => return Err(self.expected("uint, int, decimal, string or bool")),
+        };
+        self.pos += 1;
+        Ok(kind)
+    }
+
+    fn num_op(&mut self) -> Result<NumOp, DslError> {
+        let op = match self.peek() {
+            Some(Token::Eq) => NumOp::Eq,
+            Some(Token::Neq) => NumOp::Neq,
+            Some(Token::Lt) => NumOp::Lt,
+            Some(Token::Gt) => NumOp::Gt,
+            Some(Token::Lte) => NumOp::Lte,
+            Some(Token::Gte) => NumOp::Gte,
+            _ => return Err(self.expected("comparison operator")),
+        };
+        self.pos += 1;
+        Ok(op)
+    }
+
+    fn string_op(&mut self) -> Result<StringOp, DslError> {
+        let op = match self.peek() {
+            Some(Token::Eq) => StringOp::Eq,
+            Some(Token::Neq) => StringOp::Neq,
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "starts_with" => StringOp::StartsWith,
+                "ends_with" => StringOp::EndsWith,
+                "contains" => StringOp::Contains,
+                "eq_ignore_case" => StringOp::EqIgnoreCase,
+                "neq_ignore_case" => StringOp::NeqIgnoreCase,
+                _ => return Err(self.expected("string operator")),
+            },
+            _ => return Err(self.expected("string operator")),
+        };
+        self.pos += 1;
+        Ok(op)
+    }
+
+    fn list_op(&mut self) -> Option<ListOp> {
+        if self.eat_ident("in") {
+            return Some(ListOp::In);
+        }
+        if self.is_ident("not")
+            && matches!(self.peek_at(1), Some(Token::Ident(ident)) if ident == "in")
+        {
+            self.pos += 2;
+            return Some(ListOp::NotIn);
+        }
+        None
+    }
+
+    fn list_value<T>(
+        &mut self,
+        literal: fn(Node) -> Result<T, DslError>,
+    ) -> Result<ListValue<T>, DslError> {
+        if !self.eat(&Token::LBracket) {
+            return Ok(ListValue::Query(into_query(self.atom()?)?));
+        }
+
+        let mut values = vec![];
+        if !self.eat(&Token::RBracket) {
+            loop {
+                values.push(literal(self.atom()?)?);
+                if self.eat(&Token::RBracket) {
+                    break;
+                }
+                self.expect(Token::Comma, "',' or ']'")?;
+            }
+        }
+        Ok(ListValue::Simple(values))
+    }
+
+    fn array(&mut self) -> Result<ArrayValue, DslError> {
+        let node = self.atom()?;
+        match node.value {
+            NodeValue::Query(expr) => Ok(ArrayValue::Query(expr)),
+            NodeValue::Element(selector) => Ok(ArrayValue::Element(selector)),
+            _ => Err(DslError::Expected {
+                expected: "query or elem".to_string(),
+                position: node.position,
+            }),
+        }
+    }
+
+    fn arith(&mut self) -> Result<Node, DslError> {
+        let mut left = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => NumExprOp::Add,
+                Some(Token::Minus) => NumExprOp::Sub,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = binary(op, left, self.term()?);
+        }
+    }
+
+    fn term(&mut self) -> Result<Node, DslError> {
+        let mut left = self.power()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => NumExprOp::Mul,
+                Some(Token::Slash) => NumExprOp::Div,
+                Some(Token::Percent) => NumExprOp::Mod,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = binary(op, left, self.power()?);
+        }
+    }
+
+    fn power(&mut self) -> Result<Node, DslError> {
+        let base = self.atom()?;
+        if self.eat(&Token::Caret) {
+            return Ok(binary(NumExprOp::Pow, base, self.power()?));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, DslError> {
+        let position = self.position();
+        let value = match (self.peek().cloned(), self.peek_at(1).cloned()) {
+            (Some(Token::Number(literal)), _) => {
+                self.pos += 1;
+                NodeValue::Number(literal)
+            }
+            (Some(Token::Minus), Some(Token::Number(literal))) => {
+                self.pos += 2;
+                NodeValue::Number(format!("-{}", literal))
+            }
+            (Some(Token::Str(value)), _) => {
+                self.pos += 1;
+                NodeValue::Str(value)
+            }
+            (Some(Token::LParen), _) => {
+                self.pos += 1;
+                let node = self.arith()?;
+                self.expect(Token::RParen, "')'")?;
+                return Ok(node);
+            }
+            (Some(Token::Ident(name)), Some(Token::LParen)) => return self.call(name),
+            (Some(Token::Ident(name)), _)
+                if matches!(name.as_str(), "block.time" | "block.height" | "chain_id") =>
+            {
+                self.pos += 1;
+                NodeValue::Env(name)
+            }
+            _ => return Err(self.expected("value")),
+        };
+
+        Ok(Node { value, position })
+    }
+
+    fn call(&mut self, name: String) -> Result<Node, DslError> {
+        let position = self.position();
+        self.pos += 2;
+
+        let value = match name.as_str() {
+            "query" => NodeValue::Query(self.query_args()?),
+            "elem" => {
+                let selector = self.selector()?;
+                self.expect(Token::RParen, "')'")?;
+                NodeValue::Element(selector)
+            }
+            "count" => NodeValue::Aggregate(self.aggregate_args(AggregateOp::Count)?),
+            "sum" => NodeValue::Aggregate(self.aggregate_args(AggregateOp::Sum)?),
+            "min_of" => NodeValue::Aggregate(self.aggregate_args(AggregateOp::Min)?),
+            "max_of" => NodeValue::Aggregate(self.aggregate_args(AggregateOp::Max)?),
+            _ => {
+                let mut args = vec![];
+                let mut decimals = None;
+                let mut overflow = None;
+
+                if !self.eat(&Token::RParen) {
+                    loop {
+                        if self.is_keyword_arg("decimals") {
+                            self.pos += 2;
+                            decimals = Some(self.number("decimals")?);
+                        } else if self.is_keyword_arg("overflow") {
+                            self.pos += 2;
+                            overflow = Some(match self.next() {
+                                Some(Token::Ident(policy)) if policy == "saturate" => {
+                                    OverflowPolicy::Saturate
+                                }
+                                Some(Token::Ident(policy)) if policy == "error" => {
+                                    OverflowPolicy::Error
+                                }
+                                _ => {
+                                    self.pos -= 1;
+                                    return Err(self.expected("saturate or error"));
+                                }
+                            });
+                        } else {
+                            args.push(self.arith()?);
+                        }
+
+                        if self.eat(&Token::RParen) {
+                            break;
+                        }
+                        self.expect(Token::Comma, "',' or ')'")?;
+                    }
+                }
+
+                NodeValue::Call {
+                    name,
+                    args,
+                    decimals,
+                    overflow,
+                }
+            }
+        };
+
+        Ok(Node { value, position })
+    }
+
+    fn query_args(&mut self) -> Result<QueryExpr, DslError> {
+        let position = self.position();
+        let (name, query) =
+            match self.peek().cloned() {
+                Some(Token::Str(name)) => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(Token::Json(_)) => (name, self.query_request()?),
+                        _ => {
+                            let query = self.queries.get(&name).cloned().ok_or(
+                                DslError::UndefinedQuery {
+                                    name: name.clone(),
+                                    position,
+                                },
+                            )?;
+                            (name, query)
+                        }
+                    }
+                }
+                _ => ("".to_string(), self.query_request()?),
+            };
+
+        self.expect(Token::Comma, "','")?;
+        let selector = self.selector()?;
+
+        let mut default = None;
+        let mut decimals = None;
+        let mut snapshot = None;
+        while self.eat(&Token::Comma) {
+            if self.is_keyword_arg("default") {
+                self.pos += 2;
+                default = Some(self.string("default value")?);
+            } else if self.is_keyword_arg("decimals") {
+                self.pos += 2;
+                decimals = Some(self.number("decimals")?);
+            } else if self.is_keyword_arg("snapshot") {
+                self.pos += 2;
+                snapshot = Some(match self.next() {
+                    Some(Token::Ident(value)) if value == "true" => true,
+                    Some(Token::Ident(value)) if value == "false" => false,
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.expected("true or false"));
+                    }
+                });
+            } else {
+                return Err(self.expected("default, decimals or snapshot"));
+            }
+        }
+        self.expect(Token::RParen, "')'")?;
+
+        Ok(QueryExpr {
+            selector,
+            query,
+            name,
+            default,
+            decimals,
+            snapshot,
+        })
+    }
+
+    fn aggregate_args(&mut self, op: AggregateOp) -> Result<ArrayAggregate, DslError> {
+        let array = self.array()?;
+
+        let mut selector = None;
+        let mut condition = None;
+        while self.eat(&Token::Comma) {
+            if self.is_keyword_arg("select") {
+                self.pos += 2;
+                selector = Some(self.selector()?);
+            } else if self.is_keyword_arg("where") {
+                self.pos += 2;
+                condition = Some(Box::new(self.cond()?));
+            } else {
+                return Err(self.expected("select or where"));
+            }
+        }
+        self.expect(Token::RParen, "')'")?;
+
+        Ok(ArrayAggregate {
+            array,
+            op,
+            selector,
+            condition,
+        })
+    }
+}
+
+fn binary(op: NumExprOp, left: Node, right: Node) -> Node {
+    Node {
+        position: left.position,
+        value: NodeValue::Binary {
+            op,
+            overflow: None,
+            left: Box::new(left),
+            right: Box::new(right),
+        },
+    }
+}
+
+fn expected(expected: &str, node: &Node) -> DslError {
+    DslError::Expected {
+        expected: expected.to_string(),
+        position: node.position,
+    }
+}
+
+fn into_query(node: Node) -> Result<QueryExpr, DslError> {
+    match node.value {
+        NodeValue::Query(expr) => Ok(expr),
+        _ => Err(expected("query", &node)),
+    }
+}
+
+fn into_string_literal(node: Node) -> Result<String, DslError> {
+    match node.value {
+        NodeValue::Str(value) => Ok(value),
+        _ => Err(expected("string literal", &node)),
+    }
+}
+
+fn into_string_value(node: Node) -> Result<Value<String>, DslError> {
+    match node.value {
+        NodeValue::Str(value) => Ok(Value::Simple(value)),
+        NodeValue::Query(expr) => Ok(Value::Query(expr)),
+        NodeValue::Env(name) if name == "chain_id" => Ok(Value::Env(StringEnvValue::ChainId)),
+        NodeValue::Element(selector) => Ok(Value::Element(selector)),
+        _ => Err(expected("string value", &node)),
+    }
+}
+
+fn into_literal<T: Numeric>(node: Node) -> Result<T, DslError> {
+    match &node.value {
+        NodeValue::Number(literal) => {
+            T::parse_literal(literal).ok_or_else(|| DslError::InvalidLiteral {
+                kind: T::KIND.to_string(),
+                literal: literal.clone(),
+                position: node.position,
+            })
+        }
+        _ => Err(expected(&format!("{} literal", T::KIND), &node)),
+    }
+}
+
+fn calendar_field(ident: &str) -> Option<CalendarField> {
+    match ident {
+        "block.day_of_week" => Some(CalendarField::DayOfWeek),
+        "block.hour_of_day" => Some(CalendarField::HourOfDay),
+        "block.day_of_month" => Some(CalendarField::DayOfMonth),
+        _ => None,
+    }
+}
+
+fn into_num<T: Numeric>(node: Node) -> Result<NumValue<T, NumExprOp, T::FnOp>, DslError> {
+    let position = node.position;
+    match node.value {
+        NodeValue::Number(_) => Ok(NumValue::Simple(into_literal(node)?)),
+        NodeValue::Query(expr) => Ok(NumValue::Query(expr)),
+        NodeValue::Element(selector) => Ok(NumValue::Element(selector)),
+        NodeValue::Aggregate(expr) => Ok(NumValue::Aggregate(expr)),
+        NodeValue::Env(name) if name == "block.time" => Ok(NumValue::Env(NumEnvValue::Time)),
+        NodeValue::Env(name) if name == "block.height" => {
+            Ok(NumValue::Env(NumEnvValue::BlockHeight))
+        }
+        NodeValue::Binary {
+            op,
+            overflow,
+            left,
+            right,
+        } => Ok(NumValue::Expr(NumExprValue {
+            left: Box::new(into_num(*left)?),
+            op,
+            right: Box::new(into_num(*right)?),
+            overflow,
+        })),
+        NodeValue::Call {
+            name,
+            args,
+            decimals,
+            overflow,
+        } => into_call(name, args, decimals, overflow, position),
+        _ => Err(DslError::Expected {
+            expected: format!("{} value", T::KIND),
+            position,
+        }),
+    }
+}
+
+fn into_call<T: Numeric>(
+    name: String,
+    args: Vec<Node>,
+    decimals: Option<u32>,
+    overflow: Option<OverflowPolicy>,
+    position: usize,
+) -> Result<NumValue<T, NumExprOp, T::FnOp>, DslError> {
+    let invalid = |name: String| DslError::InvalidArguments { name, position };
+    let unknown = |name: String| DslError::UnknownFunction { name, position };
+
+    let op = match name.as_str() {
+        "add" => Some(NumExprOp::Add),
+        "sub" => Some(NumExprOp::Sub),
+        "mul" => Some(NumExprOp::Mul),
+        "div" => Some(NumExprOp::Div),
+        "mod" => Some(NumExprOp::Mod),
+        "pow" => Some(NumExprOp::Pow),
+        "min" => Some(NumExprOp::Min),
+        "max" => Some(NumExprOp::Max),
+        "pct_change" => Some(NumExprOp::PctChange),
+        _ => None,
+    };
+    if let Some(op) = op {
+        let [left, right] = match (decimals, <[Node; 2]>::try_from(args)) {
+            (None, Ok(args)) => args,
+            _ => return Err(invalid(name)),
+        };
+        return Ok(NumValue::Expr(NumExprValue {
+            left: Box::new(into_num(left)?),
+            op,
+            right: Box::new(into_num(right)?),
+            overflow,
+        }));
+    }
+
+    if overflow.is_some() {
+        return Err(invalid(name));
+    }
+
+    if let Some(kind) = name.strip_prefix("from_") {
+        let [value] = <[Node; 1]>::try_from(args).map_err(|_| invalid(name.clone()))?;
+        let value = match kind {
+            "uint" => CastValue::Uint(Box::new(into_num(value)?)),
+            "int" => CastValue::Int(Box::new(into_num(value)?)),
+            "decimal" => CastValue::Decimal(Box::new(into_num(value)?)),
+            _ => return Err(unknown(name)),
+        };
+        return Ok(NumValue::Cast(NumCastValue { value, decimals }));
+    }
+
+    if decimals.is_some() {
+        return Err(invalid(name));
+    }
+
+    match (name.as_str(), args.len()) {
+        ("clamp", 3) => {
+            let [value, min, max] = <[Node; 3]>::try_from(args).map_err(|_| invalid(name))?;
+            Ok(NumValue::Clamp(NumClampValue {
+                value: Box::new(into_num(value)?),
+                min: Box::new(into_num(min)?),
+                max: Box::new(into_num(max)?),
+            }))
+        }
+        ("len", 1) => {
+            let [value] = <[Node; 1]>::try_from(args).map_err(|_| invalid(name))?;
+            Ok(NumValue::StringLen(into_string_value(value)?))
+        }
+        ("round", 2) => {
+            let [value, places] = <[Node; 2]>::try_from(args).map_err(|_| invalid(name))?;
+            let places = match places.value {
+                NodeValue::Number(literal) => literal.parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| invalid("round".to_string()))?;
+            let op =
+                T::parse_fn("round", Some(places)).ok_or_else(|| unknown("round".to_string()))?;
+            Ok(NumValue::Fn(NumFnValue {
+                op,
+                right: Box::new(into_num(value)?),
+            }))
+        }
+        ("clamp" | "len" | "round", _) => Err(invalid(name)),
+        (_, 1) => {
+            let op = T::parse_fn(&name, None).ok_or_else(|| unknown(name.clone()))?;
+            let [value] = <[Node; 1]>::try_from(args).map_err(|_| invalid(name))?;
+            Ok(NumValue::Fn(NumFnValue {
+                op,
+                right: Box::new(into_num(value)?),
+            }))
+        }
+        _ => match T::parse_fn(&name, None) {
+            Some(_) => Err(invalid(name)),
+            None => Err(unknown(name)),
+        },
+    }
+}
@@ -0,0 +1,434 @@
+use crate::controller::condition::{
+    AggregateOp, ArrayAggregate, ArrayValue, CalendarField, CastValue, Condition, Expr, JobAnchor,
+    ListOp, ListValue, NumEnvValue, NumExprOp, NumOp, NumValue, OverflowPolicy, QueryExpr,
+    QuerySource, StatusOp, StringOp, Value,
+};
+use crate::controller::dsl::lexer::scan_selector;
+use crate::controller::dsl::Numeric;
+use crate::controller::job::JobStatus;
+use cosmwasm_std::{Decimal256, Uint256};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn print_condition(cond: &Condition) -> String {
+    let mut printer = Printer {
+        collecting: true,
+        ..Printer::default()
+    };
+
+    // the first pass only collects named queries
+    printer.cond(cond);
+    printer.collecting = false;
+    let body = printer.cond(cond);
+
+    let mut out = String::new();
+    for (name, query) in &printer.queries {
+        if !printer.conflicts.contains(name) {
+            out.push_str(&format!("let {} = {};\n", quote(name), json(query)));
+        }
+    }
+    out.push_str(&body);
+    out
+}
+
+#[derive(Default)]
+struct Printer {
+    collecting: bool,
+    queries: BTreeMap<String, QuerySource>,
+    // names used for different requests are printed inline instead of as definitions
+    conflicts: BTreeSet<String>,
+}
+
+impl Printer {
+    fn cond(&mut self, cond: &Condition) -> String {
+        match cond {
+            Condition::And(conds) if conds.len() > 1 => {
+                let conds: Vec<String> = conds
+                    .iter()
+                    .map(|cond| match is_infix(cond) {
+                        true => format!("({})", self.cond(cond)),
+                        false => self.cond(cond),
+                    })
+                    .collect();
+                conds.join(" && ")
+            }
+            Condition::Or(conds) if conds.len() > 1 => {
+                let conds: Vec<String> = conds
+                    .iter()
+                    .map(|cond| match **cond {
+                        Condition::Or(_) if is_infix(cond) => format!("({})", self.cond(cond)),
+                        _ => self.cond(cond),
+                    })
+                    .collect();
+                conds.join(" || ")
+            }
+            Condition::And(conds) => format!("and({})", self.conds(conds)),
+            Condition::Or(conds) => format!("or({})", self.conds(conds)),
+            Condition::Not(cond) => match is_infix(cond) {
+                true => format!("!({})", self.cond(cond)),
+                false => format!("!{}", self.cond(cond)),
+            },
+            Condition::Expr(expr) => self.expr(expr),
+            Condition::Any(cond) => format!(
+                "any({}, {})",
+                self.array(&cond.array),
+                self.cond(&cond.condition)
+            ),
+            Condition::All(cond) => format!(
+                "all({}, {})",
+                self.array(&cond.array),
+                self.cond(&cond.condition)
+            ),
+        }
+    }
+
+    fn conds(&mut self, conds: &[Box<Condition>]) -> String {
+        let conds: Vec<String> = conds.iter().map(|cond| self.cond(cond)).collect();
+        conds.join(", ")
+    }
+
+    fn expr(&mut self, expr: &Expr) -> String {
+        match expr {
+            Expr::String(expr) => format!(
+                "{} as string {} {}",
+                self.value(&expr.left),
+                string_op(&expr.op),
+                self.value(&expr.right)
+            ),
+            Expr::Uint(expr) => self.num_expr::<Uint256>(&expr.left, &expr.op, &expr.right),
+            Expr::Int(expr) => self.num_expr::<i128>(&expr.left, &expr.op, &expr.right),
+            Expr::Decimal(expr) => self.num_expr::<Decimal256>(&expr.left, &expr.op, &expr.right),
+            Expr::Timestamp(expr) => format!(
+                "block.time{} {} {}",
+                anchor(&expr.anchor),
+                num_op(&expr.op),
+                expr.comparator
+            ),
+            Expr::BlockHeight(expr) => format!(
+                "block.height{} {} {}",
+                anchor(&expr.anchor),
+                num_op(&expr.op),
+                expr.comparator
+            ),
+            Expr::Calendar(expr) => format!(
+                "{} {} {}",
+                calendar_field(&expr.field),
+                num_op(&expr.op),
+                expr.comparator
+            ),
+            Expr::Period(expr) => format!(
+                "every({}, since = {}, within = {})",
+                expr.period,
+                anchor_name(&expr.anchor),
+                expr.tolerance
+            ),
+            Expr::JobStatus(expr) => format!(
+                "job({}) is {}{}",
+                expr.job_id,
+                match expr.op {
+                    StatusOp::Eq => "",
+                    StatusOp::Neq => "not ",
+                },
+                job_status(&expr.status)
+            ),
+            Expr::Bool(expr) => format!("{} as bool", self.query(expr)),
+            Expr::Exists(expr) => format!("exists({})", self.query(expr)),
+            Expr::IsNull(expr) => format!("is_null({})", self.query(expr)),
+            Expr::StringList(expr) => format!(
+                "{} as string {} {}",
+                self.value(&expr.left),
+                list_op(&expr.op),
+                self.list(&expr.right, |value| quote(value))
+            ),
+            Expr::UintList(expr) => format!(
+                "{} as uint {} {}",
+                self.num(&expr.left),
+                list_op(&expr.op),
+                self.list(&expr.right, Uint256::print_literal)
+            ),
+            Expr::IntList(expr) => format!(
+                "{} as int {} {}",
+                self.num(&expr.left),
+                list_op(&expr.op),
+                self.list(&expr.right, i128::print_literal)
+            ),
+            Expr::DecimalList(expr) => format!(
+                "{} as decimal {} {}",
+                self.num(&expr.left),
+                list_op(&expr.op),
+                self.list(&expr.right, Decimal256::print_literal)
+            ),
+        }
+    }
+
+    fn num_expr<T: Numeric>(
+        &mut self,
+        left: &NumValue<T, NumExprOp, T::FnOp>,
+        op: &NumOp,
+        right: &NumValue<T, NumExprOp, T::FnOp>,
+    ) -> String {
+        format!(
+            "{} as {} {} {}",
+            self.num(left),
+            T::KIND,
+            num_op(op),
+            self.num(right)
+        )
+    }
+
+    fn value(&mut self, value: &Value<String>) -> String {
+        match value {
+            Value::Simple(value) => quote(value),
+            Value::Query(expr) => self.query(expr),
+            Value::Env(_) => "chain_id".to_string(),
+            Value::Element(selector) => format!("elem({})", selector_text(selector)),
+        }
+    }
+
+    fn list<T>(&mut self, value: &ListValue<T>, literal: fn(&T) -> String) -> String {
+        match value {
+            ListValue::Simple(values) => {
+                let values: Vec<String> = values.iter().map(literal).collect();
+                format!("[{}]", values.join(", "))
+            }
+            ListValue::Query(expr) => self.query(expr),
+        }
+    }
+
+    fn num<T: Numeric>(&mut self, value: &NumValue<T, NumExprOp, T::FnOp>) -> String {
+        match value {
+            NumValue::Simple(value) => value.print_literal(),
+            NumValue::Expr(expr) => {
+                let left = self.num(&expr.left);
+                let right = self.num(&expr.right);
+                match (infix_op(&expr.op), &expr.overflow) {
+                    (Some(symbol), None) => format!("({} {} {})", left, symbol, right),
+                    (_, overflow) => format!(
+                        "{}({}, {}{})",
+                        expr_fn(&expr.op),
+                        left,
+                        right,
+                        match overflow {
+                            None => "",
+                            Some(OverflowPolicy::Saturate) => ", overflow = saturate",
+                            Some(OverflowPolicy::Error) => ", overflow = error",
+                        }
+                    ),
+                }
+            }
+            NumValue::Query(expr) => self.query(expr),
+            NumValue::Fn(expr) => {
+                let value = self.num(&expr.right);
+                match T::print_fn(&expr.op) {
+                    (name, Some(places)) => format!("{}({}, {})", name, value, places),
+                    (name, None) => format!("{}({})", name, value),
+                }
+            }
+            NumValue::Clamp(expr) => format!(
+                "clamp({}, {}, {})",
+                self.num(&expr.value),
+                self.num(&expr.min),
+                self.num(&expr.max)
+            ),
+            NumValue::Cast(expr) => {
+                let (name, value) = match &expr.value {
+                    CastValue::Uint(value) => ("from_uint", self.num(value)),
+                    CastValue::Int(value) => ("from_int", self.num(value)),
+                    CastValue::Decimal(value) => ("from_decimal", self.num(value)),
+                };
+                match expr.decimals {
+                    Some(decimals) => format!("{}({}, decimals = {})", name, value, decimals),
+                    None => format!("{}({})", name, value),
+                }
+            }
+            NumValue::Env(NumEnvValue::Time) => "block.time".to_string(),
+            NumValue::Env(NumEnvValue::BlockHeight) => "block.height".to_string(),
+            NumValue::StringLen(value) => format!("len({})", self.value(value)),
+            NumValue::Element(selector) => format!("elem({})", selector_text(selector)),
+            NumValue::Aggregate(expr) => self.aggregate(expr),
+        }
+    }
+
+    fn aggregate(&mut self, expr: &ArrayAggregate) -> String {
+        let name = match expr.op {
+            AggregateOp::Count => "count",
+            AggregateOp::Sum => "sum",
+            AggregateOp::Min => "min_of",
+            AggregateOp::Max => "max_of",
+        };
+
+        let mut out = format!("{}({}", name, self.array(&expr.array));
+        if let Some(selector) = &expr.selector {
+            out.push_str(&format!(", select = {}", selector_text(selector)));
+        }
+        if let Some(cond) = &expr.condition {
+            out.push_str(&format!(", where = {}", self.cond(cond)));
+        }
+        out.push(')');
+        out
+    }
+
+    fn array(&mut self, value: &ArrayValue) -> String {
+        match value {
+            ArrayValue::Query(expr) => self.query(expr),
+            ArrayValue::Element(selector) => format!("elem({})", selector_text(selector)),
+        }
+    }
+
+    fn query(&mut self, expr: &QueryExpr) -> String {
+        let request = match expr.name.as_str() {
+            "" => json(&expr.query),
+            name if self.collecting => {
+                match self.queries.get(name) {
+                    Some(query) if *query != expr.query => {
+                        self.conflicts.insert(name.to_string());
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.queries.insert(name.to_string(), expr.query.clone());
+                    }
+                }
+                quote(name)
+            }
+            name if self.conflicts.contains(name) => {
+                format!("{} {}", quote(name), json(&expr.query))
+            }
+            name => quote(name),
+        };
+
+        let mut out = format!("query({}, {}", request, selector_text(&expr.selector));
+        if let Some(default) = &expr.default {
+            out.push_str(&format!(", default = {}", quote(default)));
+        }
+        if let Some(decimals) = expr.decimals {
+            out.push_str(&format!(", decimals = {}", decimals));
+        }
+        if let Some(snapshot) = expr.snapshot {
+            out.push_str(&format!(", snapshot = {}", snapshot));
+        }
+        out.push(')');
+        out
+    }
+}
+
+// and/or with at least two children print as `a && b` and may need parentheses
+fn is_infix(cond: &Condition) -> bool {
+    matches!(cond, Condition::And(conds) | Condition::Or(conds) if conds.len() > 1)
+}
+
+fn anchor(anchor: &Option<JobAnchor>) -> &'static str {
+    match anchor {
+        None => "",
+        Some(JobAnchor::Creation) => " since creation",
+        Some(JobAnchor::LastUpdate) => " since last_update",
+    }
+}
+
+fn anchor_name(anchor: &JobAnchor) -> &'static str {
+    match anchor {
+        JobAnchor::Creation => "creation",
+        JobAnchor::LastUpdate => "last_update",
+    }
+}
+
+fn job_status(status: &JobStatus) -> &'static str {
+    match status {
+        JobStatus::Pending => "pending",
+        JobStatus::Executed => "executed",
+        JobStatus::Failed => "failed",
+        JobStatus::Cancelled => "cancelled",
+        JobStatus::Expired => "expired",
+    }
+}
+
+fn calendar_field(field: &CalendarField) -> &'static str {
+    match field {
+        CalendarField::DayOfWeek => "block.day_of_week",
+        CalendarField::HourOfDay => "block.hour_of_day",
+        CalendarField::DayOfMonth => "block.day_of_month",
+    }
+}
+
+fn num_op(op: &NumOp) -> &'static str {
+    match op {
+        NumOp::Eq => "==",
+        NumOp::Neq => "!=",
+        NumOp::Lt => "<",
+        NumOp::Gt => ">",
+        NumOp::Gte => ">=",
+        NumOp::Lte => "<=",
+    }
+}
+
+fn string_op(op: &StringOp) -> &'static str {
+    match op {
+        StringOp::StartsWith => "starts_with",
+        StringOp::EndsWith => "ends_with",
+        StringOp::Contains => "contains",
+        StringOp::Eq => "==",
+        StringOp::Neq => "!=",
+        StringOp::EqIgnoreCase => "eq_ignore_case",
+        StringOp::NeqIgnoreCase => "neq_ignore_case",
+    }
+}
+
+fn list_op(op: &ListOp) -> &'static str {
+    match op {
+        ListOp::In => "in",
+        ListOp::NotIn => "not in",
+    }
+}
+
+fn infix_op(op: &NumExprOp) -> Option<&'static str> {
+    match op {
+        NumExprOp::Add => Some("+"),
+        NumExprOp::Sub => Some("-"),
+        NumExprOp::Mul => Some("*"),
+        NumExprOp::Div => Some("/"),
+        NumExprOp::Mod => Some("%"),
+        NumExprOp::Pow => Some("^"),
+        NumExprOp::Min | NumExprOp::Max | NumExprOp::PctChange => None,
+    }
+}
+
+fn expr_fn(op: &NumExprOp) -> &'static str {
+    match op {
+        NumExprOp::Add => "add",
+        NumExprOp::Sub => "sub",
+        NumExprOp::Mul => "mul",
+        NumExprOp::Div => "div",
+        NumExprOp::Mod => "mod",
+        NumExprOp::Pow => "pow",
+        NumExprOp::Min => "min",
+        NumExprOp::Max => "max",
+        NumExprOp::PctChange => "pct_change",
+    }
+}
+
+// selectors are printed bare unless they would not lex back as a single selector token
+fn selector_text(selector: &str) -> String {
+    let chars: Vec<char> = selector.chars().collect();
+    match selector.starts_with('$') && scan_selector(&chars, 0) == (chars.len(), true) {
+        true => selector.to_string(),
+        false => quote(selector),
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json(query: &QuerySource) -> String {
+    serde_json_wasm::to_string(query).unwrap_or_default()
+}
@@ -1,6 +1,8 @@
-use crate::controller::condition::Condition;
+use crate::controller::condition::{Condition, QuerySource};
+use crate::controller::path::ParsedSelector;
+use crate::controller::template::JobTemplate;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, CosmosMsg, Uint128, Uint64};
+use cosmwasm_std::{Addr, Binary, CosmosMsg, Uint128, Uint64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum_macros::Display;
@@ -10,11 +12,97 @@ pub struct Job {
     pub id: Uint64,
     pub owner: Addr,
     pub last_update_time: Uint64,
+    pub last_update_height: Option<Uint64>,
+    pub creation_time: Option<Uint64>,
+    pub creation_height: Option<Uint64>,
     pub name: String,
     pub status: JobStatus,
     pub condition: Condition,
     pub msgs: Vec<CosmosMsg>,
     pub reward: Uint128,
+    // set for jobs created from a template
+    pub template: Option<JobTemplate>,
+    // responses of the snapshot queries of the condition
+    pub snapshots: Option<Vec<QuerySnapshot>>,
+    // selectors of the condition, parsed when it is created or updated
+    pub selectors: Option<Vec<ParsedSelector>>,
+    // key keepers sign the data read by the condition with
+    pub signer: Option<DataSigner>,
+    // set for recurring jobs, with what is left of the budget
+    pub recurrence: Option<Recurrence>,
+    pub executions: Option<Uint64>,
+    // seconds
+    pub last_execution_time: Option<Uint64>,
+    pub expiry: Option<Expiry>,
+    // native denom of the reward and fees, None for jobs created before reward denoms
+    pub reward_denom: Option<String>,
+}
+
+// denom of the jobs created before reward denoms
+pub const LEGACY_REWARD_DENOM: &str = "uluna";
+
+impl Job {
+    // held by the controller for the rewards of the job
+    pub fn funds(&self) -> Uint128 {
+        self.recurrence
+            .as_ref()
+            .map_or(self.reward, |recurrence| recurrence.budget)
+    }
+
+    pub fn denom(&self) -> &str {
+        self.reward_denom.as_deref().unwrap_or(LEGACY_REWARD_DENOM)
+    }
+}
+
+// keeps the job pending after each execution, paying `reward` out of the budget every time,
+// until the budget or the execution count runs out
+#[cw_serde]
+pub struct Recurrence {
+    pub budget: Uint128,
+    pub max_executions: Option<Uint64>,
+    // seconds from one execution to the next
+    pub min_interval: Option<Uint64>,
+}
+
+#[cw_serde]
+pub struct QuerySnapshot {
+    pub name: String,
+    pub query: QuerySource,
+    pub response: String,
+}
+
+// from then on the job cannot be executed and anyone can clean it up
+#[cw_serde]
+pub enum Expiry {
+    // seconds
+    Time(Uint64),
+    BlockHeight(Uint64),
+}
+
+// trusted source of off-chain data, see `TypedQuery::SignedData`
+#[cw_serde]
+pub struct DataSigner {
+    pub algorithm: SignatureAlgorithm,
+    // compressed or uncompressed for secp256k1
+    pub public_key: Binary,
+    // seconds a payload is accepted for after its timestamp
+    pub max_age: Uint64,
+}
+
+#[cw_serde]
+pub enum SignatureAlgorithm {
+    // signs the sha256 hash of the payload
+    Secp256k1,
+    // signs the payload itself
+    Ed25519,
+}
+
+// payload is JSON text `{"job_id":"1","timestamp":"1700000000","data":{...}}`, with the timestamp
+// in seconds and `data` read by the condition, the timestamp must be after the job's last execution
+#[cw_serde]
+pub struct SignedData {
+    pub payload: String,
+    pub signature: Binary,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Display)]
@@ -23,6 +111,7 @@ pub enum JobStatus {
     Executed,
     Failed,
     Cancelled,
+    Expired,
 }
 
 #[cw_serde]
@@ -31,6 +120,11 @@ pub struct CreateJobMsg {
     pub condition: Condition,
     pub msgs: Vec<String>,
     pub reward: Uint128,
+    pub signer: Option<DataSigner>,
+    pub recurrence: Option<Recurrence>,
+    pub expiry: Option<Expiry>,
+    // must be accepted by the config, defaults to the first accepted denom
+    pub reward_denom: Option<String>,
 }
 
 #[cw_serde]
@@ -38,17 +132,26 @@ pub struct DeleteJobMsg {
     pub id: Uint64,
 }
 
+// moves an expired job to the finished jobs, callable by anyone for a tip
+#[cw_serde]
+pub struct CleanupJobMsg {
+    pub id: Uint64,
+}
+
 #[cw_serde]
 pub struct UpdateJobMsg {
     pub id: Uint64,
     pub name: Option<String>,
     pub condition: Option<Condition>,
+    // tops up the budget of recurring jobs
     pub added_reward: Option<Uint128>,
 }
 
 #[cw_serde]
 pub struct ExecuteJobMsg {
     pub id: Uint64,
+    // required for jobs with a signer
+    pub signed_data: Option<SignedData>,
 }
 
 #[cw_serde]
@@ -76,12 +179,12 @@ pub struct JobIndex {
 
 impl QueryJobsMsg {
     pub fn valid_query(&self) -> bool {
-        return (self.ids.is_some() as u8
+        (self.ids.is_some() as u8
             + (self.owner.is_some()
                 || self.name.is_some()
                 || self.job_status.is_some()
                 || self.condition_status.is_some()) as u8)
-            <= 1;
+            <= 1
     }
 }
 
@@ -90,6 +193,11 @@ pub struct QueryResolveJobConditionMsg {
     pub id: Uint64,
 }
 
+#[cw_serde]
+pub struct QueryExplainJobConditionMsg {
+    pub id: Uint64,
+}
+
 #[cw_serde]
 pub struct JobResponse {
     pub job: Job,
@@ -1,4 +1,9 @@
 pub mod account;
 pub mod condition;
+#[allow(clippy::module_inception)]
 pub mod controller;
+pub mod dsl;
 pub mod job;
+pub mod path;
+pub mod storage;
+pub mod template;
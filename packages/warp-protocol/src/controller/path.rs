@@ -0,0 +1,185 @@
+use cosmwasm_schema::cw_serde;
+use thiserror::Error;
+
+// jsonpath-like selector grammar, positions are 0-based character offsets
+// - root - `$` (optional, an empty selector selects the root as well)
+// - legacy fields without a leading dot - `field`, `$field`
+// - fields - `$.field`, `$.field1.field2`
+// - quoted fields - `$["field.with.dots"]`, `$['field']`
+// - array entries - `$.field[0]`, counted from the end when negative - `$.field[-1]`
+// - chained entries - `$.field[0][1].field2`
+// - length of an array, string or object - `$.field.length()`, must come last
+#[cw_serde]
+#[derive(Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(i64),
+    Length,
+}
+
+// a selector of a stored condition with its segments, so that it is not parsed again
+#[cw_serde]
+pub struct ParsedSelector {
+    pub selector: String,
+    pub path: Vec<PathSegment>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PathError {
+    #[error("Unexpected character '{character}' at position {position}")]
+    UnexpectedCharacter { character: char, position: usize },
+
+    #[error("Unexpected end of selector at position {position}")]
+    UnexpectedEnd { position: usize },
+
+    #[error("Empty field name at position {position}")]
+    EmptyKey { position: usize },
+
+    #[error("Invalid array index at position {position}")]
+    InvalidIndex { position: usize },
+
+    #[error("length() must be the last segment, found more at position {position}")]
+    LengthNotLast { position: usize },
+}
+
+impl PathError {
+    pub fn position(&self) -> usize {
+        match self {
+            PathError::UnexpectedCharacter { position, .. }
+            | PathError::UnexpectedEnd { position }
+            | PathError::EmptyKey { position }
+            | PathError::InvalidIndex { position }
+            | PathError::LengthNotLast { position } => *position,
+        }
+    }
+}
+
+const LENGTH: &str = "length()";
+
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>, PathError> {
+    let chars: Vec<char> = path.chars().collect();
+    let mut segments = vec![];
+    let mut idx = 0;
+
+    if chars.first() == Some(&'$') {
+        idx += 1;
+    }
+    if idx < chars.len() && !matches!(chars[idx], '.' | '[') {
+        // legacy selectors without the root or its dot, e.g. `field1.field2` or `$field1.field2`
+        let (key, next) = read_key(&chars, idx)?;
+        segments.push(key);
+        idx = next;
+    }
+
+    while idx < chars.len() {
+        if segments.last() == Some(&PathSegment::Length) {
+            return Err(PathError::LengthNotLast { position: idx });
+        }
+
+        match chars[idx] {
+            '.' => {
+                let (key, next) = read_key(&chars, idx + 1)?;
+                segments.push(key);
+                idx = next;
+            }
+            '[' => {
+                let (segment, next) = read_bracket(&chars, idx + 1)?;
+                segments.push(segment);
+                idx = next;
+            }
+            c => {
+                return Err(PathError::UnexpectedCharacter {
+                    character: c,
+                    position: idx,
+                })
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn read_key(chars: &[char], from: usize) -> Result<(PathSegment, usize), PathError> {
+    let mut idx = from;
+    let mut key = String::new();
+    while idx < chars.len() && !matches!(chars[idx], '.' | '[' | ']') {
+        key.push(chars[idx]);
+        idx += 1;
+    }
+
+    if key.is_empty() {
+        return Err(PathError::EmptyKey { position: from });
+    }
+
+    if key == LENGTH {
+        return Ok((PathSegment::Length, idx));
+    }
+
+    Ok((PathSegment::Key(key), idx))
+}
+
+fn read_bracket(chars: &[char], from: usize) -> Result<(PathSegment, usize), PathError> {
+    match chars.get(from) {
+        None => Err(PathError::UnexpectedEnd { position: from }),
+        Some('"') | Some('\'') => read_quoted_key(chars, from),
+        Some(_) => read_index(chars, from),
+    }
+}
+
+fn read_quoted_key(chars: &[char], from: usize) -> Result<(PathSegment, usize), PathError> {
+    let quote = chars[from];
+    let mut idx = from + 1;
+    let mut key = String::new();
+
+    loop {
+        match chars.get(idx) {
+            None => return Err(PathError::UnexpectedEnd { position: idx }),
+            Some('\\') => {
+                let escaped = chars
+                    .get(idx + 1)
+                    .ok_or(PathError::UnexpectedEnd { position: idx + 1 })?;
+                key.push(*escaped);
+                idx += 2;
+            }
+            Some(c) if *c == quote => {
+                idx += 1;
+                break;
+            }
+            Some(c) => {
+                key.push(*c);
+                idx += 1;
+            }
+        }
+    }
+
+    match chars.get(idx) {
+        None => Err(PathError::UnexpectedEnd { position: idx }),
+        Some(']') => Ok((PathSegment::Key(key), idx + 1)),
+        Some(c) => Err(PathError::UnexpectedCharacter {
+            character: *c,
+            position: idx,
+        }),
+    }
+}
+
+fn read_index(chars: &[char], from: usize) -> Result<(PathSegment, usize), PathError> {
+    let mut idx = from;
+    let mut curr = String::new();
+
+    loop {
+        match chars.get(idx) {
+            None => return Err(PathError::UnexpectedEnd { position: idx }),
+            Some(']') => break,
+            Some(c) => {
+                curr.push(*c);
+                idx += 1;
+            }
+        }
+    }
+
+    let index = curr
+        .parse::<i64>()
+        .map_err(|_| PathError::InvalidIndex { position: from })?;
+
+    Ok((PathSegment::Index(index), idx + 1))
+}
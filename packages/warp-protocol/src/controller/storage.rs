@@ -0,0 +1,71 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Empty, Uint128, Uint64};
+use cw_storage_plus::{Path, PrimaryKey};
+
+// location of a value another contract stores with cw-storage-plus
+#[cw_serde]
+pub enum StorageKey {
+    // `Item::new(namespace)`
+    Item {
+        namespace: String,
+    },
+    // entry of `Map::new(namespace)`, one part per element of a composite key,
+    // e.g. an entry of `Map<(&Addr, u64), _>` is an address part then a u64 part
+    Map {
+        namespace: String,
+        key: Vec<KeyPart>,
+    },
+}
+
+#[cw_serde]
+pub enum KeyPart {
+    String(String),
+    Addr(String),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(Uint64),
+    U128(Uint128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+}
+
+impl KeyPart {
+    // the part as cw-storage-plus encodes it, integers are big-endian with the sign bit flipped
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            KeyPart::String(value) | KeyPart::Addr(value) => value.as_bytes().to_vec(),
+            KeyPart::U8(value) => value.joined_key(),
+            KeyPart::U16(value) => value.joined_key(),
+            KeyPart::U32(value) => value.joined_key(),
+            KeyPart::U64(value) => value.u64().joined_key(),
+            KeyPart::U128(value) => value.u128().joined_key(),
+            KeyPart::I8(value) => value.joined_key(),
+            KeyPart::I16(value) => value.joined_key(),
+            KeyPart::I32(value) => value.joined_key(),
+            KeyPart::I64(value) => value.joined_key(),
+        }
+    }
+}
+
+impl StorageKey {
+    // raw storage key, None when a map key has no parts or a length-prefixed part is too long
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let (namespace, key) = match self {
+            StorageKey::Item { namespace } => return Some(namespace.as_bytes().to_vec()),
+            StorageKey::Map { namespace, key } => (namespace, key),
+        };
+
+        // the namespace and every part but the last are prefixed with a 2 byte length
+        let parts = key.iter().map(KeyPart::to_bytes).collect::<Vec<_>>();
+        let (_, prefixed) = parts.split_last()?;
+        if namespace.len() > 0xFFFF || prefixed.iter().any(|part| part.len() > 0xFFFF) {
+            return None;
+        }
+
+        let parts = parts.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        Some(Path::<Empty>::new(namespace.as_bytes(), &parts).to_vec())
+    }
+}
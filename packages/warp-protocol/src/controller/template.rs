@@ -0,0 +1,103 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128, Uint64};
+
+// a job with `{{name}}` placeholders in its condition and msgs, filled in on job creation
+#[cw_serde]
+pub struct Template {
+    pub id: Uint64,
+    pub owner: Addr,
+    // starts at 1, bumped on every edit
+    pub version: Uint64,
+    pub name: String,
+    // JSON encoded condition, a placeholder making up a whole JSON string of a bool var
+    // is replaced with the bare value, e.g. `"{{flag}}"` becomes `true`
+    pub condition: String,
+    pub msgs: Vec<String>,
+    pub vars: Vec<TemplateVar>,
+}
+
+#[cw_serde]
+pub struct TemplateVar {
+    pub name: String,
+    pub kind: TemplateVarKind,
+}
+
+#[cw_serde]
+pub enum TemplateVarKind {
+    String,
+    Uint,
+    Int,
+    Decimal,
+    Bool,
+    Address,
+}
+
+// template a job was created from
+#[cw_serde]
+pub struct JobTemplate {
+    pub id: Uint64,
+    pub version: Uint64,
+}
+
+#[cw_serde]
+pub struct TemplateParam {
+    pub name: String,
+    pub value: String,
+}
+
+#[cw_serde]
+pub struct SubmitTemplateMsg {
+    pub name: String,
+    pub condition: String,
+    pub msgs: Vec<String>,
+    pub vars: Vec<TemplateVar>,
+}
+
+#[cw_serde]
+pub struct EditTemplateMsg {
+    pub id: Uint64,
+    pub name: Option<String>,
+    pub condition: Option<String>,
+    pub msgs: Option<Vec<String>>,
+    pub vars: Option<Vec<TemplateVar>>,
+}
+
+#[cw_serde]
+pub struct DeleteTemplateMsg {
+    pub id: Uint64,
+}
+
+#[cw_serde]
+pub struct CreateJobFromTemplateMsg {
+    pub template_id: Uint64,
+    // defaults to the latest version
+    pub template_version: Option<Uint64>,
+    pub name: String,
+    pub params: Vec<TemplateParam>,
+    pub reward: Uint128,
+    pub reward_denom: Option<String>,
+}
+
+#[cw_serde]
+pub struct QueryTemplateMsg {
+    pub id: Uint64,
+    // defaults to the latest version, older versions stay queryable after a delete
+    pub version: Option<Uint64>,
+}
+
+#[cw_serde]
+pub struct QueryTemplatesMsg {
+    pub owner: Option<Addr>,
+    pub start_after: Option<Uint64>,
+    pub limit: Option<u32>,
+}
+
+#[cw_serde]
+pub struct TemplateResponse {
+    pub template: Template,
+}
+
+#[cw_serde]
+pub struct TemplatesResponse {
+    pub templates: Vec<Template>,
+}
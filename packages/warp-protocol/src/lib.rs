@@ -1,2 +1,5 @@
 pub mod account;
 pub mod controller;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,2 @@
+mod test_dsl;
+mod test_storage;
@@ -0,0 +1,715 @@
+use crate::controller::condition::{
+    AggregateOp, ArrayAggregate, ArrayCondition, ArrayValue, BlockExpr, CalendarExpr,
+    CalendarField, CastValue, Condition, DecimalFnOp, Expr, GenExpr, IntFnOp, JobAnchor,
+    JobStatusExpr, ListExpr, ListOp, ListValue, NumCastValue, NumClampValue, NumEnvValue,
+    NumExprOp, NumExprValue, NumFnValue, NumOp, NumValue, OverflowPolicy, PeriodExpr, QueryExpr,
+    QuerySource, StatusOp, StringEnvValue, StringOp, TimeExpr, TypedQuery, Value,
+};
+use crate::controller::dsl::{parse_condition, print_condition, DslError, Numeric};
+use crate::controller::job::JobStatus;
+use cosmwasm_std::{to_binary, Decimal256, QueryRequest, Uint256, Uint64, WasmQuery};
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+fn pool_query(contract: &str) -> QuerySource {
+    QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract.to_string(),
+        msg: to_binary("pool").unwrap(),
+    })
+    .into()
+}
+
+fn query_expr(name: &str, selector: &str) -> QueryExpr {
+    QueryExpr {
+        selector: selector.to_string(),
+        query: pool_query("terra1pool"),
+        name: name.to_string(),
+        default: None,
+        decimals: None,
+        snapshot: None,
+    }
+}
+
+#[test]
+fn test_parse_condition() {
+    let text = r#"
+        let "pool" = {"wasm":{"smart":{"contract_addr":"terra1pool","msg":"InBvb2wi"}}};
+        query("pool", $.price) as decimal > 1.05 && block.time > 1700000000
+    "#;
+
+    assert_eq!(
+        parse_condition(text).unwrap(),
+        Condition::And(vec![
+            Box::new(Condition::Expr(Expr::Decimal(GenExpr {
+                left: NumValue::Query(query_expr("pool", "$.price")),
+                op: NumOp::Gt,
+                right: NumValue::Simple(Decimal256::from_str("1.05").unwrap()),
+            }))),
+            Box::new(Condition::Expr(Expr::Timestamp(TimeExpr {
+                comparator: Uint64::new(1700000000),
+                op: NumOp::Gt,
+                anchor: None,
+            }))),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_typed_query() {
+    let text = r#"
+        query({"native_balance":{"address":"terra1vlad","denom":"uluna"}}, $.amount.amount) as uint > 0
+    "#;
+
+    assert_eq!(
+        parse_condition(text).unwrap(),
+        Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Query(QueryExpr {
+                selector: "$.amount.amount".to_string(),
+                query: QuerySource::Typed(TypedQuery::NativeBalance {
+                    address: "terra1vlad".to_string(),
+                    denom: "uluna".to_string(),
+                }),
+                name: "".to_string(),
+                default: None,
+                decimals: None,
+                snapshot: None,
+            }),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Uint256::zero()),
+        }))
+    );
+}
+
+#[test]
+fn test_parse_schedule_and_job_status() {
+    assert_eq!(
+        parse_condition(
+            "block.day_of_week <= 5 && every(86400, within = 600, since = last_update)"
+        )
+        .unwrap(),
+        Condition::And(vec![
+            Box::new(Condition::Expr(Expr::Calendar(CalendarExpr {
+                field: CalendarField::DayOfWeek,
+                op: NumOp::Lte,
+                comparator: Uint64::new(5),
+            }))),
+            Box::new(Condition::Expr(Expr::Period(PeriodExpr {
+                period: Uint64::new(86400),
+                anchor: JobAnchor::LastUpdate,
+                tolerance: Uint64::new(600),
+            }))),
+        ])
+    );
+    assert_eq!(
+        parse_condition("job(42) is not pending || job(42) is executed").unwrap(),
+        Condition::Or(vec![
+            Box::new(Condition::Expr(Expr::JobStatus(JobStatusExpr {
+                job_id: Uint64::new(42),
+                op: StatusOp::Neq,
+                status: JobStatus::Pending,
+            }))),
+            Box::new(Condition::Expr(Expr::JobStatus(JobStatusExpr {
+                job_id: Uint64::new(42),
+                op: StatusOp::Eq,
+                status: JobStatus::Executed,
+            }))),
+        ])
+    );
+    assert_eq!(
+        parse_condition("every(86400, since = creation)").unwrap_err(),
+        DslError::Expected {
+            expected: "since and within".to_string(),
+            position: 29,
+        }
+    );
+}
+
+#[test]
+fn test_parse_condition_precedence() {
+    let uint = |value: u128| {
+        Box::new(NumValue::<Uint256, NumExprOp, IntFnOp>::Simple(
+            Uint256::from(value),
+        ))
+    };
+
+    // `*` binds tighter than `+`, `^` is right associative
+    assert_eq!(
+        parse_condition("1 + 2 * 3 ^ 4 ^ 5 as uint == 0").unwrap(),
+        Condition::Expr(Expr::Uint(GenExpr {
+            left: NumValue::Expr(NumExprValue {
+                left: uint(1),
+                op: NumExprOp::Add,
+                right: Box::new(NumValue::Expr(NumExprValue {
+                    left: uint(2),
+                    op: NumExprOp::Mul,
+                    right: Box::new(NumValue::Expr(NumExprValue {
+                        left: uint(3),
+                        op: NumExprOp::Pow,
+                        right: Box::new(NumValue::Expr(NumExprValue {
+                            left: uint(4),
+                            op: NumExprOp::Pow,
+                            right: uint(5),
+                            overflow: None,
+                        })),
+                        overflow: None,
+                    })),
+                    overflow: None,
+                })),
+                overflow: None,
+            }),
+            op: NumOp::Eq,
+            right: NumValue::Simple(Uint256::zero()),
+        }))
+    );
+
+    // `&&` binds tighter than `||`, parentheses group conditions and values alike
+    let height = |comparator: u64| {
+        Box::new(Condition::Expr(Expr::BlockHeight(BlockExpr {
+            comparator: Uint64::new(comparator),
+            op: NumOp::Gt,
+            anchor: Some(JobAnchor::Creation),
+        })))
+    };
+    assert_eq!(
+        parse_condition(
+            "block.height since creation > 1 || !(block.height since creation > 2 \
+             || block.height since creation > 3) && (block.time - 4) as int < 5"
+        )
+        .unwrap(),
+        Condition::Or(vec![
+            height(1),
+            Box::new(Condition::And(vec![
+                Box::new(Condition::Not(Box::new(Condition::Or(vec![
+                    height(2),
+                    height(3)
+                ])))),
+                Box::new(Condition::Expr(Expr::Int(GenExpr {
+                    left: NumValue::Expr(NumExprValue {
+                        left: Box::new(NumValue::Env(NumEnvValue::Time)),
+                        op: NumExprOp::Sub,
+                        right: Box::new(NumValue::Simple(4)),
+                        overflow: None,
+                    }),
+                    op: NumOp::Lt,
+                    right: NumValue::Simple(5),
+                }))),
+            ])),
+        ])
+    );
+}
+
+#[test]
+fn test_print_condition() {
+    let cond = Condition::And(vec![
+        Box::new(Condition::Expr(Expr::Decimal(GenExpr {
+            left: NumValue::Query(query_expr("pool", "$.price")),
+            op: NumOp::Gt,
+            right: NumValue::Simple(Decimal256::from_str("1.05").unwrap()),
+        }))),
+        Box::new(Condition::Expr(Expr::StringList(ListExpr {
+            left: Value::Env(StringEnvValue::ChainId),
+            op: ListOp::NotIn,
+            right: ListValue::Simple(vec!["a\"b".to_string()]),
+        }))),
+    ]);
+
+    assert_eq!(
+        print_condition(&cond),
+        "let \"pool\" = {\"wasm\":{\"smart\":{\"contract_addr\":\"terra1pool\",\"msg\":\"InBvb2wi\"}}};\n\
+         query(\"pool\", $.price) as decimal > 1.05 && chain_id as string not in [\"a\\\"b\"]"
+    );
+
+    // a name shared by different requests cannot be a single definition
+    let mut other = query_expr("pool", "$.price");
+    other.query = pool_query("terra1other");
+    let cond = Condition::Or(vec![
+        Box::new(Condition::Expr(Expr::Exists(query_expr("pool", "$.price")))),
+        Box::new(Condition::Expr(Expr::Exists(other))),
+    ]);
+    let text = print_condition(&cond);
+    assert!(!text.starts_with("let"));
+    assert_eq!(parse_condition(&text).unwrap(), cond);
+
+    let mut baseline = query_expr("pool", "$.price");
+    baseline.snapshot = Some(true);
+    let cond = Condition::Expr(Expr::Exists(baseline));
+    assert_eq!(
+        print_condition(&cond),
+        "let \"pool\" = {\"wasm\":{\"smart\":{\"contract_addr\":\"terra1pool\",\"msg\":\"InBvb2wi\"}}};\n\
+         exists(query(\"pool\", $.price, snapshot = true))"
+    );
+}
+
+#[test]
+fn test_parse_condition_errors() {
+    assert_eq!(
+        parse_condition("block.time > 1 &&").unwrap_err(),
+        DslError::Expected {
+            expected: "value".to_string(),
+            position: 17,
+        }
+    );
+    assert_eq!(
+        parse_condition("block.time > 1 # 2").unwrap_err(),
+        DslError::UnexpectedCharacter {
+            character: '#',
+            position: 15,
+        }
+    );
+    assert_eq!(
+        parse_condition("query(\"pool\", $.price) > 1").unwrap_err(),
+        DslError::UndefinedQuery {
+            name: "pool".to_string(),
+            position: 6,
+        }
+    );
+    assert_eq!(
+        parse_condition("query({\"bank\":{}}, $.price) as bool").unwrap_err(),
+        DslError::InvalidQuery { position: 6 }
+    );
+    assert_eq!(
+        parse_condition("block.time + 1 > 2").unwrap_err(),
+        DslError::MissingType { position: 0 }
+    );
+    assert_eq!(
+        parse_condition("1 as uint > -1").unwrap_err(),
+        DslError::InvalidLiteral {
+            kind: "uint".to_string(),
+            literal: "-1".to_string(),
+            position: 12,
+        }
+    );
+    assert_eq!(
+        parse_condition("floor(1.5) as uint > 1").unwrap_err(),
+        DslError::UnknownFunction {
+            name: "floor".to_string(),
+            position: 0,
+        }
+    );
+    assert_eq!(
+        parse_condition("min(1) as uint > 1").unwrap_err(),
+        DslError::InvalidArguments {
+            name: "min".to_string(),
+            position: 0,
+        }
+    );
+    assert_eq!(
+        parse_condition("\"abc").unwrap_err(),
+        DslError::Unterminated { position: 0 }
+    );
+    assert_eq!(
+        parse_condition(
+            "let \"a\" = {\"bank\":{\"all_balances\":{\"address\":\"x\"}}}; \
+             let \"a\" = {\"bank\":{\"all_balances\":{\"address\":\"y\"}}}; block.time > 1"
+        )
+        .unwrap_err(),
+        DslError::DuplicateQuery {
+            name: "a".to_string(),
+            position: 57,
+        }
+    );
+}
+
+// strategies are bounded by depth, each level may nest conditions, expressions and values
+
+fn arb_text() -> BoxedStrategy<String> {
+    "[a-z0-9 .$\\[\\]'\"\\\\\n]{0,8}".boxed()
+}
+
+fn arb_selector() -> BoxedStrategy<String> {
+    prop_oneof![
+        Just("$.price".to_string()),
+        Just("$.assets[0]['a b'].amount".to_string()),
+        Just("$.data.length()".to_string()),
+        Just("data.price".to_string()),
+        arb_text(),
+    ]
+    .boxed()
+}
+
+fn arb_query() -> BoxedStrategy<QueryExpr> {
+    (
+        prop_oneof![
+            Just("".to_string()),
+            Just("pool".to_string()),
+            Just("oracle".to_string()),
+            arb_text(),
+        ],
+        prop_oneof![
+            Just("terra1pool").prop_map(pool_query),
+            Just("terra1oracle").prop_map(pool_query),
+            arb_text().prop_map(|msg| QuerySource::Typed(TypedQuery::Smart {
+                contract: "terra1pool".to_string(),
+                msg,
+            })),
+            Just(QuerySource::Typed(TypedQuery::NativeBalance {
+                address: "terra1vlad".to_string(),
+                denom: "uluna".to_string(),
+            })),
+        ],
+        arb_selector(),
+        proptest::option::of(arb_text()),
+        proptest::option::of(0..18u32),
+        proptest::option::of(any::<bool>()),
+    )
+        .prop_map(
+            |(name, query, selector, default, decimals, snapshot)| QueryExpr {
+                selector,
+                query,
+                name,
+                default,
+                decimals,
+                snapshot,
+            },
+        )
+        .boxed()
+}
+
+fn arb_string_value() -> BoxedStrategy<Value<String>> {
+    prop_oneof![
+        arb_text().prop_map(Value::Simple),
+        arb_query().prop_map(Value::Query),
+        Just(Value::Env(StringEnvValue::ChainId)),
+        arb_selector().prop_map(Value::Element),
+    ]
+    .boxed()
+}
+
+fn arb_array() -> BoxedStrategy<ArrayValue> {
+    prop_oneof![
+        arb_query().prop_map(ArrayValue::Query),
+        arb_selector().prop_map(ArrayValue::Element),
+    ]
+    .boxed()
+}
+
+fn arb_num_op() -> BoxedStrategy<NumOp> {
+    prop_oneof![
+        Just(NumOp::Eq),
+        Just(NumOp::Neq),
+        Just(NumOp::Lt),
+        Just(NumOp::Gt),
+        Just(NumOp::Gte),
+        Just(NumOp::Lte),
+    ]
+    .boxed()
+}
+
+fn arb_list_op() -> BoxedStrategy<ListOp> {
+    prop_oneof![Just(ListOp::In), Just(ListOp::NotIn)].boxed()
+}
+
+fn arb_list<T: Debug + 'static>(value: BoxedStrategy<T>) -> BoxedStrategy<ListValue<T>> {
+    prop_oneof![
+        prop::collection::vec(value, 0..3).prop_map(ListValue::Simple),
+        arb_query().prop_map(ListValue::Query),
+    ]
+    .boxed()
+}
+
+trait ArbNum: Numeric + Clone + Debug + 'static
+where
+    Self::FnOp: Clone + Debug,
+{
+    fn arb_literal() -> BoxedStrategy<Self>;
+    fn arb_fn_op() -> BoxedStrategy<Self::FnOp>;
+}
+
+impl ArbNum for Uint256 {
+    fn arb_literal() -> BoxedStrategy<Self> {
+        any::<u128>().prop_map(Uint256::from).boxed()
+    }
+
+    fn arb_fn_op() -> BoxedStrategy<Self::FnOp> {
+        prop_oneof![
+            Just(IntFnOp::Abs),
+            Just(IntFnOp::Neg),
+            (0..40u32).prop_map(IntFnOp::Round),
+        ]
+        .boxed()
+    }
+}
+
+impl ArbNum for i128 {
+    fn arb_literal() -> BoxedStrategy<Self> {
+        any::<i128>().boxed()
+    }
+
+    fn arb_fn_op() -> BoxedStrategy<Self::FnOp> {
+        prop_oneof![
+            Just(IntFnOp::Abs),
+            Just(IntFnOp::Neg),
+            (0..40u32).prop_map(IntFnOp::Round),
+        ]
+        .boxed()
+    }
+}
+
+impl ArbNum for Decimal256 {
+    fn arb_literal() -> BoxedStrategy<Self> {
+        (any::<u128>(), 0..18u32)
+            .prop_map(|(atomics, places)| Decimal256::from_atomics(atomics, places).unwrap())
+            .boxed()
+    }
+
+    fn arb_fn_op() -> BoxedStrategy<Self::FnOp> {
+        prop_oneof![
+            Just(DecimalFnOp::Abs),
+            Just(DecimalFnOp::Neg),
+            Just(DecimalFnOp::Floor),
+            Just(DecimalFnOp::Sqrt),
+            Just(DecimalFnOp::Ceil),
+            (0..18u32).prop_map(DecimalFnOp::Round),
+        ]
+        .boxed()
+    }
+}
+
+fn arb_num<T: ArbNum>(depth: u32) -> BoxedStrategy<NumValue<T, NumExprOp, T::FnOp>>
+where
+    T::FnOp: Clone + Debug,
+{
+    let leaf = prop_oneof![
+        T::arb_literal().prop_map(NumValue::Simple),
+        arb_query().prop_map(NumValue::Query),
+        Just(NumValue::Env(NumEnvValue::Time)),
+        Just(NumValue::Env(NumEnvValue::BlockHeight)),
+        arb_selector().prop_map(NumValue::Element),
+        arb_string_value().prop_map(NumValue::StringLen),
+    ]
+    .boxed();
+    if depth == 0 {
+        return leaf;
+    }
+
+    let inner = arb_num::<T>(depth - 1);
+    let expr_op = prop_oneof![
+        Just(NumExprOp::Add),
+        Just(NumExprOp::Sub),
+        Just(NumExprOp::Div),
+        Just(NumExprOp::Mul),
+        Just(NumExprOp::Mod),
+        Just(NumExprOp::Min),
+        Just(NumExprOp::Max),
+        Just(NumExprOp::Pow),
+        Just(NumExprOp::PctChange),
+    ];
+    let overflow = proptest::option::of(prop_oneof![
+        Just(OverflowPolicy::Saturate),
+        Just(OverflowPolicy::Error),
+    ]);
+    let cast = prop_oneof![
+        arb_num::<Uint256>(depth - 1).prop_map(|value| CastValue::Uint(Box::new(value))),
+        arb_num::<i128>(depth - 1).prop_map(|value| CastValue::Int(Box::new(value))),
+        arb_num::<Decimal256>(depth - 1).prop_map(|value| CastValue::Decimal(Box::new(value))),
+    ];
+
+    Union::new(vec![
+        leaf,
+        (inner.clone(), expr_op, inner.clone(), overflow)
+            .prop_map(|(left, op, right, overflow)| {
+                NumValue::Expr(NumExprValue {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                    overflow,
+                })
+            })
+            .boxed(),
+        (T::arb_fn_op(), inner.clone())
+            .prop_map(|(op, right)| {
+                NumValue::Fn(NumFnValue {
+                    op,
+                    right: Box::new(right),
+                })
+            })
+            .boxed(),
+        (inner.clone(), inner.clone(), inner)
+            .prop_map(|(value, min, max)| {
+                NumValue::Clamp(NumClampValue {
+                    value: Box::new(value),
+                    min: Box::new(min),
+                    max: Box::new(max),
+                })
+            })
+            .boxed(),
+        (cast, proptest::option::of(0..18u32))
+            .prop_map(|(value, decimals)| NumValue::Cast(NumCastValue { value, decimals }))
+            .boxed(),
+        (
+            arb_array(),
+            prop_oneof![
+                Just(AggregateOp::Count),
+                Just(AggregateOp::Sum),
+                Just(AggregateOp::Min),
+                Just(AggregateOp::Max),
+            ],
+            proptest::option::of(arb_selector()),
+            proptest::option::of(arb_cond(depth - 1).prop_map(Box::new)),
+        )
+            .prop_map(|(array, op, selector, condition)| {
+                NumValue::Aggregate(ArrayAggregate {
+                    array,
+                    op,
+                    selector,
+                    condition,
+                })
+            })
+            .boxed(),
+    ])
+    .boxed()
+}
+
+fn arb_num_expr<T: ArbNum>(depth: u32) -> BoxedStrategy<Expr>
+where
+    T::FnOp: Clone + Debug,
+{
+    prop_oneof![
+        (arb_num::<T>(depth), arb_num_op(), arb_num::<T>(depth))
+            .prop_map(|(left, op, right)| T::expr(GenExpr { left, op, right })),
+        (
+            arb_num::<T>(depth),
+            arb_list_op(),
+            arb_list(T::arb_literal())
+        )
+            .prop_map(|(left, op, right)| T::list_expr(ListExpr { left, op, right })),
+    ]
+    .boxed()
+}
+
+fn arb_expr(depth: u32) -> BoxedStrategy<Expr> {
+    let anchor = proptest::option::of(prop_oneof![
+        Just(JobAnchor::Creation),
+        Just(JobAnchor::LastUpdate),
+    ]);
+    let string_op = prop_oneof![
+        Just(StringOp::StartsWith),
+        Just(StringOp::EndsWith),
+        Just(StringOp::Contains),
+        Just(StringOp::Eq),
+        Just(StringOp::Neq),
+        Just(StringOp::EqIgnoreCase),
+        Just(StringOp::NeqIgnoreCase),
+    ];
+    let status_op = prop_oneof![Just(StatusOp::Eq), Just(StatusOp::Neq)];
+    let job_status = prop_oneof![
+        Just(JobStatus::Pending),
+        Just(JobStatus::Executed),
+        Just(JobStatus::Failed),
+        Just(JobStatus::Cancelled),
+        Just(JobStatus::Expired),
+    ];
+    let calendar_field = prop_oneof![
+        Just(CalendarField::DayOfWeek),
+        Just(CalendarField::HourOfDay),
+        Just(CalendarField::DayOfMonth),
+    ];
+
+    Union::new(vec![
+        (arb_string_value(), string_op, arb_string_value())
+            .prop_map(|(left, op, right)| Expr::String(GenExpr { left, op, right }))
+            .boxed(),
+        (arb_string_value(), arb_list_op(), arb_list(arb_text()))
+            .prop_map(|(left, op, right)| Expr::StringList(ListExpr { left, op, right }))
+            .boxed(),
+        arb_num_expr::<Uint256>(depth),
+        arb_num_expr::<i128>(depth),
+        arb_num_expr::<Decimal256>(depth),
+        (any::<u64>(), arb_num_op(), anchor.clone())
+            .prop_map(|(comparator, op, anchor)| {
+                Expr::Timestamp(TimeExpr {
+                    comparator: Uint64::new(comparator),
+                    op,
+                    anchor,
+                })
+            })
+            .boxed(),
+        (any::<u64>(), arb_num_op(), anchor.clone())
+            .prop_map(|(comparator, op, anchor)| {
+                Expr::BlockHeight(BlockExpr {
+                    comparator: Uint64::new(comparator),
+                    op,
+                    anchor,
+                })
+            })
+            .boxed(),
+        (calendar_field, arb_num_op(), any::<u64>())
+            .prop_map(|(field, op, comparator)| {
+                Expr::Calendar(CalendarExpr {
+                    field,
+                    op,
+                    comparator: Uint64::new(comparator),
+                })
+            })
+            .boxed(),
+        (
+            any::<u64>(),
+            anchor.prop_filter_map("anchor", |anchor| anchor),
+            any::<u64>(),
+        )
+            .prop_map(|(period, anchor, tolerance)| {
+                Expr::Period(PeriodExpr {
+                    period: Uint64::new(period),
+                    anchor,
+                    tolerance: Uint64::new(tolerance),
+                })
+            })
+            .boxed(),
+        (any::<u64>(), status_op, job_status)
+            .prop_map(|(job_id, op, status)| {
+                Expr::JobStatus(JobStatusExpr {
+                    job_id: Uint64::new(job_id),
+                    op,
+                    status,
+                })
+            })
+            .boxed(),
+        arb_query().prop_map(Expr::Bool).boxed(),
+        arb_query().prop_map(Expr::Exists).boxed(),
+        arb_query().prop_map(Expr::IsNull).boxed(),
+    ])
+    .boxed()
+}
+
+fn arb_cond(depth: u32) -> BoxedStrategy<Condition> {
+    let leaf = arb_expr(depth).prop_map(Condition::Expr).boxed();
+    if depth == 0 {
+        return leaf;
+    }
+
+    let inner = arb_cond(depth - 1).prop_map(Box::new);
+    Union::new(vec![
+        leaf,
+        prop::collection::vec(inner.clone(), 0..4)
+            .prop_map(Condition::And)
+            .boxed(),
+        prop::collection::vec(inner.clone(), 0..4)
+            .prop_map(Condition::Or)
+            .boxed(),
+        inner.clone().prop_map(Condition::Not).boxed(),
+        (arb_array(), inner.clone())
+            .prop_map(|(array, condition)| Condition::Any(ArrayCondition { array, condition }))
+            .boxed(),
+        (arb_array(), inner)
+            .prop_map(|(array, condition)| Condition::All(ArrayCondition { array, condition }))
+            .boxed(),
+    ])
+    .boxed()
+}
+
+proptest! {
+    #[test]
+    fn test_parse_inverts_print(cond in arb_cond(2)) {
+        let text = print_condition(&cond);
+        prop_assert_eq!(parse_condition(&text), Ok(cond), "{}", text);
+    }
+
+    #[test]
+    fn test_print_inverts_parse(cond in arb_cond(2)) {
+        let text = print_condition(&cond);
+        let parsed = parse_condition(&text).unwrap();
+        prop_assert_eq!(print_condition(&parsed), text);
+    }
+}
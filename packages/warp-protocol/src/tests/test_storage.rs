@@ -0,0 +1,84 @@
+use crate::controller::storage::{KeyPart, StorageKey};
+use cosmwasm_std::{Addr, Uint128, Uint64};
+use cw_storage_plus::{Item, Map};
+
+#[test]
+fn test_storage_key_item() {
+    let item: Item<u64> = Item::new("config");
+
+    assert_eq!(
+        StorageKey::Item {
+            namespace: "config".to_string()
+        }
+        .to_bytes(),
+        Some(item.as_slice().to_vec())
+    );
+}
+
+#[test]
+fn test_storage_key_map() {
+    let by_name: Map<&str, u64> = Map::new("names");
+    assert_eq!(
+        StorageKey::Map {
+            namespace: "names".to_string(),
+            key: vec![KeyPart::String("vlad".to_string())],
+        }
+        .to_bytes(),
+        Some(by_name.key("vlad").to_vec())
+    );
+
+    let by_id: Map<u64, u64> = Map::new("jobs");
+    assert_eq!(
+        StorageKey::Map {
+            namespace: "jobs".to_string(),
+            key: vec![KeyPart::U64(Uint64::new(42))],
+        }
+        .to_bytes(),
+        Some(by_id.key(42).to_vec())
+    );
+
+    let signed: Map<i32, u64> = Map::new("signed");
+    assert_eq!(
+        StorageKey::Map {
+            namespace: "signed".to_string(),
+            key: vec![KeyPart::I32(-7)],
+        }
+        .to_bytes(),
+        Some(signed.key(-7).to_vec())
+    );
+
+    let composite: Map<(&Addr, u128, &str), u64> = Map::new("positions");
+    let addr = Addr::unchecked("terra1vlad");
+    assert_eq!(
+        StorageKey::Map {
+            namespace: "positions".to_string(),
+            key: vec![
+                KeyPart::Addr("terra1vlad".to_string()),
+                KeyPart::U128(Uint128::new(7)),
+                KeyPart::String("uluna".to_string()),
+            ],
+        }
+        .to_bytes(),
+        Some(composite.key((&addr, 7, "uluna")).to_vec())
+    );
+}
+
+#[test]
+fn test_storage_key_invalid() {
+    assert_eq!(
+        StorageKey::Map {
+            namespace: "jobs".to_string(),
+            key: vec![],
+        }
+        .to_bytes(),
+        None
+    );
+    assert_eq!(
+        StorageKey::Map {
+            namespace: "jobs".to_string(),
+            key: vec![KeyPart::String("x".repeat(0x10000)), KeyPart::U8(1)],
+        }
+        .to_bytes(),
+        None
+    );
+}